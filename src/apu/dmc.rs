@@ -0,0 +1,214 @@
+use region::Region;
+
+// In CPU cycles
+const NTSC_RATE_TABLE: [u16; 16] = [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54];
+const PAL_RATE_TABLE: [u16; 16] = [398, 354, 316, 298, 276, 236, 210, 198, 176, 148, 132, 118, 98, 78, 66, 50];
+
+// $4010 - $4013. The delta modulation channel plays 1 bit delta encoded samples straight out of PRG ROM.
+// It can't read memory by itself. When its buffer runs dry it asks for the next byte and the CPU stalls to fetch it
+pub struct Dmc {
+    rate_table: &'static [u16; 16],
+    irq_enabled: bool,
+    irq_pending: bool,
+    looping: bool,
+    timer_period: u16,
+    timer: u16,
+    output_level: u8, // 7 bits
+    sample_address: u16,
+    sample_length: u16,
+    current_address: u16,
+    bytes_remaining: u16,
+    sample_buffer: Option<u8>,
+    shift_register: u8,
+    bits_remaining: u8,
+    silenced: bool
+}
+
+impl Dmc {
+    pub fn new(region: Region) -> Dmc {
+        let rate_table = if region == Region::Pal { &PAL_RATE_TABLE } else { &NTSC_RATE_TABLE };
+        return Dmc {
+            rate_table: rate_table,
+            irq_enabled: false,
+            irq_pending: false,
+            looping: false,
+            timer_period: rate_table[0],
+            timer: 0,
+            output_level: 0,
+            sample_address: 0xC000,
+            sample_length: 1,
+            current_address: 0xC000,
+            bytes_remaining: 0,
+            sample_buffer: None,
+            shift_register: 0,
+            bits_remaining: 8,
+            silenced: true
+        }
+    }
+
+    // Register is 0 - 3, relative to $4010
+    pub fn write_register(&mut self, register: u16, value: u8) {
+        match register {
+            0 => {
+                self.irq_enabled = (value & 0x80) == 0x80;
+                if !self.irq_enabled {
+                    self.irq_pending = false;
+                }
+                self.looping = (value & 0x40) == 0x40;
+                self.timer_period = self.rate_table[(value & 0x0F) as usize];
+            },
+            1 => self.output_level = value & 0x7F,
+            2 => self.sample_address = 0xC000 + value as u16 * 64,
+            3 => self.sample_length = value as u16 * 16 + 1,
+            _ => panic!("The DMC only has 4 registers. Got {}", register)
+        }
+    }
+
+    // Writing to $4015 always acknowledges the DMC's interrupt
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.irq_pending = false;
+        if !is_enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart_sample();
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        return self.bytes_remaining > 0;
+    }
+
+    pub fn is_irq_pending(&self) -> bool {
+        return self.irq_pending;
+    }
+
+    fn restart_sample(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    // The address the CPU should read on our behalf, if the sample buffer needs filling
+    pub fn get_sample_request(&self) -> Option<u16> {
+        if self.sample_buffer.is_none() && self.bytes_remaining > 0 {
+            return Some(self.current_address);
+        }
+        return None;
+    }
+
+    pub fn receive_sample(&mut self, value: u8) {
+        self.sample_buffer = Some(value);
+        // The address wraps around to $8000 rather than $0000
+        self.current_address = if self.current_address == 0xFFFF { 0x8000 } else { self.current_address + 1 };
+        self.bytes_remaining -= 1;
+
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart_sample();
+            } else if self.irq_enabled {
+                self.irq_pending = true;
+            }
+        }
+    }
+
+    // Clocked every CPU cycle. The rate table is already in CPU cycles
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+        self.timer = self.timer_period - 1;
+
+        if !self.silenced {
+            // Each bit nudges the output up or down by 2, as long as it wouldn't go out of range
+            if (self.shift_register & 1) == 1 {
+                if self.output_level <= 125 {
+                    self.output_level += 2;
+                }
+            } else if self.output_level >= 2 {
+                self.output_level -= 2;
+            }
+        }
+        self.shift_register >>= 1;
+
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+            match self.sample_buffer.take() {
+                Some(sample) => {
+                    self.silenced = false;
+                    self.shift_register = sample;
+                },
+                None => self.silenced = true
+            }
+        }
+    }
+
+    // 0 - 127
+    pub fn get_output(&self) -> u8 {
+        return self.output_level;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::dmc::Dmc;
+    use region::Region;
+
+    // Runs the timer until the output unit has consumed 8 bits
+    fn play_byte(dmc: &mut Dmc) {
+        for _ in 0..(8 * dmc.timer_period) {
+            dmc.clock_timer();
+        }
+    }
+
+    #[test]
+    fn requests_samples_from_the_sample_address() {
+        let mut dmc = Dmc::new(Region::Ntsc);
+        dmc.write_register(2, 0x01);
+        dmc.write_register(3, 0x00); // 1 byte long
+        assert_eq!(dmc.get_sample_request(), None);
+
+        dmc.set_enabled(true);
+        assert_eq!(dmc.get_sample_request(), Some(0xC040));
+
+        dmc.receive_sample(0xFF);
+        assert_eq!(dmc.get_sample_request(), None);
+        assert_eq!(dmc.is_active(), false);
+    }
+
+    #[test]
+    fn irq_is_raised_when_a_sample_finishes() {
+        let mut dmc = Dmc::new(Region::Ntsc);
+        dmc.write_register(0, 0x80);
+        dmc.set_enabled(true);
+        dmc.receive_sample(0);
+        assert_eq!(dmc.is_irq_pending(), true);
+
+        dmc.set_enabled(true);
+        assert_eq!(dmc.is_irq_pending(), false);
+    }
+
+    #[test]
+    fn looping_samples_restart() {
+        let mut dmc = Dmc::new(Region::Ntsc);
+        dmc.write_register(0, 0xC0);
+        dmc.set_enabled(true);
+        dmc.receive_sample(0);
+        assert_eq!(dmc.is_irq_pending(), false);
+        assert_eq!(dmc.is_active(), true);
+    }
+
+    #[test]
+    fn output_level_follows_the_sample_bits() {
+        let mut dmc = Dmc::new(Region::Ntsc);
+        dmc.write_register(1, 64);
+        dmc.set_enabled(true);
+        dmc.receive_sample(0b0000_0111);
+
+        play_byte(&mut dmc); // Starts silent, then loads the buffered byte into the shift register
+        assert_eq!(dmc.get_output(), 64);
+
+        play_byte(&mut dmc);
+        assert_eq!(dmc.get_output(), 64 + 6 - 10);
+    }
+}
@@ -0,0 +1,95 @@
+// Controls the volume of the pulse and noise channels. Either a constant volume, or a sawtooth that decays from 15 to 0
+// Clocked by the frame counter's quarter frame signal
+pub struct Envelope {
+    start: bool, // Set by writing to the channel's length register. Restarts the decay on the next clock
+    divider: u8,
+    decay_level: u8,
+    looping: bool, // Shares a bit with the length counter halt flag
+    constant_volume: bool,
+    volume: u8 // The constant volume, or the divider's period when decaying
+}
+
+impl Envelope {
+    pub fn new() -> Envelope {
+        return Envelope {
+            start: false,
+            divider: 0,
+            decay_level: 0,
+            looping: false,
+            constant_volume: false,
+            volume: 0
+        }
+    }
+
+    // Takes the channel's first register ($4000, $4004 or $400C). Only the lower 6 bits matter here
+    pub fn write_control(&mut self, value: u8) {
+        self.looping = (value & 0x20) == 0x20;
+        self.constant_volume = (value & 0x10) == 0x10;
+        self.volume = value & 0x0F;
+    }
+
+    pub fn restart(&mut self) {
+        self.start = true;
+    }
+
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay_level = 15;
+            self.divider = self.volume;
+        } else if self.divider == 0 {
+            self.divider = self.volume;
+            if self.decay_level > 0 {
+                self.decay_level -= 1;
+            } else if self.looping {
+                self.decay_level = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    pub fn get_volume(&self) -> u8 {
+        if self.constant_volume {
+            return self.volume;
+        } else {
+            return self.decay_level;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::envelope::Envelope;
+
+    #[test]
+    fn constant_volume() {
+        let mut envelope = Envelope::new();
+        envelope.write_control(0b0001_0111);
+        envelope.restart();
+        envelope.clock();
+        assert_eq!(envelope.get_volume(), 7);
+    }
+
+    #[test]
+    fn decays_once_per_period_and_loops() {
+        let mut envelope = Envelope::new();
+        envelope.write_control(0b0010_0001); // Looping, divider period of 1 (so every 2 clocks)
+        envelope.restart();
+        envelope.clock();
+        assert_eq!(envelope.get_volume(), 15);
+
+        envelope.clock();
+        envelope.clock();
+        assert_eq!(envelope.get_volume(), 14);
+
+        for _ in 0..28 {
+            envelope.clock();
+        }
+        assert_eq!(envelope.get_volume(), 0);
+
+        envelope.clock();
+        envelope.clock();
+        assert_eq!(envelope.get_volume(), 15);
+    }
+}
@@ -0,0 +1,158 @@
+use region::Region;
+
+// Which of the channels' slower units need to be clocked. A half frame always comes with a quarter frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameStep {
+    None,
+    Quarter, // Envelopes and the triangle's linear counter
+    Half // Length counters and sweeps, on top of everything a quarter frame clocks
+}
+
+// The CPU cycles (counted from when $4017 was last written) that each step happens on
+struct SequenceTiming {
+    quarter_frames: [u32; 2],
+    half_frames: [u32; 2],
+    length: u32
+}
+
+const NTSC_FOUR_STEP: SequenceTiming = SequenceTiming { quarter_frames: [7457, 22371], half_frames: [14913, 29829], length: 29830 };
+const NTSC_FIVE_STEP: SequenceTiming = SequenceTiming { quarter_frames: [7457, 22371], half_frames: [14913, 37281], length: 37282 };
+// PAL's CPU is slower, so it takes more cycles to keep the sequence running at about the same speed
+const PAL_FOUR_STEP: SequenceTiming = SequenceTiming { quarter_frames: [8313, 24939], half_frames: [16627, 33253], length: 33254 };
+const PAL_FIVE_STEP: SequenceTiming = SequenceTiming { quarter_frames: [8313, 24939], half_frames: [16627, 41565], length: 41566 };
+
+// $4017. Drives the envelopes, sweeps and length counters at roughly 240Hz, and can interrupt the CPU once per sequence
+pub struct FrameCounter {
+    four_step_timing: &'static SequenceTiming,
+    five_step_timing: &'static SequenceTiming,
+    cycle: u32,
+    five_step_mode: bool, // The 5 step sequence is longer and never raises an IRQ
+    irq_inhibited: bool,
+    irq_pending: bool
+}
+
+impl FrameCounter {
+    pub fn new(region: Region) -> FrameCounter {
+        // The Dendy's APU runs on NTSC timing, even though the rest of the console is closer to PAL
+        let (four_step_timing, five_step_timing) = match region {
+            Region::Pal => (&PAL_FOUR_STEP, &PAL_FIVE_STEP),
+            Region::Ntsc | Region::Dendy => (&NTSC_FOUR_STEP, &NTSC_FIVE_STEP)
+        };
+
+        return FrameCounter {
+            four_step_timing: four_step_timing,
+            five_step_timing: five_step_timing,
+            cycle: 0,
+            five_step_mode: false,
+            irq_inhibited: false,
+            irq_pending: false
+        }
+    }
+
+    // Restarts the sequence. Switching to the 5 step sequence clocks everything immediately
+    pub fn write(&mut self, value: u8) -> FrameStep {
+        self.five_step_mode = (value & 0x80) == 0x80;
+        self.irq_inhibited = (value & 0x40) == 0x40;
+        if self.irq_inhibited {
+            self.irq_pending = false;
+        }
+        self.cycle = 0;
+
+        return if self.five_step_mode { FrameStep::Half } else { FrameStep::None };
+    }
+
+    pub fn is_irq_pending(&self) -> bool {
+        return self.irq_pending;
+    }
+
+    // Reading $4015 acknowledges the interrupt
+    pub fn acknowledge_irq(&mut self) {
+        self.irq_pending = false;
+    }
+
+    // Clocked every CPU cycle
+    pub fn tick(&mut self) -> FrameStep {
+        self.cycle += 1;
+
+        let timing = if self.five_step_mode { self.five_step_timing } else { self.four_step_timing };
+        let length = timing.length;
+
+        // The IRQ flag is set over the last 3 cycles of the 4 step sequence
+        if !self.five_step_mode && !self.irq_inhibited && self.cycle >= length - 2 {
+            self.irq_pending = true;
+        }
+
+        let step;
+        if timing.half_frames.contains(&self.cycle) {
+            step = FrameStep::Half;
+        } else if timing.quarter_frames.contains(&self.cycle) {
+            step = FrameStep::Quarter;
+        } else {
+            step = FrameStep::None;
+        }
+
+        if self.cycle >= length {
+            self.cycle = 0;
+        }
+        return step;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::frame_counter::FrameCounter;
+    use apu::frame_counter::FrameStep;
+    use region::Region;
+
+    // Returns the cycle number of every step that happened, in order
+    fn run_sequence(frame_counter: &mut FrameCounter, cycles: u32) -> Vec<(u32, FrameStep)> {
+        let mut steps = vec!();
+        for cycle in 1..(cycles + 1) {
+            let step = frame_counter.tick();
+            if step != FrameStep::None {
+                steps.push((cycle, step));
+            }
+        }
+        return steps;
+    }
+
+    #[test]
+    fn four_step_sequence() {
+        let mut frame_counter = FrameCounter::new(Region::Ntsc);
+        let steps = run_sequence(&mut frame_counter, 29830);
+        assert_eq!(steps, vec![(7457, FrameStep::Quarter), (14913, FrameStep::Half), (22371, FrameStep::Quarter), (29829, FrameStep::Half)]);
+        assert_eq!(frame_counter.is_irq_pending(), true);
+
+        frame_counter.acknowledge_irq();
+        assert_eq!(frame_counter.is_irq_pending(), false);
+    }
+
+    #[test]
+    fn five_step_sequence_clocks_on_write_and_never_interrupts() {
+        let mut frame_counter = FrameCounter::new(Region::Ntsc);
+        assert_eq!(frame_counter.write(0x80), FrameStep::Half);
+
+        let steps = run_sequence(&mut frame_counter, 37282);
+        assert_eq!(steps, vec![(7457, FrameStep::Quarter), (14913, FrameStep::Half), (22371, FrameStep::Quarter), (37281, FrameStep::Half)]);
+        assert_eq!(frame_counter.is_irq_pending(), false);
+    }
+
+    #[test]
+    fn irq_can_be_inhibited() {
+        let mut frame_counter = FrameCounter::new(Region::Ntsc);
+        run_sequence(&mut frame_counter, 29830);
+        frame_counter.write(0x40);
+        assert_eq!(frame_counter.is_irq_pending(), false);
+
+        run_sequence(&mut frame_counter, 29830);
+        assert_eq!(frame_counter.is_irq_pending(), false);
+    }
+
+    #[test]
+    fn pal_sequence_takes_longer() {
+        let mut frame_counter = FrameCounter::new(Region::Pal);
+        let steps = run_sequence(&mut frame_counter, 33254);
+        assert_eq!(steps, vec![(8313, FrameStep::Quarter), (16627, FrameStep::Half), (24939, FrameStep::Quarter), (33253, FrameStep::Half)]);
+        assert_eq!(frame_counter.is_irq_pending(), true);
+    }
+}
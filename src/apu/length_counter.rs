@@ -0,0 +1,80 @@
+// Silences a channel after a set amount of time. Clocked by the frame counter's half frame signal
+// The value written to a channel's length register is an index into this table, not the length itself
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30
+];
+
+pub struct LengthCounter {
+    counter: u8,
+    halted: bool,
+    enabled: bool // Controlled by $4015. While disabled the counter is held at 0
+}
+
+impl LengthCounter {
+    pub fn new() -> LengthCounter {
+        return LengthCounter {
+            counter: 0,
+            halted: false,
+            enabled: false
+        }
+    }
+
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.enabled = is_enabled;
+        if !is_enabled {
+            self.counter = 0;
+        }
+    }
+
+    pub fn set_halted(&mut self, is_halted: bool) {
+        self.halted = is_halted;
+    }
+
+    pub fn load(&mut self, table_index: u8) {
+        if self.enabled {
+            self.counter = LENGTH_TABLE[(table_index & 0x1F) as usize];
+        }
+    }
+
+    pub fn clock(&mut self) {
+        if !self.halted && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        return self.counter > 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::length_counter::LengthCounter;
+
+    #[test]
+    fn loads_are_ignored_while_disabled() {
+        let mut length_counter = LengthCounter::new();
+        length_counter.load(1);
+        assert_eq!(length_counter.is_active(), false);
+
+        length_counter.set_enabled(true);
+        length_counter.load(3); // Length of 2
+        length_counter.clock();
+        assert_eq!(length_counter.is_active(), true);
+        length_counter.clock();
+        assert_eq!(length_counter.is_active(), false);
+    }
+
+    #[test]
+    fn halting_stops_the_count() {
+        let mut length_counter = LengthCounter::new();
+        length_counter.set_enabled(true);
+        length_counter.load(3);
+        length_counter.set_halted(true);
+        for _ in 0..10 {
+            length_counter.clock();
+        }
+        assert_eq!(length_counter.is_active(), true);
+    }
+}
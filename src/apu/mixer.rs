@@ -0,0 +1,39 @@
+// Combines the channels the same way the NES's resistor network does. The result is in the range of 0.0 - 1.0
+// The pulse channels share one DAC and the other three share a second one, so neither is a simple sum
+// https://wiki.nesdev.com/w/index.php/APU_Mixer
+pub fn mix(pulse1: u8, pulse2: u8, triangle: u8, noise: u8, dmc: u8) -> f32 {
+    let mut pulse_out = 0.0;
+    let pulse_sum = pulse1 as f32 + pulse2 as f32;
+    if pulse_sum > 0.0 {
+        pulse_out = 95.88 / (8128.0 / pulse_sum + 100.0);
+    }
+
+    let mut tnd_out = 0.0;
+    let tnd_sum = triangle as f32 / 8227.0 + noise as f32 / 12241.0 + dmc as f32 / 22638.0;
+    if tnd_sum > 0.0 {
+        tnd_out = 159.79 / (1.0 / tnd_sum + 100.0);
+    }
+
+    return pulse_out + tnd_out;
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::mixer::mix;
+
+    #[test]
+    fn silence_is_zero() {
+        assert_eq!(mix(0, 0, 0, 0, 0), 0.0);
+    }
+
+    #[test]
+    fn mixing_is_nonlinear() {
+        let one_pulse = mix(15, 0, 0, 0, 0);
+        let two_pulses = mix(15, 15, 0, 0, 0);
+        assert!(two_pulses < one_pulse * 2.0);
+
+        // Everything at full volume comes out just under 1
+        let everything = mix(15, 15, 15, 15, 127);
+        assert!(everything > 0.95 && everything <= 1.0);
+    }
+}
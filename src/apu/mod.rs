@@ -0,0 +1,190 @@
+mod envelope;
+mod length_counter;
+mod pulse;
+mod triangle;
+mod noise;
+mod dmc;
+mod frame_counter;
+mod mixer;
+
+use self::pulse::Pulse;
+use self::triangle::Triangle;
+use self::noise::Noise;
+use self::dmc::Dmc;
+use self::frame_counter::FrameCounter;
+use self::frame_counter::FrameStep;
+use region::Region;
+
+const STATUS_REGISTER: u16 = 0x4015;
+const FRAME_COUNTER_REGISTER: u16 = 0x4017;
+
+// The Audio Processing Unit. Lives inside the CPU on real hardware, so it runs off the CPU's clock
+pub struct APU {
+    pulse1: Pulse,
+    pulse2: Pulse,
+    triangle: Triangle,
+    noise: Noise,
+    dmc: Dmc,
+    frame_counter: FrameCounter,
+    odd_cycle: bool // The pulse channels are only clocked every other CPU cycle
+}
+
+impl APU {
+    pub fn new(region: Region) -> APU {
+        return APU {
+            pulse1: Pulse::new(true),
+            pulse2: Pulse::new(false),
+            triangle: Triangle::new(),
+            noise: Noise::new(region),
+            dmc: Dmc::new(region),
+            frame_counter: FrameCounter::new(region),
+            odd_cycle: false
+        }
+    }
+
+    // Called once for every CPU cycle
+    pub fn tick(&mut self) {
+        let frame_step = self.frame_counter.tick();
+        self.clock_frame_step(frame_step);
+
+        if self.odd_cycle {
+            self.pulse1.clock_timer();
+            self.pulse2.clock_timer();
+        }
+        self.odd_cycle = !self.odd_cycle;
+
+        self.triangle.clock_timer();
+        self.noise.clock_timer();
+        self.dmc.clock_timer();
+    }
+
+    fn clock_frame_step(&mut self, frame_step: FrameStep) {
+        if frame_step == FrameStep::None {
+            return;
+        }
+
+        self.pulse1.clock_quarter_frame();
+        self.pulse2.clock_quarter_frame();
+        self.triangle.clock_quarter_frame();
+        self.noise.clock_quarter_frame();
+
+        if frame_step == FrameStep::Half {
+            self.pulse1.clock_half_frame();
+            self.pulse2.clock_half_frame();
+            self.triangle.clock_half_frame();
+            self.noise.clock_half_frame();
+        }
+    }
+
+    pub fn is_register_address(address: u16) -> bool {
+        return (address >= 0x4000 && address <= 0x4013) || address == STATUS_REGISTER || address == FRAME_COUNTER_REGISTER;
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x4000..=0x4003 => self.pulse1.write_register(address - 0x4000, value),
+            0x4004..=0x4007 => self.pulse2.write_register(address - 0x4004, value),
+            0x4008..=0x400B => self.triangle.write_register(address - 0x4008, value),
+            0x400C..=0x400F => self.noise.write_register(address - 0x400C, value),
+            0x4010..=0x4013 => self.dmc.write_register(address - 0x4010, value),
+            STATUS_REGISTER => {
+                self.pulse1.set_enabled((value & 0b0000_0001) != 0);
+                self.pulse2.set_enabled((value & 0b0000_0010) != 0);
+                self.triangle.set_enabled((value & 0b0000_0100) != 0);
+                self.noise.set_enabled((value & 0b0000_1000) != 0);
+                self.dmc.set_enabled((value & 0b0001_0000) != 0);
+            },
+            FRAME_COUNTER_REGISTER => {
+                let frame_step = self.frame_counter.write(value);
+                self.clock_frame_step(frame_step);
+            },
+            _ => panic!("Address {:X} is not an APU register", address)
+        }
+    }
+
+    // $4015 is the only register that can be read. Reading it acknowledges the frame counter's interrupt
+    pub fn read_status(&mut self) -> u8 {
+        let mut status = 0;
+        if self.pulse1.is_length_counter_active() { status |= 0b0000_0001; }
+        if self.pulse2.is_length_counter_active() { status |= 0b0000_0010; }
+        if self.triangle.is_length_counter_active() { status |= 0b0000_0100; }
+        if self.noise.is_length_counter_active() { status |= 0b0000_1000; }
+        if self.dmc.is_active() { status |= 0b0001_0000; }
+        if self.frame_counter.is_irq_pending() { status |= 0b0100_0000; }
+        if self.dmc.is_irq_pending() { status |= 0b1000_0000; }
+
+        self.frame_counter.acknowledge_irq();
+        return status;
+    }
+
+    pub fn is_irq_asserted(&self) -> bool {
+        return self.frame_counter.is_irq_pending() || self.dmc.is_irq_pending();
+    }
+
+    // The DMC needs the CPU to read its next sample byte from memory
+    pub fn get_dmc_sample_request(&self) -> Option<u16> {
+        return self.dmc.get_sample_request();
+    }
+
+    pub fn receive_dmc_sample(&mut self, value: u8) {
+        self.dmc.receive_sample(value);
+    }
+
+    // The current level of the audio signal, from 0.0 - 1.0
+    pub fn get_output(&self) -> f32 {
+        return mixer::mix(
+            self.pulse1.get_output(),
+            self.pulse2.get_output(),
+            self.triangle.get_output(),
+            self.noise.get_output(),
+            self.dmc.get_output()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::APU;
+    use region::Region;
+
+    #[test]
+    fn status_reports_active_length_counters() {
+        let mut apu = APU::new(Region::Ntsc);
+        apu.write_register(0x4015, 0b0000_0101);
+        apu.write_register(0x4003, 0b0000_1000);
+        apu.write_register(0x4007, 0b0000_1000); // Pulse 2 is disabled so this is ignored
+        apu.write_register(0x400B, 0b0000_1000);
+        assert_eq!(apu.read_status(), 0b0000_0101);
+
+        apu.write_register(0x4015, 0);
+        assert_eq!(apu.read_status(), 0);
+    }
+
+    #[test]
+    fn reading_status_acknowledges_the_frame_interrupt() {
+        let mut apu = APU::new(Region::Ntsc);
+        for _ in 0..29830 {
+            apu.tick();
+        }
+        assert_eq!(apu.is_irq_asserted(), true);
+        assert_eq!(apu.read_status() & 0x40, 0x40);
+        assert_eq!(apu.is_irq_asserted(), false);
+        assert_eq!(apu.read_status() & 0x40, 0);
+    }
+
+    #[test]
+    fn pulse_is_audible_once_enabled() {
+        let mut apu = APU::new(Region::Ntsc);
+        apu.write_register(0x4015, 0b0000_0001);
+        apu.write_register(0x4000, 0b1011_1111);
+        apu.write_register(0x4002, 0x40);
+        apu.write_register(0x4003, 0b0000_1000);
+
+        let mut heard_something = false;
+        for _ in 0..1000 {
+            apu.tick();
+            heard_something |= apu.get_output() > 0.0;
+        }
+        assert_eq!(heard_something, true);
+    }
+}
@@ -0,0 +1,133 @@
+use apu::envelope::Envelope;
+use apu::length_counter::LengthCounter;
+use region::Region;
+
+// In CPU cycles
+const NTSC_PERIOD_TABLE: [u16; 16] = [4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068];
+const PAL_PERIOD_TABLE: [u16; 16] = [4, 8, 14, 30, 60, 88, 118, 148, 188, 236, 354, 472, 708, 944, 1890, 3778];
+
+// $400C - $400F. Pseudo-random noise from a 15 bit linear feedback shift register
+pub struct Noise {
+    period_table: &'static [u16; 16],
+    shift_register: u16,
+    short_mode: bool, // Feeds back from bit 6 instead of bit 1, giving a much shorter (and more metallic) sequence
+    timer_period: u16,
+    timer: u16,
+    envelope: Envelope,
+    length_counter: LengthCounter
+}
+
+impl Noise {
+    pub fn new(region: Region) -> Noise {
+        // PAL's slower CPU clock needs shorter periods to hit the same pitches. The Dendy doesn't bother
+        let period_table = if region == Region::Pal { &PAL_PERIOD_TABLE } else { &NTSC_PERIOD_TABLE };
+        return Noise {
+            period_table: period_table,
+            shift_register: 1,
+            short_mode: false,
+            timer_period: period_table[0],
+            timer: 0,
+            envelope: Envelope::new(),
+            length_counter: LengthCounter::new()
+        }
+    }
+
+    // Register is 0 - 3, relative to $400C. $400D is unused
+    pub fn write_register(&mut self, register: u16, value: u8) {
+        match register {
+            0 => {
+                self.length_counter.set_halted((value & 0x20) == 0x20);
+                self.envelope.write_control(value);
+            },
+            1 => {},
+            2 => {
+                self.short_mode = (value & 0x80) == 0x80;
+                self.timer_period = self.period_table[(value & 0x0F) as usize];
+            },
+            3 => {
+                self.length_counter.load(value >> 3);
+                self.envelope.restart();
+            },
+            _ => panic!("The noise channel only has 4 registers. Got {}", register)
+        }
+    }
+
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.length_counter.set_enabled(is_enabled);
+    }
+
+    pub fn is_length_counter_active(&self) -> bool {
+        return self.length_counter.is_active();
+    }
+
+    // Clocked every CPU cycle. The period table is already in CPU cycles
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+
+        self.timer = self.timer_period - 1;
+        let other_bit = if self.short_mode { 6 } else { 1 };
+        let feedback = (self.shift_register & 1) ^ ((self.shift_register >> other_bit) & 1);
+        self.shift_register = (self.shift_register >> 1) | (feedback << 14);
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+    }
+
+    // 0 - 15
+    pub fn get_output(&self) -> u8 {
+        if !self.length_counter.is_active() || (self.shift_register & 1) == 1 {
+            return 0;
+        }
+        return self.envelope.get_volume();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::noise::Noise;
+    use region::Region;
+
+    // Counts how many times the timer has to fire before the shift register gets back to where it started
+    fn get_sequence_length(noise: &mut Noise) -> u32 {
+        let starting_value = noise.shift_register;
+        let mut length = 0;
+        loop {
+            noise.timer = 0;
+            noise.clock_timer();
+            length += 1;
+            if noise.shift_register == starting_value {
+                return length;
+            }
+        }
+    }
+
+    #[test]
+    fn shift_register_sequence_lengths() {
+        let mut noise = Noise::new(Region::Ntsc);
+        assert_eq!(get_sequence_length(&mut noise), 32767);
+
+        noise.write_register(2, 0x80);
+        assert_eq!(get_sequence_length(&mut noise), 93);
+    }
+
+    #[test]
+    fn output_uses_the_envelope_when_bit_0_is_clear() {
+        let mut noise = Noise::new(Region::Ntsc);
+        noise.set_enabled(true);
+        noise.write_register(0, 0b0001_1001); // Constant volume of 9
+        noise.write_register(3, 0b0000_1000);
+
+        noise.shift_register = 0b10;
+        assert_eq!(noise.get_output(), 9);
+        noise.shift_register = 0b01;
+        assert_eq!(noise.get_output(), 0);
+    }
+}
@@ -0,0 +1,201 @@
+use apu::envelope::Envelope;
+use apu::length_counter::LengthCounter;
+
+// Which of the 8 steps of the waveform output a high signal, for each of the 4 duty cycles
+const DUTY_TABLE: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0], // 12.5%
+    [0, 1, 1, 0, 0, 0, 0, 0], // 25%
+    [0, 1, 1, 1, 1, 0, 0, 0], // 50%
+    [1, 0, 0, 1, 1, 1, 1, 1]  // 25% negated
+];
+
+// $4000 - $4003 and $4004 - $4007. A square wave with a variable duty cycle, volume envelope, and a sweep unit
+// that can bend the pitch up or down over time
+pub struct Pulse {
+    is_first_channel: bool, // The two channels negate the sweep slightly differently
+    duty: u8,
+    sequence_step: u8,
+    timer_period: u16, // 11 bits
+    timer: u16,
+    envelope: Envelope,
+    length_counter: LengthCounter,
+    sweep_enabled: bool,
+    sweep_period: u8,
+    sweep_negate: bool,
+    sweep_shift: u8,
+    sweep_reload: bool,
+    sweep_divider: u8
+}
+
+impl Pulse {
+    pub fn new(is_first_channel: bool) -> Pulse {
+        return Pulse {
+            is_first_channel: is_first_channel,
+            duty: 0,
+            sequence_step: 0,
+            timer_period: 0,
+            timer: 0,
+            envelope: Envelope::new(),
+            length_counter: LengthCounter::new(),
+            sweep_enabled: false,
+            sweep_period: 0,
+            sweep_negate: false,
+            sweep_shift: 0,
+            sweep_reload: false,
+            sweep_divider: 0
+        }
+    }
+
+    // Register is 0 - 3, relative to the start of the channel's registers
+    pub fn write_register(&mut self, register: u16, value: u8) {
+        match register {
+            0 => {
+                self.duty = value >> 6;
+                self.length_counter.set_halted((value & 0x20) == 0x20);
+                self.envelope.write_control(value);
+            },
+            1 => {
+                self.sweep_enabled = (value & 0x80) == 0x80;
+                self.sweep_period = (value >> 4) & 0b0111;
+                self.sweep_negate = (value & 0x08) == 0x08;
+                self.sweep_shift = value & 0b0111;
+                self.sweep_reload = true;
+            },
+            2 => self.timer_period = (self.timer_period & 0x700) | value as u16,
+            3 => {
+                self.timer_period = (self.timer_period & 0xFF) | ((value as u16 & 0b0111) << 8);
+                self.length_counter.load(value >> 3);
+                self.sequence_step = 0;
+                self.envelope.restart();
+            },
+            _ => panic!("Pulse channels only have 4 registers. Got {}", register)
+        }
+    }
+
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.length_counter.set_enabled(is_enabled);
+    }
+
+    pub fn is_length_counter_active(&self) -> bool {
+        return self.length_counter.is_active();
+    }
+
+    // Clocked every other CPU cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.sequence_step = (self.sequence_step + 1) % 8;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+
+        if self.sweep_divider == 0 && self.sweep_enabled && self.sweep_shift > 0 && !self.is_muted_by_sweep() {
+            self.timer_period = self.get_sweep_target_period();
+        }
+
+        if self.sweep_divider == 0 || self.sweep_reload {
+            self.sweep_divider = self.sweep_period;
+            self.sweep_reload = false;
+        } else {
+            self.sweep_divider -= 1;
+        }
+    }
+
+    // The sweep unit is always calculating its target, even when disabled. Which means it can mute the channel regardless
+    fn get_sweep_target_period(&self) -> u16 {
+        let change = self.timer_period >> self.sweep_shift;
+        if !self.sweep_negate {
+            return self.timer_period + change;
+        }
+
+        // The first channel uses one's complement, so it subtracts an extra 1
+        let extra = if self.is_first_channel { 1 } else { 0 };
+        return self.timer_period.saturating_sub(change + extra);
+    }
+
+    fn is_muted_by_sweep(&self) -> bool {
+        return self.timer_period < 8 || self.get_sweep_target_period() > 0x7FF;
+    }
+
+    // 0 - 15
+    pub fn get_output(&self) -> u8 {
+        if !self.length_counter.is_active() || self.is_muted_by_sweep() {
+            return 0;
+        }
+        if DUTY_TABLE[self.duty as usize][self.sequence_step as usize] == 0 {
+            return 0;
+        }
+        return self.envelope.get_volume();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::pulse::Pulse;
+
+    fn create_playing_pulse(is_first_channel: bool, timer_period: u16) -> Pulse {
+        let mut pulse = Pulse::new(is_first_channel);
+        pulse.set_enabled(true);
+        pulse.write_register(0, 0b1011_1111); // 50% duty, halted length, constant volume of 15
+        pulse.write_register(2, timer_period as u8);
+        pulse.write_register(3, (timer_period >> 8) as u8);
+        return pulse;
+    }
+
+    #[test]
+    fn waveform_follows_the_duty_cycle() {
+        let mut pulse = create_playing_pulse(true, 8);
+        pulse.clock_quarter_frame();
+
+        let mut waveform = vec!();
+        for _ in 0..8 {
+            // The timer counts down from the period, so it takes period + 1 clocks to advance a step
+            for _ in 0..9 {
+                pulse.clock_timer();
+            }
+            waveform.push(pulse.get_output());
+        }
+        assert_eq!(waveform, vec![15, 15, 15, 15, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn low_periods_are_muted() {
+        let mut pulse = create_playing_pulse(true, 7);
+        for _ in 0..16 {
+            pulse.clock_timer();
+            assert_eq!(pulse.get_output(), 0);
+        }
+    }
+
+    #[test]
+    fn sweep_negation_differs_between_channels() {
+        let mut pulse1 = create_playing_pulse(true, 0x100);
+        let mut pulse2 = create_playing_pulse(false, 0x100);
+        pulse1.write_register(1, 0b1000_1001); // Enabled, period 0, negate, shift 1
+        pulse2.write_register(1, 0b1000_1001);
+
+        pulse1.clock_half_frame();
+        pulse2.clock_half_frame();
+        assert_eq!(pulse1.timer_period, 0x7F);
+        assert_eq!(pulse2.timer_period, 0x80);
+    }
+
+    #[test]
+    fn sweeps_that_overflow_mute_the_channel() {
+        let mut pulse = create_playing_pulse(false, 0x700);
+        pulse.write_register(1, 0b0000_0001); // Disabled, but the target is still 0x700 + 0x380
+        pulse.clock_quarter_frame();
+        for _ in 0..16 {
+            pulse.clock_timer();
+            assert_eq!(pulse.get_output(), 0);
+        }
+    }
+}
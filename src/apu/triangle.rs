@@ -0,0 +1,132 @@
+use apu::length_counter::LengthCounter;
+
+// The triangle doesn't have volume control. It steps through this sequence at the timer's rate
+const TRIANGLE_SEQUENCE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15
+];
+
+// $4008 - $400B. Also has a linear counter, which works like a finer grained length counter
+pub struct Triangle {
+    sequence_step: u8,
+    timer_period: u16,
+    timer: u16,
+    length_counter: LengthCounter,
+    control_flag: bool, // Halts the length counter and keeps the linear counter reloading
+    linear_counter: u8,
+    linear_counter_reload_value: u8,
+    linear_counter_reload: bool
+}
+
+impl Triangle {
+    pub fn new() -> Triangle {
+        return Triangle {
+            sequence_step: 0,
+            timer_period: 0,
+            timer: 0,
+            length_counter: LengthCounter::new(),
+            control_flag: false,
+            linear_counter: 0,
+            linear_counter_reload_value: 0,
+            linear_counter_reload: false
+        }
+    }
+
+    // Register is 0 - 3, relative to $4008. $4009 is unused
+    pub fn write_register(&mut self, register: u16, value: u8) {
+        match register {
+            0 => {
+                self.control_flag = (value & 0x80) == 0x80;
+                self.length_counter.set_halted(self.control_flag);
+                self.linear_counter_reload_value = value & 0x7F;
+            },
+            1 => {},
+            2 => self.timer_period = (self.timer_period & 0x700) | value as u16,
+            3 => {
+                self.timer_period = (self.timer_period & 0xFF) | ((value as u16 & 0b0111) << 8);
+                self.length_counter.load(value >> 3);
+                self.linear_counter_reload = true;
+            },
+            _ => panic!("The triangle channel only has 4 registers. Got {}", register)
+        }
+    }
+
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.length_counter.set_enabled(is_enabled);
+    }
+
+    pub fn is_length_counter_active(&self) -> bool {
+        return self.length_counter.is_active();
+    }
+
+    // Unlike the other channels, this is clocked every CPU cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            // Periods this low are ultrasonic. Real hardware outputs them anyway, but they just come out as pops
+            if self.linear_counter > 0 && self.length_counter.is_active() && self.timer_period >= 2 {
+                self.sequence_step = (self.sequence_step + 1) % 32;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        if self.linear_counter_reload {
+            self.linear_counter = self.linear_counter_reload_value;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+
+        if !self.control_flag {
+            self.linear_counter_reload = false;
+        }
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+    }
+
+    // 0 - 15. When silenced the triangle holds its last value instead of dropping to 0
+    pub fn get_output(&self) -> u8 {
+        return TRIANGLE_SEQUENCE[self.sequence_step as usize];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use apu::triangle::Triangle;
+
+    #[test]
+    fn steps_through_the_sequence_while_counters_are_active() {
+        let mut triangle = Triangle::new();
+        triangle.set_enabled(true);
+        triangle.write_register(0, 0x7F);
+        triangle.write_register(2, 2);
+        triangle.write_register(3, 0b0000_1000);
+        triangle.clock_quarter_frame(); // Loads the linear counter
+
+        assert_eq!(triangle.get_output(), 15);
+        for _ in 0..3 {
+            triangle.clock_timer();
+        }
+        assert_eq!(triangle.get_output(), 14);
+    }
+
+    #[test]
+    fn linear_counter_silences_the_channel() {
+        let mut triangle = Triangle::new();
+        triangle.set_enabled(true);
+        triangle.write_register(0, 1); // Linear counter of 1, control flag clear
+        triangle.write_register(2, 2);
+        triangle.write_register(3, 0b0000_1000);
+        triangle.clock_quarter_frame();
+        triangle.clock_quarter_frame();
+
+        for _ in 0..30 {
+            triangle.clock_timer();
+        }
+        assert_eq!(triangle.get_output(), 15);
+    }
+}
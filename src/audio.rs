@@ -0,0 +1,349 @@
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+// Somewhere for resampled audio to go. Samples are mono, from -1.0 to 1.0
+pub trait AudioSink {
+    fn get_sample_rate(&self) -> u32;
+
+    fn queue_samples(&mut self, samples: &[f32]);
+
+    // How many seconds of audio are waiting to be played. Sinks that aren't played back in real time return None,
+    // which means the emulator doesn't need to slow down for them
+    fn get_queued_seconds(&self) -> Option<f64> {
+        return None;
+    }
+}
+
+// Writes 16 bit mono PCM. The sizes in the header aren't known until the end, so they're filled in when the sink is dropped
+pub struct WavSink<W: Write + Seek> {
+    writer: W,
+    sample_rate: u32,
+    num_samples: u32
+}
+
+const WAV_HEADER_SIZE: u32 = 44;
+
+impl WavSink<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P, sample_rate: u32) -> io::Result<WavSink<BufWriter<File>>> {
+        return WavSink::new(BufWriter::new(File::create(path)?), sample_rate);
+    }
+}
+
+impl<W: Write + Seek> WavSink<W> {
+    pub fn new(mut writer: W, sample_rate: u32) -> io::Result<WavSink<W>> {
+        WavSink::<W>::write_header(&mut writer, sample_rate, 0)?;
+        return Ok(WavSink {
+            writer: writer,
+            sample_rate: sample_rate,
+            num_samples: 0
+        });
+    }
+
+    fn write_header(writer: &mut W, sample_rate: u32, num_samples: u32) -> io::Result<()> {
+        let data_size = num_samples * 2;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&u32_to_bytes(WAV_HEADER_SIZE - 8 + data_size))?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&u32_to_bytes(16))?; // Size of the rest of this chunk
+        writer.write_all(&u16_to_bytes(1))?; // PCM
+        writer.write_all(&u16_to_bytes(1))?; // Mono
+        writer.write_all(&u32_to_bytes(sample_rate))?;
+        writer.write_all(&u32_to_bytes(sample_rate * 2))?; // Bytes per second
+        writer.write_all(&u16_to_bytes(2))?; // Bytes per sample, for all channels
+        writer.write_all(&u16_to_bytes(16))?; // Bits per sample
+
+        writer.write_all(b"data")?;
+        writer.write_all(&u32_to_bytes(data_size))?;
+        return Ok(());
+    }
+
+    // Goes back and fills in the header now that we know how much audio there was
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.writer.seek(SeekFrom::Start(0))?;
+        WavSink::<W>::write_header(&mut self.writer, self.sample_rate, self.num_samples)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        return self.writer.flush();
+    }
+}
+
+impl<W: Write + Seek> AudioSink for WavSink<W> {
+    fn get_sample_rate(&self) -> u32 {
+        return self.sample_rate;
+    }
+
+    fn queue_samples(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            let value = (sample.max(-1.0).min(1.0) * 32767.0) as i16;
+            if let Err(error) = self.writer.write_all(&u16_to_bytes(value as u16)) {
+                eprintln!("Could not write audio: {}", error);
+                return;
+            }
+            self.num_samples += 1;
+        }
+    }
+}
+
+impl<W: Write + Seek> Drop for WavSink<W> {
+    fn drop(&mut self) {
+        if let Err(error) = self.finish() {
+            eprintln!("Could not finish writing audio: {}", error);
+        }
+    }
+}
+
+// WAV files are little endian
+fn u16_to_bytes(value: u16) -> [u8; 2] {
+    return [value as u8, (value >> 8) as u8];
+}
+
+fn u32_to_bytes(value: u32) -> [u8; 4] {
+    return [value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8];
+}
+
+const KERNEL_TAPS: usize = 16;
+const KERNEL_PHASES: usize = 64;
+const CUTOFF: f64 = 0.9; // As a fraction of the output's Nyquist frequency. Leaves a little room for the filter to roll off
+const HIGH_PASS_FREQUENCY: f64 = 90.0; // The NES has a 90Hz high-pass filter on its output, which also removes the DC offset
+
+// Turns the APU's output (one sample per CPU cycle, so ~1.79 MHz) into something a sound card can play.
+// Throwing away samples would alias badly, and filtering every input sample would be slow. But the APU's output only
+// changes every so often, so instead each change is added to the output as a band-limited step, like blargg's Blip_Buffer
+pub struct Resampler {
+    clock_rate: f64,
+    sample_rate: f64,
+    samples_per_cycle: f64,
+    position: f64, // How far into the pending output we are, in output samples
+    deltas: Vec<f32>, // Changes in amplitude at each output sample. Summing them up gives the actual waveform
+    kernel: Vec<[f32; KERNEL_TAPS]>, // One windowed sinc for each fractional position a step can start at
+    last_amplitude: f32,
+    integrator: f32,
+    high_pass_factor: f32,
+    high_pass_input: f32,
+    high_pass_output: f32
+}
+
+impl Resampler {
+    pub fn new(clock_rate: f64, sample_rate: u32) -> Resampler {
+        let time_constant = 1.0 / (2.0 * PI * HIGH_PASS_FREQUENCY);
+        let sample_period = 1.0 / sample_rate as f64;
+
+        return Resampler {
+            clock_rate: clock_rate,
+            sample_rate: sample_rate as f64,
+            samples_per_cycle: sample_rate as f64 / clock_rate,
+            position: 0.0,
+            deltas: vec![0.0; KERNEL_TAPS],
+            kernel: Resampler::create_kernel(),
+            last_amplitude: 0.0,
+            integrator: 0.0,
+            high_pass_factor: (time_constant / (time_constant + sample_period)) as f32,
+            high_pass_input: 0.0,
+            high_pass_output: 0.0
+        }
+    }
+
+    fn create_kernel() -> Vec<[f32; KERNEL_TAPS]> {
+        let mut kernel = vec!();
+        for phase in 0..KERNEL_PHASES {
+            let offset = phase as f64 / KERNEL_PHASES as f64;
+            let mut taps = [0.0; KERNEL_TAPS];
+            let mut sum = 0.0;
+            for tap in 0..KERNEL_TAPS {
+                let t = tap as f64 - (KERNEL_TAPS / 2) as f64 - offset;
+                let sinc = if t == 0.0 { 1.0 } else { (PI * CUTOFF * t).sin() / (PI * CUTOFF * t) };
+                // Blackman window
+                let window_position = 2.0 * PI * (t / KERNEL_TAPS as f64 + 0.5);
+                let window = 0.42 - 0.5 * window_position.cos() + 0.08 * (2.0 * window_position).cos();
+                taps[tap] = sinc * window;
+                sum += taps[tap];
+            }
+
+            // Each step needs to add up to exactly its size, or the waveform would drift
+            let mut normalized = [0.0; KERNEL_TAPS];
+            for tap in 0..KERNEL_TAPS {
+                normalized[tap] = (taps[tap] / sum) as f32;
+            }
+            kernel.push(normalized);
+        }
+        return kernel;
+    }
+
+    // Speeds up or slows down the output slightly. 1.01 makes 1% more samples for the same amount of emulation
+    pub fn set_rate_adjustment(&mut self, ratio: f64) {
+        self.samples_per_cycle = self.sample_rate * ratio / self.clock_rate;
+    }
+
+    // Called once per CPU cycle with the APU's output
+    pub fn add_sample(&mut self, amplitude: f32) {
+        if amplitude != self.last_amplitude {
+            let delta = amplitude - self.last_amplitude;
+            self.last_amplitude = amplitude;
+            self.add_delta(delta);
+        }
+        self.position += self.samples_per_cycle;
+    }
+
+    fn add_delta(&mut self, delta: f32) {
+        let whole = self.position as usize;
+        let phase = ((self.position - whole as f64) * KERNEL_PHASES as f64) as usize;
+
+        if self.deltas.len() < whole + KERNEL_TAPS {
+            self.deltas.resize(whole + KERNEL_TAPS, 0.0);
+        }
+        let taps = &self.kernel[phase.min(KERNEL_PHASES - 1)];
+        for tap in 0..KERNEL_TAPS {
+            self.deltas[whole + tap] += delta * taps[tap];
+        }
+    }
+
+    // Moves every finished output sample into the given buffer
+    pub fn take_samples(&mut self, output: &mut Vec<f32>) {
+        let num_ready = self.position as usize;
+        if self.deltas.len() < num_ready + KERNEL_TAPS {
+            self.deltas.resize(num_ready + KERNEL_TAPS, 0.0);
+        }
+
+        for index in 0..num_ready {
+            self.integrator += self.deltas[index];
+
+            self.high_pass_output = self.high_pass_factor * (self.high_pass_output + self.integrator - self.high_pass_input);
+            self.high_pass_input = self.integrator;
+            output.push(self.high_pass_output);
+        }
+
+        self.deltas.drain(0..num_ready);
+        self.position -= num_ready as f64;
+    }
+}
+
+// How far ahead of the sound card we try to stay. Less is more responsive, but risks running dry and crackling
+const TARGET_LATENCY_SECONDS: f64 = 0.05;
+const MAX_RATE_ADJUSTMENT: f64 = 0.005;
+
+// Sends the APU's output to a sink, and keeps the emulator in step with it
+pub struct AudioOutput {
+    sink: Box<dyn AudioSink>,
+    resampler: Resampler,
+    samples: Vec<f32>
+}
+
+impl AudioOutput {
+    pub fn new(sink: Box<dyn AudioSink>, clock_rate: f64) -> AudioOutput {
+        let sample_rate = sink.get_sample_rate();
+        return AudioOutput {
+            sink: sink,
+            resampler: Resampler::new(clock_rate, sample_rate),
+            samples: vec!()
+        }
+    }
+
+    pub fn add_sample(&mut self, amplitude: f32) {
+        self.resampler.add_sample(amplitude);
+    }
+
+    // Whether the sink is being played as we go. If so it decides how fast the emulator runs
+    #[cfg(feature = "sdl")]
+    pub fn is_real_time(&self) -> bool {
+        return self.sink.get_queued_seconds().is_some();
+    }
+
+    // Throws away the frame's audio. Used while fast-forwarding, since there's no way to play it back fast enough
+    #[cfg(feature = "sdl")]
+    pub fn skip_frame(&mut self) {
+        self.samples.clear();
+        self.resampler.take_samples(&mut self.samples);
+    }
+
+    // Called once per frame. Hands the frame's audio to the sink, then holds the emulator back if the sink is getting too far
+    // ahead. To keep from bouncing off of that limit, the amount of audio made per frame is nudged up or down a little
+    // so the buffer settles at the target. That drifts the emulation speed by at most half a percent, which nobody can hear
+    pub fn end_frame(&mut self) {
+        self.samples.clear();
+        self.resampler.take_samples(&mut self.samples);
+        self.sink.queue_samples(&self.samples);
+
+        if let Some(queued_seconds) = self.sink.get_queued_seconds() {
+            let fill = queued_seconds / TARGET_LATENCY_SECONDS;
+            let adjustment = ((1.0 - fill) * MAX_RATE_ADJUSTMENT).max(-MAX_RATE_ADJUSTMENT).min(MAX_RATE_ADJUSTMENT);
+            self.resampler.set_rate_adjustment(1.0 + adjustment);
+
+            if queued_seconds > TARGET_LATENCY_SECONDS {
+                thread::sleep(Duration::from_micros(((queued_seconds - TARGET_LATENCY_SECONDS) * 1_000_000.0) as u64));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use audio::AudioSink;
+    use audio::Resampler;
+    use audio::WavSink;
+    use std::io::Cursor;
+
+    const CLOCK_RATE: f64 = 1_789_773.0;
+
+    fn resample(resampler: &mut Resampler, amplitude: f32, num_cycles: u32) -> Vec<f32> {
+        for _ in 0..num_cycles {
+            resampler.add_sample(amplitude);
+        }
+        let mut output = vec!();
+        resampler.take_samples(&mut output);
+        return output;
+    }
+
+    #[test]
+    fn one_second_of_input_is_one_second_of_output() {
+        let mut resampler = Resampler::new(CLOCK_RATE, 48000);
+        let output = resample(&mut resampler, 0.0, CLOCK_RATE as u32);
+        assert!(output.len() >= 47999 && output.len() <= 48000);
+    }
+
+    #[test]
+    fn rate_adjustment_changes_the_amount_of_output() {
+        let mut resampler = Resampler::new(CLOCK_RATE, 44100);
+        resampler.set_rate_adjustment(1.005);
+        let output = resample(&mut resampler, 0.0, CLOCK_RATE as u32);
+        assert!(output.len() >= 44320 && output.len() <= 44321);
+    }
+
+    #[test]
+    fn steps_are_smooth_and_settle_back_to_zero() {
+        let mut resampler = Resampler::new(CLOCK_RATE, 48000);
+        let output = resample(&mut resampler, 0.5, CLOCK_RATE as u32 / 10);
+
+        // The step is spread over a few samples instead of jumping straight there
+        let peak = output.iter().cloned().fold(0.0, f32::max);
+        let first_big_sample = output.iter().position(|&sample| sample > 0.25).unwrap();
+        assert!(output[first_big_sample - 1] > 0.0);
+        assert!(peak < 0.6);
+
+        // And the high-pass filter takes away the DC offset
+        assert!(output.last().unwrap().abs() < 0.01);
+    }
+
+    #[test]
+    fn wav_header_is_filled_in_when_finished() {
+        let mut wav_sink = WavSink::new(Cursor::new(vec!()), 44100).unwrap();
+        wav_sink.queue_samples(&[0.0, 1.0, -1.0]);
+        wav_sink.finish().unwrap();
+
+        let bytes = wav_sink.writer.get_ref().clone();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[4..8], &[42, 0, 0, 0]);
+        assert_eq!(&bytes[24..28], &[0x44, 0xAC, 0, 0]);
+        assert_eq!(&bytes[40..44], &[6, 0, 0, 0]);
+        assert_eq!(&bytes[44..], &[0, 0, 0xFF, 0x7F, 0x01, 0x80]);
+    }
+}
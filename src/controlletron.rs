@@ -1,4 +1,39 @@
-use sdl2::keyboard::Keycode;
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(dead_code)] // Nothing is mapped to the second controller yet
+pub enum Player {
+    One,
+    Two
+}
+
+#[cfg(any(feature = "sdl", test))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Button {
+    A,
+    B,
+    Select,
+    Start,
+    Up,
+    Down,
+    Left,
+    Right
+}
+
+#[cfg(any(feature = "sdl", test))]
+impl Button {
+    // Where the button lives in the byte the game reads out of the controller
+    fn get_bit_num(&self) -> u8 {
+        match *self {
+            Button::A => 7,
+            Button::B => 6,
+            Button::Select => 5,
+            Button::Start => 4,
+            Button::Up => 3,
+            Button::Down => 2,
+            Button::Left => 1,
+            Button::Right => 0
+        }
+    }
+}
 
 // bit:   	 7     6     5     4     3     2     1     0
 // button:	 A     B  Select Start  Up   Down  Left  Right
@@ -62,21 +97,13 @@ impl Controlletron {
         }
     }
 
-    // These are all currently hard coded. But eventually, they should be remappable
-    pub fn receive_key_input(&mut self, keycode: Keycode, is_keydown: bool) {
-        match keycode {
-            Keycode::Z => self.set_controller_bit(7, is_keydown, true),
-            Keycode::X => self.set_controller_bit(6, is_keydown, true),
-            Keycode::Backspace => self.set_controller_bit(5, is_keydown, true),
-            Keycode::Return => self.set_controller_bit(4, is_keydown, true),
-            Keycode::Up => self.set_controller_bit(3, is_keydown, true),
-            Keycode::Down => self.set_controller_bit(2, is_keydown, true),
-            Keycode::Left => self.set_controller_bit(1, is_keydown, true),
-            Keycode::Right => self.set_controller_bit(0, is_keydown, true),
-            _ => { }
-        }
+    // Whatever is providing input (a keyboard, a gamepad, a movie file...) decides which buttons are held
+    #[cfg(any(feature = "sdl", test))]
+    pub fn set_button(&mut self, player: Player, button: Button, is_pressed: bool) {
+        self.set_controller_bit(button.get_bit_num(), is_pressed, player == Player::One);
     }
 
+    #[cfg(any(feature = "sdl", test))]
     fn set_controller_bit(&mut self, bit_num: u8, is_set: bool, is_first_player: bool) {
         let mask = 1 << bit_num;
         if is_first_player {
@@ -97,17 +124,18 @@ impl Controlletron {
 
 #[cfg(test)]
 mod tests {
+    use controlletron::Button;
     use controlletron::Controlletron;
-    use sdl2::keyboard::Keycode;
+    use controlletron::Player;
 
     #[test]
     fn set_and_unset_controller1_b_button() {
         let mut controlletron = Controlletron::new();
 
-        controlletron.receive_key_input(Keycode::X, true);
+        controlletron.set_button(Player::One, Button::B, true);
         assert_eq!(controlletron.controller1, 0b0100_0000);
 
-        controlletron.receive_key_input(Keycode::X, false);
+        controlletron.set_button(Player::One, Button::B, false);
         assert_eq!(controlletron.controller1, 0b0000_0000);
     }
 
@@ -115,13 +143,13 @@ mod tests {
     fn changing_multiple_bits_does_not_affect_other_bits() {
         let mut controlletron = Controlletron::new();
 
-        controlletron.receive_key_input(Keycode::X, true);
+        controlletron.set_button(Player::One, Button::B, true);
         assert_eq!(controlletron.controller1, 0b0100_0000);
 
-        controlletron.receive_key_input(Keycode::Up, true);
+        controlletron.set_button(Player::One, Button::Up, true);
         assert_eq!(controlletron.controller1, 0b0100_1000);
 
-        controlletron.receive_key_input(Keycode::X, false);
+        controlletron.set_button(Player::One, Button::B, false);
         assert_eq!(controlletron.controller1, 0b0000_1000);
     }
 
@@ -129,10 +157,10 @@ mod tests {
     fn changing_strobe_from_1_to_0_latches_controller_values() {
         let mut controlletron = Controlletron::new();
 
-        controlletron.receive_key_input(Keycode::Z, true);
+        controlletron.set_button(Player::One, Button::A, true);
         controlletron.receive_memory_write(1);
         controlletron.receive_memory_write(0);
-        controlletron.receive_key_input(Keycode::Z, false);
+        controlletron.set_button(Player::One, Button::A, false);
 
         assert_eq!(controlletron.controller1, 0b0000_0000);
         assert_eq!(controlletron.controller1_latched, 0b1000_0000);
@@ -149,6 +177,25 @@ mod tests {
         assert_eq!(controlletron.read_controller_value(0x4016), 0);
         assert_eq!(controlletron.read_controller_value(0x4017), 1);
     }
-}
 
+    #[test]
+    fn each_button_has_its_own_bit() {
+        let mut controlletron = Controlletron::new();
+        let buttons = [Button::Right, Button::Left, Button::Down, Button::Up, Button::Start, Button::Select, Button::B, Button::A];
+
+        for (bit_num, &button) in buttons.iter().enumerate() {
+            controlletron.set_button(Player::One, button, true);
+            assert_eq!(controlletron.controller1, 1 << bit_num);
+            controlletron.set_button(Player::One, button, false);
+        }
+    }
+
+    #[test]
+    fn player_two_has_their_own_controller() {
+        let mut controlletron = Controlletron::new();
 
+        controlletron.set_button(Player::Two, Button::Start, true);
+        assert_eq!(controlletron.controller1, 0b0000_0000);
+        assert_eq!(controlletron.controller2, 0b0001_0000);
+    }
+}
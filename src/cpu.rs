@@ -2,12 +2,27 @@ use instruction_set::get_instruction;
 use cpu_memory::CPUMemory;
 use instruction_set::AddressingMode;
 use instruction_set::InstructionType;
+use instruction_set::Mnemonic;
 use ppu::PPU as PPU;
+use apu::APU as APU;
 use controlletron::Controlletron as Controlletron;
-use std::collections::linked_list::LinkedList;
+use mapper::Cartridge as Cartridge;
+use std::collections::VecDeque;
 
 static STACK_POINTER_OFFSET: u16 = 0x100;
 
+// The registers as they are between instructions. Used by traces and debugging
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CpuState {
+    pub program_counter: u16,
+    pub accumulator: u8,
+    pub x_register: u8,
+    pub y_register: u8,
+    pub status_register: u8,
+    pub stack_pointer: u8,
+    pub cycle_count: u64
+}
+
 pub struct CPU {
     program_counter: u16,
     stack_pointer: u8,
@@ -15,19 +30,62 @@ pub struct CPU {
     accumulator: u8,
     x_register: u8,
     y_register: u8,
-    remaining_clock_cycles: i8,
+    instruction_cycle: u8, // Which cycle of the current instruction was last run. 0 means we're between instructions
     current_instruction: Option<InstructionType>,
+    opcode: u8,
+    operand_address: u16, // Built up a byte at a time as the instruction reads its operands
+    operand_pointer: u8, // The zero page pointer used by the indirect addressing modes
+    operand_value: u8, // Read-modify-write instructions hold on to the value between reading it and writing it back
+    is_operand_address_ready: bool,
+    is_page_crossed: bool, // Indexing carried into the high byte of the address, which takes the CPU an extra cycle to fix
+    operation_cycle: u8, // Cycles spent on the operand once its address was worked out
+    is_interrupt_polled: bool, // Interrupts are polled at the end of every cycle. This is what the last poll saw
+    should_skip_interrupt_poll: bool,
+    interrupt_vector: u16,
+    instruction_count: u64,
+    cycle_count: u64, // Every cycle since power on, including the ones spent stalled or jammed
+    is_jammed: bool, // KIL stops the CPU for good. Only a reset gets it going again
     dma_counter: i16,
     dma_address: u8,
+    dmc_stall_cycles: u8, // The CPU is halted while the DMC reads a sample byte
+    was_nmi_line_asserted: bool, // NMIs are edge triggered, so the line's previous state has to be remembered
+    is_nmi_pending: bool,
+    is_running_interrupt_sequence: bool, // For IRQs and NMIs. BRK runs the same sequence as a regular instruction
     memory: CPUMemory,
     ppu: *mut PPU,
+    apu: *mut APU,
     controlletron: *mut Controlletron,
-    history: LinkedList<String> // Just used for debugging what the emulator has run
+    history: VecDeque<&'static str> // Just used for debugging what the emulator has run
 }
 
 const OAM_DMA_MEMORY: u16 = 0x4014;
 const CONTROLLER1_MEMORY: u16 = 0x4016;
 const CONTROLLER2_MEMORY: u16 = 0x4017;
+const APU_STATUS_MEMORY: u16 = 0x4015;
+const DMC_STALL_CYCLES: u8 = 4;
+const NMI_VECTOR: u16 = 0xFFFA;
+const IRQ_VECTOR: u16 = 0xFFFE; // Shared with BRK
+const BRK_OPCODE: u8 = 0x00;
+const HISTORY_LENGTH: usize = 100;
+const UNSTABLE_MAGIC_CONSTANT: u8 = 0xEE; // XAA and LXA mix this into the accumulator. It varies from chip to chip
+
+// How an instruction that works on memory uses its operand. This decides which cycles it spends doing what
+#[derive(Clone, Copy, PartialEq)]
+enum Operation {
+    Read,
+    Write,
+    ReadModifyWrite
+}
+
+fn get_operation(instruction: InstructionType) -> Operation {
+    match instruction.mnemonic {
+        Mnemonic::Sta | Mnemonic::Stx | Mnemonic::Sty | Mnemonic::Sax => return Operation::Write,
+        Mnemonic::Ahx | Mnemonic::Shx | Mnemonic::Shy | Mnemonic::Tas => return Operation::Write, // Unofficial opcodes
+        Mnemonic::Asl | Mnemonic::Lsr | Mnemonic::Rol | Mnemonic::Ror | Mnemonic::Inc | Mnemonic::Dec => return Operation::ReadModifyWrite,
+        Mnemonic::Dcp | Mnemonic::Isb | Mnemonic::Slo | Mnemonic::Rla | Mnemonic::Rra | Mnemonic::Sre => return Operation::ReadModifyWrite, // Unofficial opcodes
+        _ => return Operation::Read
+    }
+}
 
 impl CPU {
     pub fn new() -> CPU {
@@ -38,224 +96,665 @@ impl CPU {
             accumulator: 0,
             x_register: 0,
             y_register: 0,
-            remaining_clock_cycles: 0,
+            instruction_cycle: 0,
             current_instruction: None,
+            opcode: 0,
+            operand_address: 0,
+            operand_pointer: 0,
+            operand_value: 0,
+            is_operand_address_ready: false,
+            is_page_crossed: false,
+            operation_cycle: 0,
+            is_interrupt_polled: false,
+            should_skip_interrupt_poll: false,
+            interrupt_vector: IRQ_VECTOR,
+            instruction_count: 0,
+            cycle_count: 0,
+            is_jammed: false,
             dma_counter: -1,
             dma_address: 0,
+            dmc_stall_cycles: 0,
+            was_nmi_line_asserted: false,
+            is_nmi_pending: false,
+            is_running_interrupt_sequence: false,
             memory: CPUMemory::new(),
             ppu: 0 as *mut PPU,
+            apu: 0 as *mut APU,
             controlletron: 0 as *mut Controlletron,
-            history: LinkedList::new()
+            history: VecDeque::with_capacity(HISTORY_LENGTH + 1)
         }
     }
 
     // FIXME: Due to shitty separation of concerns (e.g. CPU and PPU both rely on references to each other), this is set after the CPU is newed up
-    pub fn init_late_pointers(&mut self, ppu: *mut PPU) {
+    pub fn init_late_pointers(&mut self, ppu: *mut PPU, apu: *mut APU, controlletron: *mut Controlletron) {
         self.ppu = ppu;
-        unsafe {
-            self.controlletron = (*self.ppu).get_controlletron();
-        }
+        self.apu = apu;
+        self.controlletron = controlletron;
     }
 
-    pub fn init_prg_rom(&mut self, prg_rom: Vec<u8>) {
-        self.memory.init_prg_rom(prg_rom);
+    pub fn insert_cartridge(&mut self, cartridge: Cartridge) {
+        self.memory.insert_cartridge(cartridge);
         self.program_counter = self.memory.get_reset_vector();
     }
 
     pub fn tick(&mut self) {
-        if self.dma_counter != -1 {
-            self.perform_dma();
+        self.cycle_count += 1;
+        self.memory.tick_cartridge();
+        if self.is_jammed {
             return;
         }
+        self.detect_nmi_edge();
 
-        // Used to load in the very first instruction
-        if self.current_instruction.is_none() {
-            self.load_new_instruction();
+        if self.dmc_stall_cycles > 0 {
+            self.dmc_stall_cycles -= 1;
+            return;
         }
 
-        self.remaining_clock_cycles -= 1;
+        if let Some(sample_address) = self.get_dmc_sample_request() {
+            let sample = self.memory.get_8_bit_value(sample_address);
+            unsafe {
+                (*self.apu).receive_dmc_sample(sample);
+            }
+            self.dmc_stall_cycles = DMC_STALL_CYCLES - 1;
+            return;
+        }
 
-        if self.remaining_clock_cycles > 0 { // The instruction takes multiple cycles to finish. Keep waiting
+        if self.dma_counter != -1 {
+            self.perform_dma();
             return;
-        } else if self.remaining_clock_cycles == 0 { // Instruction is finished. Execute it
-            // TODO bake the opcode into the InstructionType, and have handle_instruction take care of all of this nonsense
-            let memory_start = self.program_counter;
-            let opcode: u8 = self.read_from_memory_8(memory_start);
-            // The instruction counter is incremented prior to doing the action
-            let num_bytes = self.current_instruction.unwrap().num_bytes;
-            let instruction_data: Vec<u8> = self.memory.get_memory_range(self.program_counter + 1, num_bytes as u16 - 1);
-
-//            println!("{:X} {:X} {}", self.program_counter, opcode, self.current_instruction.unwrap().name);
-            self.program_counter += num_bytes as u16;
-
-            let instruction = self.current_instruction.unwrap();
-            self.handle_instruction(opcode, instruction, instruction_data.as_slice());
-
-            // We finished the last instruction. Grab another instruction
-            self.load_new_instruction();
+        }
+
+        // Every cycle does exactly one read or write, including the dummy ones the 6502 makes while it's busy doing something else
+        self.instruction_cycle += 1;
+        let is_instruction_finished = if self.is_running_interrupt_sequence {
+            self.run_interrupt_sequence_cycle(false)
+        } else if self.instruction_cycle == 1 {
+            self.fetch_opcode();
+            false
+        } else {
+            self.run_instruction_cycle()
+        };
+
+        if is_instruction_finished {
+            // Whether to interrupt is decided by the poll at the end of the second last cycle, so when CLI, SEI or PLP change the
+            // interrupt disable flag on their last cycle it doesn't affect IRQs until after the next instruction. The handler's
+            // first instruction always runs before another interrupt can be taken
+            let is_interrupt_sequence_done = self.is_running_interrupt_sequence || self.opcode == BRK_OPCODE;
+            self.is_running_interrupt_sequence = self.is_interrupt_polled && !is_interrupt_sequence_done;
+            self.instruction_cycle = 0;
+        }
+
+        if self.should_skip_interrupt_poll {
+            self.should_skip_interrupt_poll = false;
+        } else {
+            self.is_interrupt_polled = self.is_interrupt_requested();
         }
 //        self.debug_check_for_instruction_sequence();
     }
 
-    fn load_new_instruction(&mut self) {
-        self.handle_nmi();
-
+    fn fetch_opcode(&mut self) {
         let memory_start = self.program_counter;
-        let opcode: u8 = self.read_from_memory_8(memory_start);
-        self.current_instruction = Some(get_instruction(opcode));
-        self.remaining_clock_cycles = self.current_instruction.unwrap().num_cycles as i8;
-
-        self.history.push_back(self.current_instruction.unwrap().name.to_string());
-        if self.history.len() > 100 {
+        self.opcode = self.read_from_memory_8(memory_start);
+        self.program_counter = memory_start.wrapping_add(1);
+        self.current_instruction = Some(get_instruction(self.opcode));
+        self.is_operand_address_ready = false;
+        self.operation_cycle = 0;
+        self.instruction_count += 1;
+
+        self.history.push_back(self.current_instruction.unwrap().name);
+        if self.history.len() > HISTORY_LENGTH {
             self.history.pop_front();
         }
     }
 
-    // NOTE: There is some tomfoolery possible here. A thing called 'Interrupt Hijacking'. Might have to implement
-    fn handle_nmi(&mut self) {
-        if self.memory.are_nmis_enabled() {
-            if self.memory.read_ppu_for_nmi() {
-//                println!("NMI detected for CPU!");
-                let program_counter = self.program_counter;
-                self.push_stack_16(program_counter);
-                self.push_status_to_stack(true);
+    // The PPU pulls the NMI line for as long as the vblank flag and the NMI enable bit are both set. The CPU only reacts to
+    // the line becoming asserted, so an NMI happens at the start of vblank, or when NMIs are enabled part way through it
+    fn detect_nmi_edge(&mut self) {
+        let is_nmi_line_asserted = self.memory.is_ppu_nmi_asserted();
+        if is_nmi_line_asserted && !self.was_nmi_line_asserted {
+            self.is_nmi_pending = true;
+        }
+        self.was_nmi_line_asserted = is_nmi_line_asserted;
+    }
+
+    // Unlike NMIs, IRQs are ignored while the interrupt disable flag is set. The line stays asserted until whatever
+    // is pulling it (like a mapper's scanline counter) is acknowledged by the game, so it's polled over and over again
+    fn is_interrupt_requested(&self) -> bool {
+        return self.is_nmi_pending || (!self.are_interrupts_disabled() && self.is_irq_asserted());
+    }
+
+    // BRK, IRQs and NMIs all share the same 7 cycle sequence. IRQs and NMIs start with two dummy reads of the next opcode, where BRK
+    // has already fetched its opcode and skips over the padding byte after it. Only BRK pushes the status with the B flag set.
+    // The vector is picked as the status is pushed, so an NMI that comes along before then hijacks a BRK or IRQ
+    // and its handler gets run instead. Returns true once the sequence is finished
+    fn run_interrupt_sequence_cycle(&mut self, is_brk: bool) -> bool {
+        let program_counter = self.program_counter;
+        match self.instruction_cycle {
+            1 => { self.read_from_memory_8(program_counter); },
+            2 => {
+                self.read_from_memory_8(program_counter);
+                if is_brk {
+                    self.program_counter = program_counter.wrapping_add(1);
+                }
+            },
+            3 => self.push_stack((program_counter >> 8) as u8),
+            4 => self.push_stack(program_counter as u8),
+            5 => {
+                self.interrupt_vector = if self.is_nmi_pending { NMI_VECTOR } else { IRQ_VECTOR };
+                self.is_nmi_pending = false;
+                self.push_status_to_stack(!is_brk);
+            },
+            6 => {
+                let vector = self.interrupt_vector;
+                self.operand_value = self.read_from_memory_8(vector);
                 self.asm_sei(); // Disable interrupts
-                self.program_counter = self.memory.get_16_bit_value(0xFFFA);
+            },
+            _ => {
+                let vector = self.interrupt_vector;
+                let high_byte = self.read_from_memory_8(vector + 1);
+                self.program_counter = (high_byte as u16) << 8 | self.operand_value as u16;
+                return true;
             }
         }
+        return false;
     }
 
-    fn get_source_address(&mut self, instruction: InstructionType, instruction_data: &[u8]) -> u16 {
-        // Make copies so the borrow checker doesn't get mad
-        let x_register = self.x_register;
-        let y_register = self.y_register;
+    // The IRQ line is shared. Anything on it can pull it low
+    fn is_irq_asserted(&self) -> bool {
+        if self.memory.is_cartridge_irq_asserted() {
+            return true;
+        }
+        // Most of the tests don't hook up an APU
+        if self.apu.is_null() {
+            return false;
+        }
+        unsafe {
+            return (*self.apu).is_irq_asserted();
+        }
+    }
+
+    fn get_dmc_sample_request(&self) -> Option<u16> {
+        if self.apu.is_null() {
+            return None;
+        }
+        unsafe {
+            return (*self.apu).get_dmc_sample_request();
+        }
+    }
 
+    // Runs one cycle of the instruction that's been fetched. Returns true when it was the instruction's last cycle
+    fn run_instruction_cycle(&mut self) -> bool {
+        let instruction = self.current_instruction.unwrap();
         match instruction.addressing_mode {
-            AddressingMode::Immediate => panic!("Makes no sense! {}", instruction.name),
-            AddressingMode::Absolute => CPU::convert_to_address(instruction_data),
-            AddressingMode::Implied => panic!("There is no data for implied instructions!"),
-            AddressingMode::Accumulator => panic!("There is no data for accumulator instructions!"),
-            AddressingMode::AbsoluteX => self.compute_absolute_address(instruction_data, x_register),
-            AddressingMode::AbsoluteY => self.compute_absolute_address(instruction_data, y_register),
-            AddressingMode::ZeroPageAbsolute => self.compute_zero_page_absolute_address(instruction_data[0], 0),
-            AddressingMode::ZeroPageAbsoluteX => self.compute_zero_page_absolute_address(instruction_data[0], x_register),
-            AddressingMode::ZeroPageAbsoluteY => self.compute_zero_page_absolute_address(instruction_data[0], y_register),
-            AddressingMode::PreIndexedIndirect => self.get_pre_indexed_indirect_address(instruction_data[0]),
-            AddressingMode::PostIndexedIndirect => self.get_post_indexed_indirect_address(instruction_data[0]),
-            AddressingMode::Relative => panic!("Also makes no sense!"),
-            AddressingMode::Indirect => self.get_indirect_address(instruction_data),
-            AddressingMode::Empty => panic!("AddressingMode not set for {}!", instruction.name)
+            AddressingMode::Immediate => {
+                let value = self.fetch_operand_byte();
+                self.execute_read(instruction, value);
+                return true;
+            },
+            AddressingMode::Implied => return self.run_implied_cycle(instruction),
+            AddressingMode::Accumulator => {
+                let program_counter = self.program_counter;
+                self.read_from_memory_8(program_counter); // Dummy read of the next opcode
+                self.execute_accumulator(instruction);
+                return true;
+            },
+            AddressingMode::Relative => return self.run_branch_cycle(instruction),
+            AddressingMode::Indirect => return self.run_indirect_jump_cycle(),
+            AddressingMode::Absolute if instruction.mnemonic == Mnemonic::Jmp => {
+                let value = self.fetch_operand_byte();
+                if self.instruction_cycle == 2 {
+                    self.operand_value = value;
+                    return false;
+                }
+                self.asm_jmp((value as u16) << 8 | self.operand_value as u16);
+                return true;
+            },
+            AddressingMode::Absolute if instruction.mnemonic == Mnemonic::Jsr => return self.run_jsr_cycle(),
+            _ => return self.run_memory_cycle(instruction)
         }
     }
 
-    fn handle_instruction(&mut self, opcode: u8, instruction: InstructionType, instruction_data: &[u8]) {
-//        println!("Name: {} Opcode: {:X} Data: {:?}", instruction.name, opcode, instruction_data);
-        if instruction.addressing_mode == AddressingMode::Implied {
-            match instruction.name.as_ref() {
-                "CLC" => self.asm_clc(),
-                "CLV" => self.asm_clv(),
-                "SEC" => self.asm_sec(),
-                "PHA" => self.asm_pha(),
-                "RTI" => self.asm_rti(),
-                "RTS" => self.asm_rts(),
-                "PLA" => self.asm_pla(),
-                "SEI" => self.asm_sei(),
-                "DEY" => self.asm_dey(),
-                "TXA" => self.asm_txa(),
-                "TYA" => self.asm_tya(),
-                "TXS" => self.asm_txs(),
-                "TAX" => self.asm_tax(),
-                "TAY" => self.asm_tay(),
-                "TSX" => self.asm_tsx(),
-                "INY" => self.asm_iny(),
-                "DEX" => self.asm_dex(),
-                "CLD" => self.asm_cld(),
-                "INX" => self.asm_inx(),
-                "BRK" => self.asm_brk(),
-                "NOP" => self.asm_nop(2),
-                "SED" => self.asm_sed(),
-                "PHP" => self.asm_php(),
-                "PLP" => self.asm_plp(),
-                _ => panic!("Implied instruction {} not implemented!", instruction.name)
+    // Implied instructions read the next opcode and throw it away while they execute, so they take at least 2 cycles
+    fn run_implied_cycle(&mut self, instruction: InstructionType) -> bool {
+        let cycle = self.instruction_cycle;
+        let program_counter = self.program_counter;
+        let stack_address = self.stack_pointer as u16 + STACK_POINTER_OFFSET;
+        match instruction.mnemonic {
+            Mnemonic::Brk => return self.run_interrupt_sequence_cycle(true),
+            Mnemonic::Kil => {
+                // Reads the next byte and then never finishes
+                self.read_from_memory_8(program_counter);
+                self.is_jammed = true;
+                return false;
+            },
+            Mnemonic::Pha | Mnemonic::Php => {
+                if cycle == 2 {
+                    self.read_from_memory_8(program_counter);
+                    return false;
+                }
+                if instruction.mnemonic == Mnemonic::Pha { self.asm_pha(); } else { self.asm_php(); }
+                return true;
+            },
+            Mnemonic::Pla | Mnemonic::Plp => {
+                match cycle {
+                    2 => { self.read_from_memory_8(program_counter); },
+                    3 => { self.read_from_memory_8(stack_address); }, // Dummy read while the stack pointer is incremented
+                    _ => {
+                        if instruction.mnemonic == Mnemonic::Pla { self.asm_pla(); } else { self.asm_plp(); }
+                        return true;
+                    }
+                }
+                return false;
+            },
+            Mnemonic::Rts => {
+                match cycle {
+                    2 => { self.read_from_memory_8(program_counter); },
+                    3 => { self.read_from_memory_8(stack_address); },
+                    4 => self.operand_address = self.pull_stack() as u16,
+                    5 => self.operand_address |= (self.pull_stack() as u16) << 8,
+                    _ => {
+                        // JSR pushed the address of its last byte, so it still needs to be stepped past
+                        let return_address = self.operand_address;
+                        self.read_from_memory_8(return_address);
+                        self.program_counter = return_address.wrapping_add(1);
+                        return true;
+                    }
+                }
+                return false;
+            },
+            Mnemonic::Rti => {
+                match cycle {
+                    2 => { self.read_from_memory_8(program_counter); },
+                    3 => { self.read_from_memory_8(stack_address); },
+                    4 => self.pull_status_from_stack(),
+                    5 => self.operand_address = self.pull_stack() as u16,
+                    _ => {
+                        self.program_counter = (self.pull_stack() as u16) << 8 | self.operand_address;
+                        return true;
+                    }
+                }
+                return false;
+            },
+            _ => {
+                self.read_from_memory_8(program_counter);
+                self.execute_implied(instruction);
+                return true;
             }
-            return;
         }
+    }
 
-        if instruction.addressing_mode == AddressingMode::Accumulator {
-            match instruction.name.as_ref() {
-                "ASL" => self.asm_asl_accumulator(),
-                "LSR" => self.asm_lsr_accumulator(),
-                "ROL" => self.asm_rol_accumulator(),
-                "ROR" => self.asm_ror_accumulator(),
-                _ => panic!("Accumulator instruction {} not implemented!", instruction.name)
+    // The offset is read on the second cycle. A taken branch spends a third cycle adding it to the low byte of the program
+    // counter, and a fourth fixing the high byte if that crossed a page. It doesn't poll for interrupts during that third cycle,
+    // so an interrupt that comes in then waits until after the next instruction
+    fn run_branch_cycle(&mut self, instruction: InstructionType) -> bool {
+        match self.instruction_cycle {
+            2 => {
+                let offset = self.fetch_operand_byte();
+                self.operand_address = self.program_counter;
+                if !self.execute_branch(instruction, offset) {
+                    return true;
+                }
+                self.should_skip_interrupt_poll = true;
+                return false;
+            },
+            3 => {
+                let next_address = self.operand_address;
+                self.read_from_memory_8(next_address);
+                return (next_address & 0xFF00) == (self.program_counter & 0xFF00);
+            },
+            _ => {
+                // Reads from where the branch would have gone if the high byte didn't need fixing
+                let unfixed_address = (self.operand_address & 0xFF00) | (self.program_counter & 0x00FF);
+                self.read_from_memory_8(unfixed_address);
+                return true;
             }
-            return;
         }
+    }
 
-        // TODO handle Immediate and Relative (which is basically Immediate) more gracefully
-        let mut source_address = 0;
-        if instruction.addressing_mode != AddressingMode::Immediate
-            && instruction.addressing_mode != AddressingMode::Relative {
-            source_address = self.get_source_address(instruction, instruction_data);
-            match instruction.name.as_ref() {
-                "STA" => { self.asm_sta(source_address); return; },
-                "STY" => { self.asm_sty(source_address); return; },
-                "STX" => { self.asm_stx(source_address); return; },
-                "SAX" => { self.asm_sax(source_address); return; }, // Unofficial opcode
-                "JSR" => { self.asm_jsr(source_address); return; },
-                "JMP" => { self.asm_jmp(source_address); return; },
-                "INC" => { self.asm_inc(source_address); return; },
-                "DEC" => { self.asm_dec(source_address); return; },
-                "DCP" => { self.asm_dcp(source_address); return; }, // Unofficial opcode
-                "ISB" => { self.asm_isb(source_address); return; }, // Unofficial opcode
-                "SLO" => { self.asm_slo(source_address); return; }, // Unofficial opcode
-                "ROR" => { self.asm_ror_memory(source_address); return; },
-                "ROL" => { self.asm_rol_memory(source_address); return; },
-                "RLA" => { self.asm_rla(source_address); return; }, // Unofficial opcode
-                "RRA" => { self.asm_rra(source_address); return; }, // Unofficial opcode
-                "SRE" => { self.asm_sre(source_address); return; }, // Unofficial opcode
-                "LSR" => { self.asm_lsr_memory(source_address); return; },
-                "ASL" => { self.asm_asl_memory(source_address); return; },
-                "DOP" => { self.asm_nop(3); return; }, // Unofficial opcode
-                "TOP" => { self.asm_nop(4); return; }, // Unofficial opcode
-                _ => ()
+    // Used only by JMP. The address it jumps to is stored at the operand's address
+    fn run_indirect_jump_cycle(&mut self) -> bool {
+        match self.instruction_cycle {
+            2 => self.operand_address = self.fetch_operand_byte() as u16,
+            3 => self.operand_address |= (self.fetch_operand_byte() as u16) << 8,
+            4 => {
+                let pointer = self.operand_address;
+                self.operand_value = self.read_from_memory_8(pointer);
+            },
+            _ => {
+                // This is a bug in the 6502 itself that has to be reproduced for accuracy. The high byte is read without
+                // carrying into the pointer's high byte, so a pointer at $30FF reads its high byte from $3000
+                let pointer = self.operand_address;
+                let high_byte_address = (pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF);
+                let high_byte = self.read_from_memory_8(high_byte_address);
+                self.asm_jmp((high_byte as u16) << 8 | self.operand_value as u16);
+                return true;
             }
         }
+        return false;
+    }
+
+    // The return address is pushed in between reading the two bytes of the address to jump to
+    fn run_jsr_cycle(&mut self) -> bool {
+        let program_counter = self.program_counter;
+        match self.instruction_cycle {
+            2 => self.operand_value = self.fetch_operand_byte(),
+            3 => {
+                let stack_address = self.stack_pointer as u16 + STACK_POINTER_OFFSET;
+                self.read_from_memory_8(stack_address);
+            },
+            4 => self.push_stack((program_counter >> 8) as u8),
+            5 => self.push_stack(program_counter as u8),
+            _ => {
+                let high_byte = self.fetch_operand_byte();
+                self.program_counter = (high_byte as u16) << 8 | self.operand_value as u16;
+                return true;
+            }
+        }
+        return false;
+    }
 
-        let source_value;
-        // Instruction we read in wasn't in the previous groups. So we want the address's value
-        if instruction.addressing_mode == AddressingMode::Immediate
-            || instruction.addressing_mode == AddressingMode::Relative {
-            source_value = instruction_data[0];
-        } else {
-            source_value = self.read_from_memory_8(source_address);
+    // Instructions that work on memory first spend a few cycles working out the address, then read it, write it, or read it
+    // and write it back twice (the first write puts back the unmodified value while the CPU works out the new one)
+    fn run_memory_cycle(&mut self, instruction: InstructionType) -> bool {
+        let operation = get_operation(instruction);
+        if !self.is_operand_address_ready {
+            self.is_operand_address_ready = self.run_addressing_cycle(instruction.addressing_mode, operation);
+            return false;
         }
-        match instruction.name.as_ref() {
-            "ADC" => { self.asm_adc(source_value); },
-            "SBC" => { self.asm_sbc(source_value); },
-            "LDA" => { self.asm_lda(source_value); },
-            "LDX" => { self.asm_ldx(source_value); },
-            "LDY" => { self.asm_ldy(source_value); },
-            "LAX" => { self.asm_lax(source_value); },
-            "CMP" => { self.asm_cmp(source_value); },
-            "CPX" => { self.asm_cpx(source_value); },
-            "CPY" => { self.asm_cpy(source_value); },
-            "BPL" => { self.asm_bpl(source_value); },
-            "BCS" => { self.asm_bcs(source_value); },
-            "BNE" => { self.asm_bne(source_value); },
-            "BEQ" => { self.asm_beq(source_value); },
-            "BVS" => { self.asm_bvs(source_value); },
-            "BVC" => { self.asm_bvc(source_value); },
-            "BCC" => { self.asm_bcc(source_value); },
-            "BMI" => { self.asm_bmi(source_value); },
-            "ORA" => { self.asm_ora(source_value); },
-            "AND" => { self.asm_and(source_value); },
-            "BIT" => { self.asm_bit(source_value); },
-            "EOR" => { self.asm_eor(source_value); },
-            "DOP" => { self.asm_nop(2); }, // Unofficial
-            _ => panic!("Found unimplemented instruction! Name: {} Opcode: {:X}", instruction.name, opcode)
+
+        let address = self.operand_address;
+        self.operation_cycle += 1;
+        match operation {
+            Operation::Read => {
+                let value = self.read_from_memory_8(address);
+                self.execute_read(instruction, value);
+                return true;
+            },
+            Operation::Write => {
+                self.execute_write(instruction, address);
+                return true;
+            },
+            Operation::ReadModifyWrite => {
+                match self.operation_cycle {
+                    1 => self.operand_value = self.read_from_memory_8(address),
+                    2 => {
+                        let value = self.operand_value;
+                        self.write_to_memory_8(address, value);
+                    },
+                    _ => {
+                        let value = self.operand_value;
+                        let new_value = self.execute_read_modify_write(instruction, value);
+                        self.write_to_memory_8(address, new_value);
+                        return true;
+                    }
+                }
+                return false;
+            }
         }
+    }
+
+    // Works towards the address an instruction operates on, one bus access at a time. Returns true once it's ready
+    fn run_addressing_cycle(&mut self, addressing_mode: AddressingMode, operation: Operation) -> bool {
+        let cycle = self.instruction_cycle;
+        let x_register = self.x_register;
+        let y_register = self.y_register;
+
+        match addressing_mode {
+            AddressingMode::ZeroPageAbsolute => {
+                self.operand_address = self.fetch_operand_byte() as u16;
+                return true;
+            },
+            AddressingMode::ZeroPageAbsoluteX | AddressingMode::ZeroPageAbsoluteY => {
+                if cycle == 2 {
+                    self.operand_address = self.fetch_operand_byte() as u16;
+                    return false;
+                }
+                // The unindexed address is read while the index is added. The result wraps around within the zero page
+                let base_address = self.operand_address;
+                self.read_from_memory_8(base_address);
+                let index = if addressing_mode == AddressingMode::ZeroPageAbsoluteX { x_register } else { y_register };
+                self.operand_address = (base_address as u8).wrapping_add(index) as u16;
+                return true;
+            },
+            AddressingMode::Absolute => {
+                if cycle == 2 {
+                    self.operand_address = self.fetch_operand_byte() as u16;
+                    return false;
+                }
+                self.operand_address |= (self.fetch_operand_byte() as u16) << 8;
+                return true;
+            },
+            AddressingMode::AbsoluteX | AddressingMode::AbsoluteY => {
+                match cycle {
+                    2 => { self.operand_address = self.fetch_operand_byte() as u16; },
+                    3 => {
+                        let base_address = (self.fetch_operand_byte() as u16) << 8 | self.operand_address;
+                        let index = if addressing_mode == AddressingMode::AbsoluteX { x_register } else { y_register };
+                        self.apply_index(base_address, index);
+                        return !CPU::needs_address_fix_cycle(operation, self.is_page_crossed);
+                    },
+                    _ => {
+                        self.read_unfixed_address();
+                        return true;
+                    }
+                }
+                return false;
+            },
+            AddressingMode::PreIndexedIndirect => {
+                match cycle {
+                    2 => { self.operand_pointer = self.fetch_operand_byte(); },
+                    3 => {
+                        let pointer = self.operand_pointer;
+                        self.read_from_memory_8(pointer as u16);
+                        self.operand_pointer = pointer.wrapping_add(x_register);
+                    },
+                    4 => {
+                        let pointer = self.operand_pointer;
+                        self.operand_address = self.read_from_memory_8(pointer as u16) as u16;
+                    },
+                    _ => {
+                        // The pointer wraps around within the zero page
+                        let pointer = self.operand_pointer.wrapping_add(1);
+                        self.operand_address |= (self.read_from_memory_8(pointer as u16) as u16) << 8;
+                        return true;
+                    }
+                }
+                return false;
+            },
+            AddressingMode::PostIndexedIndirect => {
+                match cycle {
+                    2 => { self.operand_pointer = self.fetch_operand_byte(); },
+                    3 => {
+                        let pointer = self.operand_pointer;
+                        self.operand_address = self.read_from_memory_8(pointer as u16) as u16;
+                    },
+                    4 => {
+                        let pointer = self.operand_pointer.wrapping_add(1);
+                        let base_address = (self.read_from_memory_8(pointer as u16) as u16) << 8 | self.operand_address;
+                        self.apply_index(base_address, y_register);
+                        return !CPU::needs_address_fix_cycle(operation, self.is_page_crossed);
+                    },
+                    _ => {
+                        self.read_unfixed_address();
+                        return true;
+                    }
+                }
+                return false;
+            },
+            _ => panic!("AddressingMode {:?} doesn't go through memory!", addressing_mode)
+        }
+    }
+
+    // The index is added to the low byte first. Any carry into the high byte gets fixed on the next cycle
+    fn apply_index(&mut self, base_address: u16, index: u8) {
+        self.operand_address = base_address.wrapping_add(index as u16);
+        self.is_page_crossed = (base_address & 0xFF00) != (self.operand_address & 0xFF00);
+    }
+
+    // Reads can skip fixing the high byte when it didn't need fixing, since they've already read from the right address.
+    // Writes (and read-modify-writes) can't take back what they did, so they always wait
+    fn needs_address_fix_cycle(operation: Operation, is_page_crossed: bool) -> bool {
+        return is_page_crossed || operation != Operation::Read;
+    }
+
+    // The dummy read made while the high byte of an indexed address is being fixed. It uses the high byte from before indexing
+    fn read_unfixed_address(&mut self) {
+        let unfixed_address = if self.is_page_crossed { self.operand_address.wrapping_sub(0x100) } else { self.operand_address };
+        self.read_from_memory_8(unfixed_address);
+    }
+
+    fn fetch_operand_byte(&mut self) -> u8 {
+        let program_counter = self.program_counter;
+        let value = self.read_from_memory_8(program_counter);
+        self.program_counter = program_counter.wrapping_add(1);
+        return value;
+    }
+
+    fn execute_implied(&mut self, instruction: InstructionType) {
+        match instruction.mnemonic {
+            Mnemonic::Clc => self.asm_clc(),
+            Mnemonic::Clv => self.asm_clv(),
+            Mnemonic::Sec => self.asm_sec(),
+            Mnemonic::Sei => self.asm_sei(),
+            Mnemonic::Cli => self.asm_cli(),
+            Mnemonic::Dey => self.asm_dey(),
+            Mnemonic::Txa => self.asm_txa(),
+            Mnemonic::Tya => self.asm_tya(),
+            Mnemonic::Txs => self.asm_txs(),
+            Mnemonic::Tax => self.asm_tax(),
+            Mnemonic::Tay => self.asm_tay(),
+            Mnemonic::Tsx => self.asm_tsx(),
+            Mnemonic::Iny => self.asm_iny(),
+            Mnemonic::Dex => self.asm_dex(),
+            Mnemonic::Cld => self.asm_cld(),
+            Mnemonic::Inx => self.asm_inx(),
+            Mnemonic::Nop => self.asm_nop(),
+            Mnemonic::Sed => self.asm_sed(),
+            _ => panic!("Implied instruction {} not implemented!", instruction.name)
+        }
+    }
+
+    fn execute_accumulator(&mut self, instruction: InstructionType) {
+        match instruction.mnemonic {
+            Mnemonic::Asl => self.asm_asl_accumulator(),
+            Mnemonic::Lsr => self.asm_lsr_accumulator(),
+            Mnemonic::Rol => self.asm_rol_accumulator(),
+            Mnemonic::Ror => self.asm_ror_accumulator(),
+            _ => panic!("Accumulator instruction {} not implemented!", instruction.name)
+        }
+    }
 
+    // Returns true if the branch was taken
+    fn execute_branch(&mut self, instruction: InstructionType, offset: u8) -> bool {
+        match instruction.mnemonic {
+            Mnemonic::Bpl => return self.asm_bpl(offset),
+            Mnemonic::Bcs => return self.asm_bcs(offset),
+            Mnemonic::Bne => return self.asm_bne(offset),
+            Mnemonic::Beq => return self.asm_beq(offset),
+            Mnemonic::Bvs => return self.asm_bvs(offset),
+            Mnemonic::Bvc => return self.asm_bvc(offset),
+            Mnemonic::Bcc => return self.asm_bcc(offset),
+            Mnemonic::Bmi => return self.asm_bmi(offset),
+            _ => panic!("Branch instruction {} not implemented!", instruction.name)
+        }
+    }
+
+    fn execute_read(&mut self, instruction: InstructionType, source_value: u8) {
+        match instruction.mnemonic {
+            Mnemonic::Adc => self.asm_adc(source_value),
+            Mnemonic::Sbc => self.asm_sbc(source_value),
+            Mnemonic::Lda => self.asm_lda(source_value),
+            Mnemonic::Ldx => self.asm_ldx(source_value),
+            Mnemonic::Ldy => self.asm_ldy(source_value),
+            Mnemonic::Lax => self.asm_lax(source_value), // Unofficial opcode
+            Mnemonic::Cmp => self.asm_cmp(source_value),
+            Mnemonic::Cpx => self.asm_cpx(source_value),
+            Mnemonic::Cpy => self.asm_cpy(source_value),
+            Mnemonic::Ora => self.asm_ora(source_value),
+            Mnemonic::And => self.asm_and(source_value),
+            Mnemonic::Bit => self.asm_bit(source_value),
+            Mnemonic::Eor => self.asm_eor(source_value),
+            Mnemonic::Nop => self.asm_nop(), // The unofficial NOPs that still read their operand
+            Mnemonic::Anc => self.asm_anc(source_value), // Unofficial opcode
+            Mnemonic::Alr => self.asm_alr(source_value), // Unofficial opcode
+            Mnemonic::Arr => self.asm_arr(source_value), // Unofficial opcode
+            Mnemonic::Axs => self.asm_axs(source_value), // Unofficial opcode
+            Mnemonic::Las => self.asm_las(source_value), // Unofficial opcode
+            Mnemonic::Lxa => self.asm_lxa(source_value), // Unofficial opcode
+            Mnemonic::Xaa => self.asm_xaa(source_value), // Unofficial opcode
+            _ => panic!("Found unimplemented instruction! Name: {} Opcode: {:X}", instruction.name, self.opcode)
+        }
+    }
+
+    fn execute_write(&mut self, instruction: InstructionType, address: u16) {
+        match instruction.mnemonic {
+            Mnemonic::Sta => self.asm_sta(address),
+            Mnemonic::Sty => self.asm_sty(address),
+            Mnemonic::Stx => self.asm_stx(address),
+            Mnemonic::Sax => self.asm_sax(address), // Unofficial opcode
+            Mnemonic::Ahx => self.asm_ahx(address), // Unofficial opcode
+            Mnemonic::Shx => self.asm_shx(address), // Unofficial opcode
+            Mnemonic::Shy => self.asm_shy(address), // Unofficial opcode
+            Mnemonic::Tas => self.asm_tas(address), // Unofficial opcode
+            _ => panic!("Write instruction {} not implemented!", instruction.name)
+        }
+    }
+
+    // Returns the value to write back
+    fn execute_read_modify_write(&mut self, instruction: InstructionType, source_value: u8) -> u8 {
+        match instruction.mnemonic {
+            Mnemonic::Asl => return self.asm_asl(source_value),
+            Mnemonic::Lsr => return self.asm_lsr(source_value),
+            Mnemonic::Rol => return self.asm_rol(source_value),
+            Mnemonic::Ror => return self.asm_ror(source_value),
+            Mnemonic::Inc => return self.asm_inc(source_value),
+            Mnemonic::Dec => return self.asm_dec(source_value),
+            Mnemonic::Dcp => return self.asm_dcp(source_value), // Unofficial opcode
+            Mnemonic::Isb => return self.asm_isb(source_value), // Unofficial opcode
+            Mnemonic::Slo => return self.asm_slo(source_value), // Unofficial opcode
+            Mnemonic::Rla => return self.asm_rla(source_value), // Unofficial opcode
+            Mnemonic::Rra => return self.asm_rra(source_value), // Unofficial opcode
+            Mnemonic::Sre => return self.asm_sre(source_value), // Unofficial opcode
+            _ => panic!("Read-modify-write instruction {} not implemented!", instruction.name)
+        }
+    }
+
+    // Starts running from an address other than the one in the reset vector. The cycle count is moved on by however many
+    // cycles were meant to have gone by already
+    pub fn start_at(&mut self, address: u16, elapsed_cycles: u64) {
+        self.program_counter = address;
+        self.cycle_count += elapsed_cycles;
+    }
+
+    // True when the next cycle fetches an opcode, rather than carrying on with an instruction, an interrupt or a DMA
+    pub fn is_between_instructions(&self) -> bool {
+        return self.instruction_cycle == 0 && !self.is_running_interrupt_sequence && self.dma_counter == -1 && self.dmc_stall_cycles == 0;
+    }
+
+    pub fn get_state(&self) -> CpuState {
+        return CpuState {
+            program_counter: self.program_counter,
+            accumulator: self.accumulator,
+            x_register: self.x_register,
+            y_register: self.y_register,
+            status_register: self.status_register,
+            stack_pointer: self.stack_pointer,
+            cycle_count: self.cycle_count
+        };
+    }
+
+    // Reads without any of the side effects a real read might have (like acknowledging an interrupt)
+    pub fn read_memory_for_debugging(&self, address: u16) -> u8 {
+        return self.memory.get_memory_range(address, 1)[0];
+    }
+
+    pub fn get_instruction_count(&self) -> u64 {
+        return self.instruction_count;
+    }
+
+    // True once a KIL instruction has run
+    pub fn is_jammed(&self) -> bool {
+        return self.is_jammed;
     }
 
     pub fn get_ppu_io_registers_address(&mut self) -> *mut u8 {
@@ -281,6 +780,10 @@ impl CPU {
             unsafe {
                 (*self.controlletron).receive_memory_write(new_value);
             }
+        } else if APU::is_register_address(address) {
+            unsafe {
+                (*self.apu).write_register(address, new_value);
+            }
         }
     }
 
@@ -297,6 +800,10 @@ impl CPU {
             unsafe {
                 return (*self.controlletron).read_controller_value(address);
             }
+        } else if address == APU_STATUS_MEMORY {
+            unsafe {
+                return (*self.apu).read_status();
+            }
         }
 
         return self.memory.get_8_bit_value(address);
@@ -365,93 +872,18 @@ impl CPU {
         }
     }
 
-    // Some resource seem to disagree about this bit. I actually think this might be wrong...
-    fn set_brk_bit(&mut self, is_set: bool) {
-        if is_set {
-            self.status_register |= 0b0001_0000;
-        } else {
-            self.status_register &= !0b0001_0000;
-        }
-    }
-
-    fn convert_to_address(address_data: &[u8]) -> u16 {
-        if address_data.len() == 2 {
-            return ((address_data[1] as u16) << 8) | (address_data[0] as u16);
-        } else {
-            return address_data[0] as u16;
-        }
-    }
-
-    fn get_pre_indexed_indirect_address(&mut self, zero_page_address: u8) -> u16 {
-        let address = zero_page_address.wrapping_add(self.x_register);
-        return if address == 0xFF {
-            let low_byte = self.read_from_memory_8(0xFF);
-            let high_byte = self.read_from_memory_8(0x00);
-            CPU::convert_to_address(&[low_byte, high_byte])
-        } else {
-            self.memory.get_16_bit_value(address as u16)
-        }
-    }
-
-    fn get_post_indexed_indirect_address(&mut self, zero_page_address: u8) -> u16 {
-        // TODO instead of having this (and pre-indexed) both do this check, add a function to CPUMemory to get a zero-page address
-        let address = if zero_page_address == 0xFF {
-            let low_byte = self.read_from_memory_8(0xFF);
-            let high_byte = self.read_from_memory_8(0x00);
-            CPU::convert_to_address(&[low_byte, high_byte])
-        } else {
-            self.memory.get_16_bit_value(zero_page_address as u16)
-        };
-
-        return address.wrapping_add(self.y_register as u16);
-    }
-
-    // Used only by JMP. Returns the address stored at the address
-    fn get_indirect_address(&mut self, instruction_data: &[u8]) -> u16 {
-        // This is a bug in the 6502 itself that has to be reproduced for accuracy
-        if instruction_data[0] == 0xFF {
-            let low_byte = self.memory.get_8_bit_value(CPU::convert_to_address(instruction_data));
-            let high_byte = self.memory.get_8_bit_value(CPU::convert_to_address(&[0x00, instruction_data[1]]));
-            return (high_byte as u16) << 8 | low_byte as u16
-        } else {
-            let address = CPU::convert_to_address(instruction_data);
-            return self.memory.get_16_bit_value(address);
-        };
-    }
-
-    fn compute_absolute_address(&mut self, instruction_data: &[u8], offset: u8) -> u16 {
-        let address = CPU::convert_to_address(instruction_data);
-        return address.wrapping_add(offset as u16);
-    }
-
-    fn compute_zero_page_absolute_address(&mut self, starting_address: u8, offset: u8) -> u16 {
-        return starting_address.wrapping_add(offset) as u16;
-    }
-
     fn push_stack(&mut self, value_to_write: u8) {
         let stack_address: u16 = self.stack_pointer as u16 + STACK_POINTER_OFFSET;
         self.write_to_memory_8(stack_address, value_to_write);
         self.stack_pointer = self.stack_pointer.wrapping_sub(1); // This tells rust we expect to underflow (if that's a word) and wrap around to 0xFF
     }
 
-    fn push_stack_16(&mut self, value_to_write: u16) {
-        self.push_stack((value_to_write >> 8) as u8);
-        self.push_stack(value_to_write as u8);
-    }
-
     fn pull_stack(&mut self) -> u8 {
         self.stack_pointer = self.stack_pointer.wrapping_add(1);
         let stack_address: u16 = self.stack_pointer as u16 + STACK_POINTER_OFFSET;
         return self.read_from_memory_8(stack_address);
     }
 
-    fn pull_stack_16(&mut self) -> u16 {
-        let low_bye: u16 = self.pull_stack() as u16;
-        let high_byte: u16 = (self.pull_stack() as u16) << 8;
-        return high_byte | low_bye;
-    }
-
-    #[allow(dead_code)]
     pub fn are_interrupts_disabled(&self) -> bool {
         return (self.status_register & 0x04) == 0x04;
     }
@@ -495,7 +927,6 @@ impl CPU {
         self.set_sign(difference);
     }
 
-    // TODO test
     fn push_status_to_stack(&mut self, from_interrupt: bool) {
         let mut status_register = self.status_register;
         status_register |= 0b0010_0000; // Bit 5 of the status register is always set
@@ -510,7 +941,6 @@ impl CPU {
         self.push_stack(status_register);
     }
 
-    // TODO test
     fn pull_status_from_stack(&mut self) {
         let mut status_register = self.pull_stack();
         status_register &= 0b1100_1111; // Ignore bits 4 and 5
@@ -536,7 +966,46 @@ impl CPU {
     // TODO test
     fn asm_sax(&mut self, source: u16) {
         let result = self.x_register & self.accumulator;
-        self.memory.set_8_bit_value(source, result);
+        self.write_to_memory_8(source, result);
+    }
+
+    // ** Unofficial Instruction **
+    // Stores the accumulator ANDed with the X register, and with the high byte of the address plus 1
+    fn asm_ahx(&mut self, address: u16) {
+        let result = self.accumulator & self.x_register;
+        self.store_with_high_byte(address, result);
+    }
+
+    // ** Unofficial Instruction **
+    // Stores the X register ANDed with the high byte of the address plus 1
+    fn asm_shx(&mut self, address: u16) {
+        let x_register = self.x_register;
+        self.store_with_high_byte(address, x_register);
+    }
+
+    // ** Unofficial Instruction **
+    // Stores the Y register ANDed with the high byte of the address plus 1
+    fn asm_shy(&mut self, address: u16) {
+        let y_register = self.y_register;
+        self.store_with_high_byte(address, y_register);
+    }
+
+    // ** Unofficial Instruction **
+    // Puts the accumulator ANDed with the X register in the stack pointer, then stores that like SHX and SHY do
+    fn asm_tas(&mut self, address: u16) {
+        self.stack_pointer = self.accumulator & self.x_register;
+        let stack_pointer = self.stack_pointer;
+        self.store_with_high_byte(address, stack_pointer);
+    }
+
+    // The SH* stores AND the value with the high byte of the address before indexing, plus 1. When indexing crossed a page, the
+    // value being stored also ends up as the high byte of the address it's stored to
+    fn store_with_high_byte(&mut self, address: u16, value: u8) {
+        let high_byte = (address >> 8) as u8;
+        let unindexed_high_byte = if self.is_page_crossed { high_byte.wrapping_sub(1) } else { high_byte };
+        let result = value & unindexed_high_byte.wrapping_add(1);
+        let target_address = if self.is_page_crossed { (result as u16) << 8 | (address & 0xFF) } else { address };
+        self.write_to_memory_8(target_address, result);
     }
 
     fn asm_and(&mut self, source: u8) {
@@ -577,63 +1046,113 @@ impl CPU {
         self.x_register = source;
     }
 
-    // Increment the value stored at a memory location
-    fn asm_inc(&mut self, address: u16) {
-        let memory_value = self.read_from_memory_8(address);
-        let new_memory_value = memory_value.wrapping_add(1);
-        self.write_to_memory_8(address, new_memory_value);
+    // ** Unofficial Instruction **
+    // ANDs a value with the stack pointer, and puts the result in the accumulator, the X register and the stack pointer
+    fn asm_las(&mut self, source: u8) {
+        let result = source & self.stack_pointer;
+        self.stack_pointer = result;
+        self.asm_lax(result);
+    }
+
+    // ** Unofficial Instruction **
+    // Mixes the magic constant into the accumulator, ANDs it with a value, and puts the result in both the accumulator and the X register
+    fn asm_lxa(&mut self, source: u8) {
+        let result = (self.accumulator | UNSTABLE_MAGIC_CONSTANT) & source;
+        self.asm_lax(result);
+    }
+
+    // ** Unofficial Instruction **
+    // Mixes the magic constant into the accumulator, and ANDs it with the X register and a value
+    fn asm_xaa(&mut self, source: u8) {
+        let result = (self.accumulator | UNSTABLE_MAGIC_CONSTANT) & self.x_register & source;
+        self.asm_lda(result);
+    }
+
+    // ** Unofficial Instruction **
+    // AND with the accumulator, and copy the sign into the carry as if the result had been shifted left
+    fn asm_anc(&mut self, source: u8) {
+        self.asm_and(source);
+        let is_negative = self.is_negative_set();
+        self.set_carry_bit(is_negative);
+    }
+
+    // ** Unofficial Instruction **
+    // AND with the accumulator, then shift the accumulator right by 1
+    fn asm_alr(&mut self, source: u8) {
+        self.asm_and(source);
+        self.asm_lsr_accumulator();
+    }
+
+    // ** Unofficial Instruction **
+    // AND with the accumulator, then rotate it right by 1. The carry and overflow come from bits 6 and 5 of the result instead
+    fn asm_arr(&mut self, source: u8) {
+        self.asm_and(source);
+        self.asm_ror_accumulator();
+        let result = self.accumulator;
+        self.set_carry_bit((result & 0x40) == 0x40);
+        self.set_overflow_bit(((result >> 6) ^ (result >> 5)) & 1 == 1);
+    }
+
+    // ** Unofficial Instruction **
+    // Subtracts a value from the accumulator ANDed with the X register, and puts the result in X. Sets the flags like CMP does,
+    // so the carry and decimal flags don't come into it
+    fn asm_axs(&mut self, source: u8) {
+        let value = self.accumulator & self.x_register;
+        self.compare(value, source);
+        self.x_register = value.wrapping_sub(source);
+    }
+
+    // Increment a value from memory. Returns the value to write back
+    fn asm_inc(&mut self, source: u8) -> u8 {
+        let new_memory_value = source.wrapping_add(1);
         self.set_sign(new_memory_value);
         self.set_zero(new_memory_value);
+        return new_memory_value;
     }
 
     // ** Unofficial **
     // Equivalent to an INC of the value then an SBC
     // TODO test
-    fn asm_isb(&mut self, address: u16) {
-        self.asm_inc(address);
-
-        let value = self.read_from_memory_8(address);
+    fn asm_isb(&mut self, source: u8) -> u8 {
+        let value = self.asm_inc(source);
         self.asm_sbc(value);
+        return value;
     }
 
     // ** Unofficial **
     // Equivalent to an ASL of the value then an ORA
     // TODO test
-    fn asm_slo(&mut self, address: u16) {
-        self.asm_asl_memory(address);
-
-        let value = self.read_from_memory_8(address);
+    fn asm_slo(&mut self, source: u8) -> u8 {
+        let value = self.asm_asl(source);
         self.asm_ora(value);
+        return value;
     }
 
     // ** Unofficial **
     // Equivalent to a ROL of the value then an AND
     // TODO test
-    fn asm_rla(&mut self, address: u16) {
-        self.asm_rol_memory(address);
-
-        let value = self.read_from_memory_8(address);
+    fn asm_rla(&mut self, source: u8) -> u8 {
+        let value = self.asm_rol(source);
         self.asm_and(value);
+        return value;
     }
 
     // ** Unofficial **
-    // Equivalent to a ROL of the value then an AND
+    // Equivalent to a ROR of the value then an ADC
     // TODO test
-    fn asm_rra(&mut self, address: u16) {
-        self.asm_ror_memory(address);
-
-        let value = self.read_from_memory_8(address);
+    fn asm_rra(&mut self, source: u8) -> u8 {
+        let value = self.asm_ror(source);
         self.asm_adc(value);
+        return value;
     }
 
     // ** Unofficial **
     // Equivalent to a LSR of the value then an EOR
     // TODO test
-    fn asm_sre(&mut self, address: u16) {
-        self.asm_lsr_memory(address);
-
-        let value = self.read_from_memory_8(address);
+    fn asm_sre(&mut self, source: u8) -> u8 {
+        let value = self.asm_lsr(source);
         self.asm_eor(value);
+        return value;
     }
 
     // Subtraction (with carry)
@@ -669,10 +1188,11 @@ impl CPU {
     }
 
     // Branches on 'result plus' - the result being a positive number
-    fn asm_bpl(&mut self, source: u8) {
-        if self.is_negative_set() { return; }
+    fn asm_bpl(&mut self, source: u8) -> bool {
+        if self.is_negative_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 
     // Clears the carry flag
@@ -685,14 +1205,6 @@ impl CPU {
         self.set_overflow_bit(false);
     }
 
-    // Have program start executing from a new address. Store current address on the stack
-    fn asm_jsr(&mut self, source: u16) {
-        let return_address = self.program_counter - 1;
-        self.push_stack((return_address >> 8) as u8);
-        self.push_stack((return_address & 0x00FF) as u8);
-        self.program_counter = source;
-    }
-
     // Bitshift accumulator to the left by 1, making the LSB the value of the current carry
     fn asm_rol_accumulator(&mut self) {
         let accumulator = self.accumulator;
@@ -739,36 +1251,6 @@ impl CPU {
         self.accumulator = self.asm_ror(accumulator);
     }
 
-    // Bitshift a memory location to the right by 1, making the MSB the value of the current carry. If LSB is set, set the carry flag
-    fn asm_ror_memory(&mut self, address: u16) {
-        let memory_value = self.read_from_memory_8(address);
-        let new_value = self.asm_ror(memory_value);
-        self.write_to_memory_8(address, new_value);
-    }
-
-    // Bitshift a memory location to the left by 1, making the LSB the value of the current carry. If MSB is set, set the carry flag
-    fn asm_rol_memory(&mut self, address: u16) {
-        let memory_value = self.read_from_memory_8(address);
-        let new_value = self.asm_rol(memory_value);
-        self.write_to_memory_8(address, new_value);
-    }
-
-    // Bitshift a memory location to the right by 1. If the LSB is set, set the carry flag
-    // TODO test
-    fn asm_lsr_memory(&mut self, address: u16) {
-        let memory_value = self.read_from_memory_8(address);
-        let new_value = self.asm_lsr(memory_value);
-        self.write_to_memory_8(address, new_value);
-    }
-
-    // Bitshift a memory location to left right by 1.
-    // TODO test
-    fn asm_asl_memory(&mut self, address: u16) {
-        let memory_value = self.read_from_memory_8(address);
-        let new_value = self.asm_asl(memory_value);
-        self.write_to_memory_8(address, new_value);
-    }
-
     // Sets various flags based off the current accumulator and memory address
     fn asm_bit(&mut self, source: u8) {
         let accumulator = self.accumulator;
@@ -813,19 +1295,6 @@ impl CPU {
         self.program_counter = address;
     }
 
-    // Return the program from an interrupt routine
-    fn asm_rti(&mut self) {
-        self.pull_status_from_stack();
-        self.program_counter = self.pull_stack_16();
-    }
-
-    // Have program return to the instruction it last jumped from
-    fn asm_rts(&mut self) {
-        let lower_byte: u8 = self.pull_stack();
-        let upper_byte: u8 = self.pull_stack();
-        self.program_counter = CPU::convert_to_address(&[lower_byte, upper_byte]) + 1;
-    }
-
     // Pull accumulator from the stack
     fn asm_pla(&mut self) {
         let accumulator = self.pull_stack();
@@ -839,6 +1308,11 @@ impl CPU {
         self.status_register |= 0x04;
     }
 
+    // Sets interrupts as being enabled. IRQs can be taken again after this
+    fn asm_cli(&mut self) {
+        self.status_register &= !0x04;
+    }
+
     // Decrements Y register by 1
     fn asm_dey(&mut self) {
         let y_register: u8 = self.y_register.wrapping_sub(1);
@@ -874,18 +1348,20 @@ impl CPU {
     }
 
     // Branches on 'carry clear' - the carry bit being 0 / not set
-    fn asm_bcc(&mut self, source: u8) {
-        if self.is_carry_set() { return; }
+    fn asm_bcc(&mut self, source: u8) -> bool {
+        if self.is_carry_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 
     // Branches on 'result minus'
     // TODO test
-    fn asm_bmi(&mut self, source: u8) {
-        if !self.is_negative_set() { return; }
+    fn asm_bmi(&mut self, source: u8) -> bool {
+        if !self.is_negative_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 
     // Copies the X register to the stack pointer. This does not mean pushing the X value onto the stack
@@ -918,10 +1394,11 @@ impl CPU {
     }
 
     // Branch when carry is set
-    fn asm_bcs(&mut self, source: u8) {
-        if !self.is_carry_set() { return; }
+    fn asm_bcs(&mut self, source: u8) -> bool {
+        if !self.is_carry_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 
     // Compare a value with value stored in the y register and set various flags
@@ -952,30 +1429,29 @@ impl CPU {
         self.x_register = x_register;
     }
 
-    // Decrements the value at a memory location by 1
-    fn asm_dec(&mut self, address: u16) {
-        let memory_value: u8 = self.read_from_memory_8(address);
-        let new_memory_value = memory_value.wrapping_sub(1);
-        self.write_to_memory_8(address, new_memory_value);
+    // Decrements a value from memory by 1. Returns the value to write back
+    fn asm_dec(&mut self, source: u8) -> u8 {
+        let new_memory_value = source.wrapping_sub(1);
         self.set_sign(new_memory_value);
         self.set_zero(new_memory_value);
+        return new_memory_value;
     }
 
     // ** Unofficial **
     // Equivalent to a DEC then a CMP of the value
     // TODO test
-    fn asm_dcp(&mut self, address: u16) {
-        self.asm_dec(address);
-
-        let value = self.read_from_memory_8(address);
+    fn asm_dcp(&mut self, source: u8) -> u8 {
+        let value = self.asm_dec(source);
         self.asm_cmp(value);
+        return value;
     }
 
     // Branch on result not zero
-    fn asm_bne(&mut self, source: u8) {
-        if self.is_zero_set() { return; }
+    fn asm_bne(&mut self, source: u8) -> bool {
+        if self.is_zero_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 
     // Sets the operational mode to binary instead of decimal
@@ -988,14 +1464,12 @@ impl CPU {
         self.status_register |= 0x08;
     }
 
-    // Pushes the status register onto the stack
-    // TODO test
+    // Pushes the status register onto the stack, with the B flag set
     fn asm_php(&mut self) {
         self.push_status_to_stack(false);
     }
 
     // Sets the status register with what is next to come off the stack
-    // TODO test
     fn asm_plp(&mut self) {
         self.pull_status_from_stack();
     }
@@ -1014,54 +1488,87 @@ impl CPU {
         self.x_register = x_register;
     }
 
-    // Triggers an error interrupt
-    // TODO test
-    fn asm_brk(&mut self) {
-        let return_address = self.program_counter + 1;
-
-        self.push_stack_16(return_address);
-        self.set_brk_bit(true); // Is there also a "set interrupt" bit?
-        self.push_status_to_stack(false);
-
-        let error_handler_address = self.memory.get_16_bit_value(0xFFFE);
-        self.program_counter = error_handler_address;
-
-        panic!("BRK hit");
-    }
-
-    // Does nothing at all
-    // TODO test? Maybe? Maybe once I have accurate timings?
-    fn asm_nop(&self, _num_cycles: u8) {
-        // TODO burn specific number of cycles or something depending on how I have this work
+    // Does nothing at all. The cycles it takes (and the operand the unofficial versions read) are handled like any other instruction's
+    fn asm_nop(&self) {
     }
 
     // Branches on 'result zero' - the last result having been zero
-    fn asm_beq(&mut self, source: u8) {
-        if !self.is_zero_set() { return; }
+    fn asm_beq(&mut self, source: u8) -> bool {
+        if !self.is_zero_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 
     // Branches on 'overflow set'
     // TODO test
-    fn asm_bvs(&mut self, source: u8) {
-        if !self.is_overflow_set() { return; }
+    fn asm_bvs(&mut self, source: u8) -> bool {
+        if !self.is_overflow_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 
     // Branches on 'overflow clear'
     // TODO test
-    fn asm_bvc(&mut self, source: u8) {
-        if self.is_overflow_set() { return; }
+    fn asm_bvc(&mut self, source: u8) -> bool {
+        if self.is_overflow_set() { return false; }
 
         self.branch(source);
+        return true;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use cpu::CPU;
+    use apu::APU;
+    use instruction_set;
+    use instruction_set::{AddressingMode, Mnemonic};
+    use mapper::Mapper;
+    use mapper::Mirroring;
+    use mapper::Nrom;
+    use region::Region;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // A cartridge that holds the IRQ line for as long as a test wants it to. It also keeps track of every access below
+    // its PRG ROM, so tests can see the dummy reads and writes the CPU makes
+    struct TestMapper {
+        prg_rom: Vec<u8>,
+        irq_asserted: bool,
+        reads: Vec<u16>,
+        writes: Vec<(u16, u8)>
+    }
+
+    impl Mapper for TestMapper {
+        fn read_prg(&mut self, address: u16) -> u8 {
+            if address >= 0x8000 {
+                return self.prg_rom[address as usize - 0x8000];
+            }
+            self.reads.push(address);
+            return 0;
+        }
+
+        fn write_prg(&mut self, address: u16, value: u8) {
+            self.writes.push((address, value));
+        }
+
+        fn read_chr(&mut self, _address: u16) -> u8 {
+            return 0;
+        }
+
+        fn write_chr(&mut self, _address: u16, _value: u8) {
+        }
+
+        fn get_mirroring(&self) -> Mirroring {
+            return Mirroring::Horizontal;
+        }
+
+        fn is_irq_asserted(&self) -> bool {
+            return self.irq_asserted;
+        }
+    }
 
     #[test]
     fn test_branch_positive_offset() {
@@ -1180,6 +1687,14 @@ mod tests {
         assert_eq!(cpu.are_interrupts_disabled(), true);
     }
 
+    #[test]
+    fn test_cli() {
+        let mut cpu: CPU = CPU::new();
+        cpu.asm_sei();
+        cpu.asm_cli();
+        assert_eq!(cpu.are_interrupts_disabled(), false);
+    }
+
     #[test]
     fn test_cld() {
         let mut cpu: CPU = CPU::new();
@@ -1274,6 +1789,80 @@ mod tests {
         assert_eq!(0x42, actual);
     }
 
+    #[test]
+    fn test_anc() {
+        let mut cpu: CPU = CPU::new();
+
+        cpu.accumulator = 0xF0;
+        cpu.asm_anc(0x8F);
+        assert_eq!(cpu.accumulator, 0x80);
+        assert_eq!(cpu.is_carry_set(), true);
+        assert_eq!(cpu.is_negative_set(), true);
+
+        cpu.asm_anc(0x7F);
+        assert_eq!(cpu.accumulator, 0x00);
+        assert_eq!(cpu.is_carry_set(), false);
+        assert_eq!(cpu.is_zero_set(), true);
+    }
+
+    #[test]
+    fn test_alr() {
+        let mut cpu: CPU = CPU::new();
+
+        cpu.accumulator = 0xFF;
+        cpu.asm_alr(0x0F);
+        assert_eq!(cpu.accumulator, 0x07);
+        assert_eq!(cpu.is_carry_set(), true);
+        assert_eq!(cpu.is_negative_set(), false);
+    }
+
+    #[test]
+    fn test_arr() {
+        let mut cpu: CPU = CPU::new();
+
+        cpu.set_carry_bit(true);
+        cpu.accumulator = 0xFF;
+        cpu.asm_arr(0xC0);
+        assert_eq!(cpu.accumulator, 0xE0);
+        assert_eq!(cpu.is_carry_set(), true); // Bit 6
+        assert_eq!(cpu.is_overflow_set(), false); // Bits 6 and 5 are the same
+        assert_eq!(cpu.is_negative_set(), true);
+
+        cpu.set_carry_bit(false);
+        cpu.accumulator = 0xFF;
+        cpu.asm_arr(0x40);
+        assert_eq!(cpu.accumulator, 0x20);
+        assert_eq!(cpu.is_carry_set(), false);
+        assert_eq!(cpu.is_overflow_set(), true);
+    }
+
+    #[test]
+    fn test_axs() {
+        let mut cpu: CPU = CPU::new();
+
+        cpu.accumulator = 0x0F;
+        cpu.x_register = 0xFC;
+        cpu.asm_axs(0x02);
+        assert_eq!(cpu.x_register, 0x0A);
+        assert_eq!(cpu.is_carry_set(), true);
+
+        cpu.asm_axs(0x0B);
+        assert_eq!(cpu.x_register, 0xFF);
+        assert_eq!(cpu.is_carry_set(), false);
+        assert_eq!(cpu.is_negative_set(), true);
+    }
+
+    #[test]
+    fn test_las() {
+        let mut cpu: CPU = CPU::new();
+
+        cpu.stack_pointer = 0xF5;
+        cpu.asm_las(0x3C);
+        assert_eq!(cpu.accumulator, 0x34);
+        assert_eq!(cpu.x_register, 0x34);
+        assert_eq!(cpu.stack_pointer, 0x34);
+    }
+
     #[test]
     fn test_stx() {
         let mut cpu: CPU = CPU::new();
@@ -1442,21 +2031,15 @@ mod tests {
     #[test]
     fn test_inc() {
         let mut cpu: CPU = CPU::new();
-        cpu.memory.set_8_bit_value(0x1020, 0x50);
-        cpu.asm_inc(0x1020);
-        assert_eq!(cpu.memory.get_8_bit_value(0x1020), 0x51);
+        assert_eq!(cpu.asm_inc(0x50), 0x51);
         assert_eq!(cpu.is_zero_set(), false);
         assert_eq!(cpu.is_negative_set(), false);
 
-        cpu.memory.set_8_bit_value(0x1020, 0x7F);
-        cpu.asm_inc(0x1020);
-        assert_eq!(cpu.memory.get_8_bit_value(0x1020), 0x80);
+        assert_eq!(cpu.asm_inc(0x7F), 0x80);
         assert_eq!(cpu.is_zero_set(), false);
         assert_eq!(cpu.is_negative_set(), true);
 
-        cpu.memory.set_8_bit_value(0x1020, 0xFF);
-        cpu.asm_inc(0x1020);
-        assert_eq!(cpu.memory.get_8_bit_value(0x1020), 0x00);
+        assert_eq!(cpu.asm_inc(0xFF), 0x00);
         assert_eq!(cpu.is_zero_set(), true);
         assert_eq!(cpu.is_negative_set(), false);
     }
@@ -1464,33 +2047,32 @@ mod tests {
     #[test]
     fn test_dec() {
         let mut cpu: CPU = CPU::new();
-        cpu.memory.set_8_bit_value(0x1020, 0x50);
-        cpu.asm_dec(0x1020);
-        assert_eq!(cpu.memory.get_8_bit_value(0x1020), 0x4F);
+        assert_eq!(cpu.asm_dec(0x50), 0x4F);
         assert_eq!(cpu.is_zero_set(), false);
         assert_eq!(cpu.is_negative_set(), false);
 
-        cpu.memory.set_8_bit_value(0x1020, 0x81);
-        cpu.asm_dec(0x1020);
-        assert_eq!(cpu.memory.get_8_bit_value(0x1020), 0x80);
+        assert_eq!(cpu.asm_dec(0x81), 0x80);
         assert_eq!(cpu.is_zero_set(), false);
         assert_eq!(cpu.is_negative_set(), true);
 
-        cpu.memory.set_8_bit_value(0x1020, 0x01);
-        cpu.asm_dec(0x1020);
-        assert_eq!(cpu.memory.get_8_bit_value(0x1020), 0x00);
+        assert_eq!(cpu.asm_dec(0x01), 0x00);
         assert_eq!(cpu.is_zero_set(), true);
         assert_eq!(cpu.is_negative_set(), false);
     }
 
     #[test]
     fn test_jsr() {
-        let mut cpu: CPU = CPU::new();
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0054] = 0x20; // JSR $9035
+        prg_rom[0x0055] = 0x35;
+        prg_rom[0x0056] = 0x90;
+
+        let mut cpu = create_test_cpu(prg_rom);
         cpu.program_counter = 0x8054;
-        cpu.asm_jsr(0x9035);
+        run_until(&mut cpu, 0x9035);
 
-        assert_eq!(cpu.program_counter, 0x9035);
         assert_eq!(cpu.stack_pointer, 0xFB);
+        assert_eq!(cpu.memory.get_16_bit_value(0x01FC), 0x8056); // The address of JSR's last byte
     }
 
     #[test]
@@ -1503,14 +2085,18 @@ mod tests {
 
     #[test]
     fn test_indirect_page_bug() {
-        let mut cpu: CPU = CPU::new();
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x6C; // JMP ($30FF)
+        prg_rom[0x0001] = 0xFF;
+        prg_rom[0x0002] = 0x30;
+
+        let mut cpu = create_test_cpu(prg_rom);
         cpu.memory.set_8_bit_value(0x3000, 0x40);
         cpu.memory.set_8_bit_value(0x30FF, 0x80);
         cpu.memory.set_8_bit_value(0x3100, 0x50);
 
-        let result = cpu.get_indirect_address(&[0xFF, 0x30]);
-
-        assert_eq!(result, 0x4080);
+        assert_eq!(count_instruction_cycles(&mut cpu), 5);
+        assert_eq!(cpu.program_counter, 0x4080);
     }
 
     #[test]
@@ -1614,12 +2200,17 @@ mod tests {
 
     #[test]
     fn test_rts() {
-        let mut cpu: CPU = CPU::new();
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0054] = 0x20; // JSR $9035
+        prg_rom[0x0055] = 0x35;
+        prg_rom[0x0056] = 0x90;
+        prg_rom[0x1035] = 0x60; // RTS
+
+        let mut cpu = create_test_cpu(prg_rom);
         cpu.program_counter = 0x8054;
-        cpu.asm_jsr(0x9035);
-        cpu.asm_rts();
+        run_until(&mut cpu, 0x9035);
+        run_until(&mut cpu, 0x8057);
 
-        assert_eq!(cpu.program_counter, 0x8054);
         assert_eq!(cpu.stack_pointer, 0xFD);
     }
 
@@ -1751,29 +2342,37 @@ mod tests {
 
     #[test]
     fn test_ror_memory() {
-        let mut cpu: CPU = CPU::new();
-        cpu.memory.set_8_bit_value(0x2500, 0b10010001);
-        cpu.asm_ror_memory(0x2500);
-
-        assert_eq!(cpu.memory.get_8_bit_value(0x2500), 0b01001000);
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x6E; // ROR $0500
+        prg_rom[0x0001] = 0x00;
+        prg_rom[0x0002] = 0x05;
+        prg_rom[0x0003] = 0x4C; // JMP $8000
+        prg_rom[0x0004] = 0x00;
+        prg_rom[0x0005] = 0x80;
+
+        let mut cpu = create_test_cpu(prg_rom);
+        cpu.memory.set_8_bit_value(0x0500, 0b10010001);
+        run_until(&mut cpu, 0x8003);
+
+        assert_eq!(cpu.memory.get_8_bit_value(0x0500), 0b01001000);
         assert_eq!(cpu.is_carry_set(), true);
         assert_eq!(cpu.is_negative_set(), false);
         assert_eq!(cpu.is_zero_set(), false);
 
-        cpu.memory.set_8_bit_value(0x2500, 0b01000010);
+        cpu.memory.set_8_bit_value(0x0500, 0b01000010);
         cpu.set_carry_bit(true);
-        cpu.asm_ror_memory(0x2500);
+        run_until(&mut cpu, 0x8003);
 
-        assert_eq!(cpu.memory.get_8_bit_value(0x2500), 0b10100001);
+        assert_eq!(cpu.memory.get_8_bit_value(0x0500), 0b10100001);
         assert_eq!(cpu.is_carry_set(), false);
         assert_eq!(cpu.is_negative_set(), true);
         assert_eq!(cpu.is_zero_set(), false);
 
-        cpu.memory.set_8_bit_value(0x2500, 0b00000000);
+        cpu.memory.set_8_bit_value(0x0500, 0b00000000);
         cpu.set_carry_bit(false);
-        cpu.asm_ror_memory(0x2500);
+        run_until(&mut cpu, 0x8003);
 
-        assert_eq!(cpu.memory.get_8_bit_value(0x2500), 0b00000000);
+        assert_eq!(cpu.memory.get_8_bit_value(0x0500), 0b00000000);
         assert_eq!(cpu.is_carry_set(), false);
         assert_eq!(cpu.is_negative_set(), false);
         assert_eq!(cpu.is_zero_set(), true);
@@ -1971,7 +2570,7 @@ mod tests {
         prg_rom[0x7FFD] = 0x80;
 
         let mut cpu: CPU = CPU::new();
-        cpu.init_prg_rom(prg_rom);
+        cpu.insert_cartridge(Rc::new(RefCell::new(Nrom::new(prg_rom, vec!(), 0x2000, Mirroring::Horizontal))));
         cpu.tick(); cpu.tick(); // Executes 0x78 (2 cycles)
         cpu.tick(); cpu.tick(); // Executes 0xD8 (2 cycles)
         cpu.tick(); cpu.tick(); // Executes 0xA9 [0x10] (2 cycles)
@@ -1982,9 +2581,56 @@ mod tests {
         assert_eq!(cpu.program_counter, 0x8004);
     }
 
+    // Ticks until the CPU is about to run the instruction at the address
+    fn run_until(cpu: &mut CPU, address: u16) {
+        for _ in 0..1000 {
+            cpu.tick();
+            if cpu.instruction_cycle == 0 && cpu.program_counter == address && !cpu.is_running_interrupt_sequence {
+                return;
+            }
+        }
+        panic!("Never got to {:X}", address);
+    }
+
+    // Runs whatever instruction (or interrupt) is about to start, and returns how many cycles it took
+    fn count_instruction_cycles(cpu: &mut CPU) -> u32 {
+        let mut cycles = 0;
+        loop {
+            cpu.tick();
+            cycles += 1;
+            if cpu.instruction_cycle == 0 {
+                return cycles;
+            }
+        }
+    }
+
+    fn create_interrupt_test_cpu(mut prg_rom: Vec<u8>) -> (CPU, Rc<RefCell<TestMapper>>) {
+        prg_rom[0x7FFC] = 0x00; // Reset vector. Initializes program counter to 8000
+        prg_rom[0x7FFD] = 0x80;
+        prg_rom[0x7FFA] = 0x00; // NMI vector. Handler at 9000
+        prg_rom[0x7FFB] = 0x90;
+        prg_rom[0x7FFE] = 0x00; // IRQ/BRK vector. Handler at A000
+        prg_rom[0x7FFF] = 0xA0;
+
+        let mapper = Rc::new(RefCell::new(TestMapper { prg_rom: prg_rom, irq_asserted: false, reads: vec!(), writes: vec!() }));
+        let mut cpu: CPU = CPU::new();
+        cpu.insert_cartridge(mapper.clone());
+        return (cpu, mapper);
+    }
+
+    fn create_test_cpu(prg_rom: Vec<u8>) -> CPU {
+        let (cpu, _) = create_interrupt_test_cpu(prg_rom);
+        return cpu;
+    }
+
+    fn set_nmi_line(cpu: &mut CPU, is_asserted: bool) {
+        cpu.memory.set_8_bit_value(0x2000, 0x80); // NMIs enabled
+        cpu.memory.set_8_bit_value(0x2002, if is_asserted { 0x80 } else { 0x00 }); // In vblank
+    }
+
     #[test]
     fn nmi_routine() {
-        let mut prg_rom: Vec<u8> = vec![0 as u8; 0x8000];
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
 
         // Normal instruction data
         prg_rom[0x0000] = 0xE8; // INX
@@ -1996,36 +2642,426 @@ mod tests {
         prg_rom[0x1001] = 0x18; // CLC (Clear carry flag. This should get undone after RTI)
         prg_rom[0x1002] = 0x40; // RTI
 
-        prg_rom[0x7FFC] = 0x00; // Reset vector. Initializes program counter to 8000
-        prg_rom[0x7FFD] = 0x80;
+        let (mut cpu, _) = create_interrupt_test_cpu(prg_rom);
+        run_until(&mut cpu, 0x8002); // Executes INX and SEC
 
-        prg_rom[0x7FFA] = 0x00; // Interrupt vector. Initializes program counter to 9000
-        prg_rom[0x7FFB] = 0x90;
+        set_nmi_line(&mut cpu, true);
 
-        let mut cpu: CPU = CPU::new();
-        cpu.init_prg_rom(prg_rom);
-        cpu.tick(); cpu.tick(); // Executes INX
-        cpu.tick(); cpu.tick(); // Executes SEC
+        // The INX that was already under way finishes first
+        run_until(&mut cpu, 0x9000);
+        assert_eq!(cpu.x_register, 0x02);
+        assert_eq!(cpu.memory.get_16_bit_value(0x01FC), 0x8003);
 
-        // Enable NMIs and set one as having happened
-        let mut ppu_ctrl_register = cpu.memory.get_8_bit_value(0x2000);
-        ppu_ctrl_register |= 0x80;
-        cpu.memory.set_8_bit_value(0x2000, ppu_ctrl_register);
+        run_until(&mut cpu, 0x8003); // Executes INY, CLC and RTI
+        assert_eq!(cpu.y_register, 0x01);
+        assert_eq!(cpu.is_carry_set(), true);
+    }
+
+    #[test]
+    fn interrupt_sequence_takes_7_cycles() {
+        let (mut cpu, mapper) = create_interrupt_test_cpu(vec![0xEA as u8; 0x8000]);
+        cpu.status_register &= !0x04; // Enable interrupts
+        mapper.borrow_mut().irq_asserted = true;
+
+        cpu.tick(); cpu.tick(); // NOP. The IRQ is taken after it
+        for _ in 0..6 {
+            cpu.tick();
+            assert_eq!(cpu.program_counter, 0x8001);
+        }
+        cpu.tick();
+        assert_eq!(cpu.program_counter, 0xA000);
+    }
 
-        let mut ppu_status_register = cpu.memory.get_8_bit_value(0x2002);
-        ppu_status_register |= 0x80;
-        cpu.memory.set_8_bit_value(0x2002, ppu_status_register);
+    #[test]
+    fn nmi_is_edge_triggered() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x1000] = 0xC8; // INY
+        prg_rom[0x1001] = 0x40; // RTI
 
-        // This should now be in the NMI routine
-        cpu.tick(); cpu.tick(); // Executes INY
-        cpu.tick(); cpu.tick(); // Executes CLC
-        cpu.tick(); // Executes RTI
+        let (mut cpu, _) = create_interrupt_test_cpu(prg_rom);
+        set_nmi_line(&mut cpu, true);
+        run_until(&mut cpu, 0x9000);
+        run_until(&mut cpu, 0x8001);
 
-        // We should now be back in the normal flow
-        cpu.tick(); cpu.tick(); // Executes INX
+        // The line is still asserted, but that isn't a new NMI
+        for _ in 0..100 {
+            cpu.tick();
+            assert_eq!(cpu.program_counter >= 0x8000 && cpu.program_counter < 0x9000, true);
+        }
 
+        // Reading $2002 releases the line. The next vblank is a new NMI
+        cpu.memory.get_8_bit_value(0x2002);
+        cpu.tick();
+        set_nmi_line(&mut cpu, true);
+        run_until(&mut cpu, 0x9000);
+        assert_eq!(cpu.y_register, 0x01);
+    }
+
+    #[test]
+    fn enabling_nmis_during_vblank_causes_an_nmi() {
+        let (mut cpu, _) = create_interrupt_test_cpu(vec![0xEA as u8; 0x8000]);
+        cpu.memory.set_8_bit_value(0x2002, 0x80); // Already in vblank
+        cpu.tick(); cpu.tick();
+        assert_eq!(cpu.is_nmi_pending, false);
+
+        cpu.memory.set_8_bit_value(0x2000, 0x80);
+        run_until(&mut cpu, 0x9000);
+    }
+
+    #[test]
+    fn irq_routine() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+
+        prg_rom[0x0000] = 0xE8; // INX (Interrupts are disabled at power on, so the IRQ is ignored)
+        prg_rom[0x0001] = 0x58; // CLI
+        prg_rom[0x0002] = 0xE8; // INX (Still runs. CLI only takes effect after the next instruction)
+        prg_rom[0x0003] = 0xE8; // INX (Should not run. The IRQ is taken first)
+
+        prg_rom[0x2000] = 0xC8; // INY
+
+        let (mut cpu, mapper) = create_interrupt_test_cpu(prg_rom);
+        mapper.borrow_mut().irq_asserted = true;
+
+        run_until(&mut cpu, 0xA000);
         assert_eq!(cpu.x_register, 0x02);
+        cpu.tick(); cpu.tick(); // Executes INY in the IRQ routine
+
         assert_eq!(cpu.y_register, 0x01);
-        assert_eq!(cpu.is_carry_set(), false);
+        assert_eq!(cpu.are_interrupts_disabled(), true);
+
+        // The return address and status were pushed, in that order
+        assert_eq!(cpu.memory.get_16_bit_value(0x01FC), 0x8003);
+        assert_eq!(cpu.memory.get_8_bit_value(0x01FB) & 0b0011_0000, 0b0010_0000); // B flag is clear for hardware interrupts
+    }
+
+    #[test]
+    fn irq_is_level_triggered() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x2000] = 0xC8; // INY
+        prg_rom[0x2001] = 0x40; // RTI
+
+        let (mut cpu, mapper) = create_interrupt_test_cpu(prg_rom);
+        cpu.status_register &= !0x04; // Enable interrupts
+        mapper.borrow_mut().irq_asserted = true;
+
+        // The line is still held after RTI, so the IRQ is taken again straight away
+        run_until(&mut cpu, 0xA000);
+        run_until(&mut cpu, 0xA001);
+        run_until(&mut cpu, 0xA000);
+        assert_eq!(cpu.y_register, 0x01);
+        assert_eq!(cpu.memory.get_16_bit_value(0x01FC), 0x8001);
+
+        // Once it's acknowledged, the game gets to carry on
+        mapper.borrow_mut().irq_asserted = false;
+        run_until(&mut cpu, 0x8002);
+        assert_eq!(cpu.y_register, 0x02);
+    }
+
+    #[test]
+    fn sei_still_lets_a_pending_irq_through() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x78; // SEI
+
+        let (mut cpu, mapper) = create_interrupt_test_cpu(prg_rom);
+        cpu.status_register &= !0x04; // Enable interrupts
+        mapper.borrow_mut().irq_asserted = true;
+
+        run_until(&mut cpu, 0xA000);
+        assert_eq!(cpu.memory.get_16_bit_value(0x01FC), 0x8001);
+        assert_eq!(cpu.memory.get_8_bit_value(0x01FB) & 0x04, 0x04); // SEI had already taken effect when the status was pushed
+    }
+
+    #[test]
+    fn apu_frame_counter_can_cause_an_irq() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x58; // CLI
+        prg_rom[0x0001] = 0x4C; // JMP $8001
+        prg_rom[0x0002] = 0x01;
+        prg_rom[0x0003] = 0x80;
+
+        let (mut cpu, _) = create_interrupt_test_cpu(prg_rom);
+        let mut apu = APU::new(Region::Ntsc);
+        cpu.apu = &mut apu as *mut APU;
+
+        // The 4 step sequence raises its IRQ at the end of every frame
+        for _ in 0..30000 {
+            cpu.tick();
+            apu.tick();
+            if cpu.program_counter == 0xA000 {
+                assert_eq!(apu.is_irq_asserted(), true);
+                return;
+            }
+        }
+        panic!("The frame IRQ never happened");
+    }
+
+    #[test]
+    fn brk_runs_the_irq_handler() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x00; // BRK
+        prg_rom[0x0001] = 0xFF; // Padding byte, skipped over
+        prg_rom[0x2000] = 0x40; // RTI
+
+        let (mut cpu, _) = create_interrupt_test_cpu(prg_rom);
+        run_until(&mut cpu, 0xA000); // BRK ignores the interrupt disable flag
+        assert_eq!(cpu.memory.get_16_bit_value(0x01FC), 0x8002);
+        assert_eq!(cpu.memory.get_8_bit_value(0x01FB) & 0b0011_0000, 0b0011_0000); // B flag is set for BRK
+        assert_eq!(cpu.status_register & 0b0001_0000, 0); // But it doesn't really exist in the status register
+
+        run_until(&mut cpu, 0x8002);
+    }
+
+    #[test]
+    fn php_pushes_the_b_flag() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x08; // PHP
+        prg_rom[0x0001] = 0x28; // PLP
+
+        let (mut cpu, _) = create_interrupt_test_cpu(prg_rom);
+        run_until(&mut cpu, 0x8001);
+        assert_eq!(cpu.memory.get_8_bit_value(0x01FD), 0b0011_0100);
+
+        run_until(&mut cpu, 0x8002);
+        assert_eq!(cpu.status_register, 0b0010_0100);
+    }
+
+    #[test]
+    fn nmi_hijacks_brk() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x00; // BRK
+
+        let (mut cpu, _) = create_interrupt_test_cpu(prg_rom);
+        cpu.tick(); cpu.tick(); // Part way through BRK
+        set_nmi_line(&mut cpu, true);
+
+        run_until(&mut cpu, 0x9000);
+        assert_eq!(cpu.memory.get_8_bit_value(0x01FB) & 0b0001_0000, 0b0001_0000); // Still pushed like a BRK
+        assert_eq!(cpu.is_nmi_pending, false); // The NMI was used up
+    }
+
+    #[test]
+    fn nmi_hijacks_irq() {
+        let (mut cpu, mapper) = create_interrupt_test_cpu(vec![0xEA as u8; 0x8000]);
+        cpu.status_register &= !0x04; // Enable interrupts
+        mapper.borrow_mut().irq_asserted = true;
+
+        cpu.tick(); cpu.tick(); cpu.tick(); // NOP, then the start of the IRQ
+        assert_eq!(cpu.is_running_interrupt_sequence, true);
+        set_nmi_line(&mut cpu, true);
+
+        run_until(&mut cpu, 0x9000);
+        assert_eq!(cpu.memory.get_8_bit_value(0x01FB) & 0b0001_0000, 0); // Still pushed like an IRQ
+    }
+
+    #[test]
+    fn instructions_take_as_many_cycles_as_the_instruction_set_says() {
+        let mut mismatches: Vec<String> = vec!();
+        for opcode in 0..0x100 {
+            let instruction = instruction_set::get_instruction(opcode as u8);
+            // Branches depend on the flags, and KIL never finishes. They're tested separately
+            if instruction.addressing_mode == AddressingMode::Relative || instruction.mnemonic == Mnemonic::Kil {
+                continue;
+            }
+
+            let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+            prg_rom[0x0000] = opcode as u8;
+            prg_rom[0x0001] = 0x10; // Zero page $10, or $0510 for absolute addressing
+            prg_rom[0x0002] = 0x05;
+
+            let mut cpu = create_test_cpu(prg_rom);
+            cpu.memory.set_16_bit_value(0x10, 0x0500); // Pointer for the indirect addressing modes
+            let cycles = count_instruction_cycles(&mut cpu);
+            if cycles != instruction.num_cycles as u32 {
+                mismatches.push(format!("{} ({:X}) took {} cycles instead of {}", instruction.name, opcode, cycles, instruction.num_cycles));
+            }
+        }
+        assert_eq!(mismatches, Vec::<String>::new());
+    }
+
+    #[test]
+    fn indexed_reads_take_an_extra_cycle_to_cross_a_page() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0xBD; // LDA $05FF,X
+        prg_rom[0x0001] = 0xFF;
+        prg_rom[0x0002] = 0x05;
+        prg_rom[0x0003] = 0xB1; // LDA ($10),Y
+        prg_rom[0x0004] = 0x10;
+
+        let mut cpu = create_test_cpu(prg_rom);
+        cpu.memory.set_16_bit_value(0x10, 0x05FF);
+        cpu.memory.set_8_bit_value(0x0600, 0x42);
+        cpu.x_register = 1;
+        cpu.y_register = 1;
+
+        assert_eq!(count_instruction_cycles(&mut cpu), 5);
+        assert_eq!(cpu.accumulator, 0x42);
+        cpu.accumulator = 0;
+        assert_eq!(count_instruction_cycles(&mut cpu), 6);
+        assert_eq!(cpu.accumulator, 0x42);
+    }
+
+    #[test]
+    fn indexed_writes_always_take_the_extra_cycle() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x9D; // STA $0500,X
+        prg_rom[0x0001] = 0x00;
+        prg_rom[0x0002] = 0x05;
+        prg_rom[0x0003] = 0x9D; // STA $05FF,X
+        prg_rom[0x0004] = 0xFF;
+        prg_rom[0x0005] = 0x05;
+
+        let mut cpu = create_test_cpu(prg_rom);
+        cpu.x_register = 1;
+        cpu.accumulator = 0x42;
+
+        assert_eq!(count_instruction_cycles(&mut cpu), 5);
+        assert_eq!(count_instruction_cycles(&mut cpu), 5);
+        assert_eq!(cpu.memory.get_8_bit_value(0x0501), 0x42);
+        assert_eq!(cpu.memory.get_8_bit_value(0x0600), 0x42);
+    }
+
+    #[test]
+    fn unstable_stores_and_the_value_with_the_high_byte_plus_1() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0x9E; // SHX $0500,Y
+        prg_rom[0x0001] = 0x00;
+        prg_rom[0x0002] = 0x05;
+        prg_rom[0x0003] = 0x9C; // SHY $0500,X
+        prg_rom[0x0004] = 0x00;
+        prg_rom[0x0005] = 0x05;
+        prg_rom[0x0006] = 0x9F; // AHX $05FF,Y
+        prg_rom[0x0007] = 0xFF;
+        prg_rom[0x0008] = 0x05;
+        prg_rom[0x0009] = 0x9B; // TAS $0530,Y
+        prg_rom[0x000A] = 0x30;
+        prg_rom[0x000B] = 0x05;
+
+        let mut cpu = create_test_cpu(prg_rom);
+        cpu.accumulator = 0xF3;
+        cpu.x_register = 0x07;
+        cpu.y_register = 0x03;
+
+        for _ in 0..4 {
+            count_instruction_cycles(&mut cpu);
+        }
+        assert_eq!(cpu.memory.get_8_bit_value(0x0503), 0x06);
+        assert_eq!(cpu.memory.get_8_bit_value(0x0507), 0x02);
+        assert_eq!(cpu.memory.get_8_bit_value(0x0602), 0x00);
+        assert_eq!(cpu.memory.get_8_bit_value(0x0202), 0x02); // Indexing crossed a page, so the value became the high byte of the address
+        assert_eq!(cpu.memory.get_8_bit_value(0x0533), 0x02);
+        assert_eq!(cpu.stack_pointer, 0x03);
+    }
+
+    #[test]
+    fn kil_jams_the_cpu() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0xE8; // INX
+        prg_rom[0x0001] = 0x02; // KIL
+
+        let mut cpu = create_test_cpu(prg_rom);
+        for _ in 0..100 {
+            cpu.tick();
+        }
+        assert_eq!(cpu.is_jammed(), true);
+        assert_eq!(cpu.x_register, 0x01);
+        assert_eq!(cpu.program_counter, 0x8002);
+
+        // Not even an NMI can get it going again
+        set_nmi_line(&mut cpu, true);
+        for _ in 0..100 {
+            cpu.tick();
+        }
+        assert_eq!(cpu.program_counter, 0x8002);
+    }
+
+    #[test]
+    fn branches_take_longer_when_taken_and_when_crossing_a_page() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0xF0; // BEQ +2 (Not taken)
+        prg_rom[0x0001] = 0x02;
+        prg_rom[0x0002] = 0xD0; // BNE +2 (Taken)
+        prg_rom[0x0003] = 0x02;
+        prg_rom[0x0006] = 0xD0; // BNE -16 (Taken, back into the previous page)
+        prg_rom[0x0007] = 0xF0;
+
+        let mut cpu = create_test_cpu(prg_rom);
+        assert_eq!(count_instruction_cycles(&mut cpu), 2);
+        assert_eq!(cpu.program_counter, 0x8002);
+        assert_eq!(count_instruction_cycles(&mut cpu), 3);
+        assert_eq!(cpu.program_counter, 0x8006);
+        assert_eq!(count_instruction_cycles(&mut cpu), 4);
+        assert_eq!(cpu.program_counter, 0x7FF8);
+    }
+
+    #[test]
+    fn read_modify_write_instructions_write_the_old_value_back_first() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0xEE; // INC $6000
+        prg_rom[0x0001] = 0x00;
+        prg_rom[0x0002] = 0x60;
+
+        let (mut cpu, mapper) = create_interrupt_test_cpu(prg_rom);
+        assert_eq!(count_instruction_cycles(&mut cpu), 6);
+        assert_eq!(mapper.borrow().reads, vec!(0x6000));
+        assert_eq!(mapper.borrow().writes, vec!((0x6000, 0x00), (0x6000, 0x01)));
+    }
+
+    #[test]
+    fn indexing_across_a_page_reads_the_unfixed_address_first() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0xBD; // LDA $60FF,X
+        prg_rom[0x0001] = 0xFF;
+        prg_rom[0x0002] = 0x60;
+        prg_rom[0x0003] = 0x9D; // STA $6000,X
+        prg_rom[0x0004] = 0x00;
+        prg_rom[0x0005] = 0x60;
+
+        let (mut cpu, mapper) = create_interrupt_test_cpu(prg_rom);
+        cpu.x_register = 1;
+        run_until(&mut cpu, 0x8006);
+        assert_eq!(mapper.borrow().reads, vec!(0x6000, 0x6100, 0x6001)); // STA reads before it writes, even without crossing
+        assert_eq!(mapper.borrow().writes, vec!((0x6001, 0x00)));
+    }
+
+    #[test]
+    fn taken_branches_delay_interrupts_by_an_instruction() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x0000] = 0xD0; // BNE +0 (Taken, without crossing a page)
+        prg_rom[0x0001] = 0x00;
+
+        let (mut cpu, mapper) = create_interrupt_test_cpu(prg_rom);
+        cpu.status_register &= !0x04; // Enable interrupts
+        cpu.tick();
+        mapper.borrow_mut().irq_asserted = true;
+
+        // Without the delay the IRQ would be taken straight after the branch
+        run_until(&mut cpu, 0xA000);
+        assert_eq!(cpu.memory.get_16_bit_value(0x01FC), 0x8003);
+    }
+
+    #[test]
+    fn dmc_sample_fetches_stall_the_cpu() {
+        let mut prg_rom: Vec<u8> = vec![0xEA as u8; 0x8000]; // NOP
+        prg_rom[0x4000] = 0x42; // First byte of the sample at $C000
+        prg_rom[0x7FFC] = 0x00;
+        prg_rom[0x7FFD] = 0x80;
+
+        let mut apu = APU::new(Region::Ntsc);
+        let mut cpu: CPU = CPU::new();
+        cpu.apu = &mut apu as *mut APU;
+        cpu.insert_cartridge(Rc::new(RefCell::new(Nrom::new(prg_rom, vec!(), 0x2000, Mirroring::Horizontal))));
+
+        cpu.write_to_memory_8(0x4013, 0); // 1 byte sample
+        cpu.write_to_memory_8(0x4015, 0b0001_0000);
+        assert_eq!(apu.get_dmc_sample_request(), Some(0xC000));
+
+        // The fetch takes 4 cycles, and then the NOP gets to run
+        for _ in 0..4 {
+            cpu.tick();
+            assert_eq!(cpu.program_counter, 0x8000);
+        }
+        assert_eq!(apu.get_dmc_sample_request(), None);
+        cpu.tick(); cpu.tick();
+        assert_eq!(cpu.program_counter, 0x8001);
     }
 }
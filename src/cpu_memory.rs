@@ -1,14 +1,21 @@
+use mapper::Cartridge as Cartridge;
+
 pub struct CPUMemory {
-    memory: [u8; 0x10000]
+    memory: [u8; 0x4020], // Internal RAM plus the PPU, APU and IO registers. Everything above this lives on the cartridge
+    cartridge: Option<Cartridge>
 }
 
+// Addresses from here and up are sent to the cartridge
+const CARTRIDGE_SPACE_START: u16 = 0x4020;
+
 //TODO need to somehow implement memory mirroring on either the getters or the setters
 //Not sure which is better yet... probably setters tho
 
 impl CPUMemory {
     pub fn new() -> CPUMemory {
         return CPUMemory {
-            memory: [0; 0x10000]
+            memory: [0; 0x4020],
+            cartridge: None
         }
     }
 
@@ -19,96 +26,96 @@ impl CPUMemory {
         }
     }
 
-    pub fn read_ppu_for_nmi(&mut self) -> bool {
-        unsafe {
-            let first_address: *mut u8 = self.memory.first_mut().unwrap();
-            let ppu_status_register: *mut u8 = first_address.offset(0x2002);
-            let nmi_triggered = (*ppu_status_register & 0x80) == 0x80;
-            *ppu_status_register &= !0x80; // Clear the register of the NMI now that it has been read
-
-            return nmi_triggered;
-        }
+    // The PPU pulls the NMI line while it's in vblank, as long as NMIs are enabled in PPUCTRL
+    pub fn is_ppu_nmi_asserted(&self) -> bool {
+        let are_nmis_enabled = (self.memory[0x2000] & 0x80) == 0x80;
+        let is_in_vblank = (self.memory[0x2002] & 0x80) == 0x80;
+        return are_nmis_enabled && is_in_vblank;
     }
 
-    pub fn are_nmis_enabled(&mut self) -> bool {
-        unsafe {
-            let first_address: *mut u8 = self.memory.first_mut().unwrap();
-            let ppu_control_register_1: *mut u8 = first_address.offset(0x2000);
-            return (*ppu_control_register_1 & 0x80) == 0x80;
-        }
+    pub fn insert_cartridge(&mut self, cartridge: Cartridge) {
+        self.cartridge = Some(cartridge);
     }
 
-    pub fn init_prg_rom(&mut self, prg_rom: Vec<u8>) {
-        // TODO this will need to be more sophisticated with a ROM that requires bank switching. Should handle this with pointers to different banks
-        // TODO can try to use https://stackoverflow.com/a/28224758 here instead of looping
-        for i in 0..prg_rom.len() {
-            let rom_byte = prg_rom[i];
-            self.memory[0x8000 + i] = rom_byte;
+    // The cartridge sees the CPU clock, so anything on it that needs to count time can do so
+    pub fn tick_cartridge(&mut self) {
+        if let Some(ref cartridge) = self.cartridge {
+            cartridge.borrow_mut().cpu_tick();
         }
+    }
 
-        // FIXME this is another temporary solution. For ROMs with 1 prg ROM bank, just copy it to both banks
-        if prg_rom.len() == 0x4000 {
-            for i in 0..prg_rom.len() {
-                let rom_byte = prg_rom[i];
-                self.memory[0xC000 + i] = rom_byte;
-            }
-        }
+    pub fn is_cartridge_irq_asserted(&self) -> bool {
+        return match self.cartridge {
+            Some(ref cartridge) => cartridge.borrow().is_irq_asserted(),
+            None => false
+        };
     }
 
     pub fn get_reset_vector(&self) -> u16 {
-        return ((self.memory[0xFFFD] as u16) << 8) | (self.memory[0xFFFC] as u16);
+        return self.get_16_bit_value(0xFFFC);
     }
 
     pub fn get_8_bit_value(&mut self, address: u16) -> u8 {
         if address == 0x2002 {
             // "When a read from $2002 occurs, bit 7 [of the status register] is reset to 0 as are $2005 and $2006."
-            // I think my handling of NMIs isn't very in-line with the real NES hardware. I reset bit 7 when I read for NMIs. So I don't reset it here
-            // FIXME? Maybe it's still a good idea to clear the NMI bit here though... remains to be seen
+            // The read still sees the flag. Clearing it also releases the NMI line
+            let status = self.memory[0x2002];
+            self.memory[0x2002] &= !0x80;
             self.memory[0x2005] = 0;
             self.memory[0x2006] = 0;
+            return status;
         }
-        return self.memory[address as usize];
+        return self.read(address);
     }
 
     pub fn get_16_bit_value(&self, address: u16) -> u16 {
         // Little Endian. Low byte is stored first
-        let high_byte: u16 = (self.memory[address as usize + 1] as u16) << 8;
-        let low_byte: u16 = self.memory[address as usize] as u16;
+        let high_byte: u16 = (self.read(address.wrapping_add(1)) as u16) << 8;
+        let low_byte: u16 = self.read(address) as u16;
         return high_byte | low_byte;
     }
 
     pub fn get_memory_range(&self, address: u16, num_bytes: u16) -> Vec<u8> {
-        let memory: &[u8] = &self.memory[(address as usize)..(address as usize + num_bytes as usize)];
-        let mut memory_copy: Vec<u8> = vec![0; num_bytes as usize];
-        memory_copy.copy_from_slice(memory);
-        return memory_copy;
+        return (0..num_bytes).map(|offset| self.read(address.wrapping_add(offset))).collect();
     }
 
     pub fn set_8_bit_value(&mut self, address: u16, value: u8) {
-        // TODO apparently some games intentionally write to invalid addresses as a no-op instead.
-        CPUMemory::check_valid_write(address);
+        if address >= CARTRIDGE_SPACE_START {
+            // The cartridge decides what a write means. Could be PRG RAM, a mapper register, or nothing at all
+            if let Some(ref cartridge) = self.cartridge {
+                cartridge.borrow_mut().write_prg(address, value);
+            }
+            return;
+        }
 
         self.memory[address as usize] = value;
     }
 
     #[allow(dead_code)] // Is actually used. It's used by tests. At least for now
     pub fn set_16_bit_value(&mut self, address: u16, value: u16) {
-        CPUMemory::check_valid_write(address);
-
-        self.memory[address as usize] = value as u8;
-        self.memory[address as usize + 1] = (value >> 8) as u8;
+        self.set_8_bit_value(address, value as u8);
+        self.set_8_bit_value(address.wrapping_add(1), (value >> 8) as u8);
     }
 
-    fn check_valid_write(address: u16) {
-        if address >= 0x8000 {
-            panic!("Attempting to write to read-only memory address: {}!", address);
+    fn read(&self, address: u16) -> u8 {
+        if address >= CARTRIDGE_SPACE_START {
+            return match self.cartridge {
+                Some(ref cartridge) => cartridge.borrow_mut().read_prg(address),
+                None => 0 // Nothing is plugged in to drive the bus
+            };
         }
+
+        return self.memory[address as usize];
     }
 }
 
 #[cfg(test)]
 mod tests {
     use cpu_memory::CPUMemory;
+    use mapper::Mirroring;
+    use mapper::Nrom;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     #[test]
     fn can_get_a_stored_8_bit_value() {
@@ -138,17 +145,19 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn cannot_8_bit_write_to_prg_rom() {
         let mut memory: CPUMemory = CPUMemory::new();
-        memory.set_8_bit_value(0x8000, 0x42); // 8 bit value stored as 16
+        memory.insert_cartridge(Rc::new(RefCell::new(Nrom::new(vec![0xFF as u8; 0x8000], vec!(), 0x2000, Mirroring::Horizontal))));
+        memory.set_8_bit_value(0x8000, 0x42);
+        assert_eq!(memory.get_8_bit_value(0x8000), 0xFF);
     }
 
     #[test]
-    #[should_panic]
     fn cannot_16_bit_write_to_prg_rom() {
         let mut memory: CPUMemory = CPUMemory::new();
-        memory.set_16_bit_value(0x8000, 0x4242); // 8 bit value stored as 16
+        memory.insert_cartridge(Rc::new(RefCell::new(Nrom::new(vec![0xFF as u8; 0x8000], vec!(), 0x2000, Mirroring::Horizontal))));
+        memory.set_16_bit_value(0x8000, 0x4242);
+        assert_eq!(memory.get_16_bit_value(0x8000), 0xFFFF);
     }
 
     #[test]
@@ -168,11 +177,23 @@ mod tests {
     }
 
     #[test]
-    fn init_prg_rom() {
+    fn cartridge_space_is_read_from_the_cartridge() {
         let mut memory: CPUMemory = CPUMemory::new();
-        memory.init_prg_rom(vec![0xFF as u8; 0x8000]);
+        memory.insert_cartridge(Rc::new(RefCell::new(Nrom::new(vec![0xFF as u8; 0x8000], vec!(), 0x2000, Mirroring::Horizontal))));
         assert_eq!(memory.get_8_bit_value(0x7999), 0x00);
         assert_eq!(memory.get_8_bit_value(0x8000), 0xFF);
         assert_eq!(memory.get_8_bit_value(0xFFFF), 0xFF);
     }
+
+    #[test]
+    fn reading_ppu_status_clears_vblank_and_releases_nmi() {
+        let mut memory: CPUMemory = CPUMemory::new();
+        memory.set_8_bit_value(0x2000, 0x80);
+        memory.set_8_bit_value(0x2002, 0xC0);
+        assert_eq!(memory.is_ppu_nmi_asserted(), true);
+
+        assert_eq!(memory.get_8_bit_value(0x2002), 0xC0);
+        assert_eq!(memory.get_8_bit_value(0x2002), 0x40); // Sprite 0 hit stays set
+        assert_eq!(memory.is_ppu_nmi_asserted(), false);
+    }
 }
@@ -0,0 +1,113 @@
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+// If we fall this many frames behind (a slow frame, the window being dragged around...) give up on catching up.
+// Otherwise the emulator would race through the backlog as fast as it could
+const MAX_FRAMES_BEHIND: u32 = 4;
+
+// Keeps the emulator running at the console's frame rate, instead of as fast as the host can go
+pub struct FramePacer {
+    frame_duration: Duration,
+    speed_multiplier: f64,
+    next_frame_time: Option<Instant>
+}
+
+impl FramePacer {
+    pub fn new(frames_per_second: f64) -> FramePacer {
+        return FramePacer {
+            frame_duration: duration_from_seconds(1.0 / frames_per_second),
+            speed_multiplier: 1.0,
+            next_frame_time: None
+        }
+    }
+
+    // Fast-forwarding runs some multiple of full speed. 1.0 is back to normal
+    pub fn set_speed_multiplier(&mut self, speed_multiplier: f64) {
+        self.speed_multiplier = speed_multiplier;
+    }
+
+    // Forgets about the previous frames. Used when something else (like the audio) has been deciding when frames happen,
+    // or after being paused, so the time spent waiting isn't raced through afterwards
+    pub fn reset(&mut self) {
+        self.next_frame_time = None;
+    }
+
+    // Called once a frame has been finished. Blocks until it's time to start the next one
+    pub fn wait_for_next_frame(&mut self) {
+        if let Some(sleep_duration) = self.get_time_until_next_frame(Instant::now()) {
+            thread::sleep(sleep_duration);
+        }
+    }
+
+    fn get_time_until_next_frame(&mut self, now: Instant) -> Option<Duration> {
+        let frame_duration = duration_from_seconds(as_seconds(self.frame_duration) / self.speed_multiplier);
+        let next_frame_time = match self.next_frame_time {
+            Some(previous_frame_time) => previous_frame_time + frame_duration,
+            None => now + frame_duration
+        };
+
+        // Deadlines are kept on a fixed schedule rather than being set relative to now. That way the time it takes to
+        // wake up from sleeping doesn't add up, and the average frame rate comes out exactly right
+        if next_frame_time > now {
+            self.next_frame_time = Some(next_frame_time);
+            return Some(next_frame_time - now);
+        }
+
+        if now - next_frame_time > frame_duration * MAX_FRAMES_BEHIND {
+            self.next_frame_time = Some(now);
+        } else {
+            self.next_frame_time = Some(next_frame_time);
+        }
+        return None;
+    }
+}
+
+fn duration_from_seconds(seconds: f64) -> Duration {
+    return Duration::from_nanos((seconds * 1_000_000_000.0) as u64);
+}
+
+fn as_seconds(duration: Duration) -> f64 {
+    return duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1_000_000_000.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use frame_pacer::FramePacer;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn frames_are_scheduled_from_the_previous_deadline() {
+        let mut frame_pacer = FramePacer::new(50.0);
+        let start = Instant::now();
+        assert_eq!(frame_pacer.get_time_until_next_frame(start), Some(Duration::from_millis(20)));
+
+        // Woke up a little late. The next frame should make up for it
+        let woke_up = start + Duration::from_millis(22);
+        assert_eq!(frame_pacer.get_time_until_next_frame(woke_up), Some(Duration::from_millis(18)));
+    }
+
+    #[test]
+    fn fast_forward_shortens_frames() {
+        let mut frame_pacer = FramePacer::new(50.0);
+        frame_pacer.set_speed_multiplier(4.0);
+        assert_eq!(frame_pacer.get_time_until_next_frame(Instant::now()), Some(Duration::from_millis(5)));
+    }
+
+    #[test]
+    fn falling_far_behind_starts_a_new_schedule() {
+        let mut frame_pacer = FramePacer::new(50.0);
+        let start = Instant::now();
+        frame_pacer.get_time_until_next_frame(start);
+
+        // A little behind. Run the next frame right away to catch up
+        assert_eq!(frame_pacer.get_time_until_next_frame(start + Duration::from_millis(50)), None);
+        assert_eq!(frame_pacer.get_time_until_next_frame(start + Duration::from_millis(50)), Some(Duration::from_millis(10)));
+
+        // Very far behind. Don't try to catch up
+        let much_later = start + Duration::from_secs(5);
+        assert_eq!(frame_pacer.get_time_until_next_frame(much_later), None);
+        assert_eq!(frame_pacer.get_time_until_next_frame(much_later), Some(Duration::from_millis(20)));
+    }
+}
@@ -0,0 +1,70 @@
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 240;
+
+// Where the PPU draws. Each pixel is an index into the NES's palette, not a color. That's one of the 64 colors the NES
+// has, with the 3 color emphasis bits from $2001 on top of it (see palette.rs)
+// The PPU draws into one buffer while the other holds the last finished frame, so the frontend never sees half a frame
+pub struct Framebuffer {
+    drawing: Vec<u16>,
+    finished: Vec<u16>,
+    finished_frame_count: u32
+}
+
+impl Framebuffer {
+    pub fn new() -> Framebuffer {
+        return Framebuffer {
+            drawing: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            finished: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            finished_frame_count: 0
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: u8, y: u8, pixel: u16) {
+        self.drawing[y as usize * SCREEN_WIDTH + x as usize] = pixel;
+    }
+
+    // Reads back from the frame being drawn
+    #[allow(dead_code)] // Used by tests
+    pub fn get_pixel(&self, x: u8, y: u8) -> u16 {
+        return self.drawing[y as usize * SCREEN_WIDTH + x as usize];
+    }
+
+    // The frame being drawn becomes the finished one, and drawing starts over on a blank frame
+    pub fn finish_frame(&mut self) {
+        ::std::mem::swap(&mut self.drawing, &mut self.finished);
+        for pixel in self.drawing.iter_mut() {
+            *pixel = 0;
+        }
+        self.finished_frame_count += 1;
+    }
+
+    #[cfg(any(feature = "sdl", test))]
+    pub fn get_finished_frame_count(&self) -> u32 {
+        return self.finished_frame_count;
+    }
+
+    // Rows of SCREEN_WIDTH pixels, top to bottom
+    #[cfg(any(feature = "sdl", test))]
+    pub fn get_finished_frame(&self) -> &[u16] {
+        return &self.finished;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use framebuffer::Framebuffer;
+    use framebuffer::SCREEN_WIDTH;
+
+    #[test]
+    fn drawing_is_only_visible_once_the_frame_is_finished() {
+        let mut framebuffer = Framebuffer::new();
+        framebuffer.set_pixel(3, 2, 0x21);
+        assert_eq!(framebuffer.get_pixel(3, 2), 0x21);
+        assert_eq!(framebuffer.get_finished_frame()[2 * SCREEN_WIDTH + 3], 0);
+
+        framebuffer.finish_frame();
+        assert_eq!(framebuffer.get_finished_frame_count(), 1);
+        assert_eq!(framebuffer.get_finished_frame()[2 * SCREEN_WIDTH + 3], 0x21);
+        assert_eq!(framebuffer.get_pixel(3, 2), 0);
+    }
+}
@@ -1,6 +1,7 @@
 extern crate sdl2;
 use sdl2::Sdl;
-use sdl2::pixels::Color;
+use sdl2::audio::AudioQueue;
+use sdl2::audio::AudioSpecDesired;
 use sdl2::pixels::PixelFormatEnum;
 use sdl2::rect::Rect;
 use sdl2::render::Canvas;
@@ -8,58 +9,73 @@ use sdl2::video::Window;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 
-use controlletron::Controlletron as Controlletron;
+use audio::AudioSink;
+use controlletron::Button;
+use controlletron::Player;
+use framebuffer;
+use nes::Nes;
+use palette::Palette;
 
-#[allow(dead_code)]
+// The SDL frontend. Shows the frames the NES draws, and turns keyboard input into controller input
 pub struct GameWindow {
-    things_to_draw: [[u8; 240]; 256],
     canvas: Canvas<Window>,
     pub sdl_context: Sdl,
-    pub controlletron: Controlletron
+    palette: Palette,
+    fast_forward_held: bool,
+    paused: bool,
+    frame_advance_requested: bool
 }
 
-const SCREEN_WIDTH: u16 = 256;
-const SCREEN_HEIGHT: u16 = 240;
+const SCREEN_WIDTH: u16 = framebuffer::SCREEN_WIDTH as u16;
 const OVERSCAN_HEIGHT: u8 = 8;
-const EFFECTIVE_SCREEN_HEIGHT: u16 = 240 - (OVERSCAN_HEIGHT as u16 * 2);
-const SCALING: u8 = 3;
+const EFFECTIVE_SCREEN_HEIGHT: u16 = framebuffer::SCREEN_HEIGHT as u16 - (OVERSCAN_HEIGHT as u16 * 2);
+
+// Keys that control the emulator itself, rather than the game
+const FAST_FORWARD_KEY: Keycode = Keycode::Tab; // Held down
+const PAUSE_KEY: Keycode = Keycode::P;
+const FRAME_ADVANCE_KEY: Keycode = Keycode::Period; // Pauses, then runs a single frame each time it's pressed
 
-#[allow(dead_code)]
 impl GameWindow {
     //noinspection RsFieldInitShorthand
-    pub fn new() -> GameWindow {
-
+    pub fn new(scaling: u8, vsync: bool, palette: Palette) -> GameWindow {
         let sdl_context = sdl2::init().unwrap();
         let video_subsystem = sdl_context.video().unwrap();
 
-        let window = video_subsystem.window("MadNes", SCREEN_WIDTH as u32 * SCALING as u32, EFFECTIVE_SCREEN_HEIGHT as u32 * SCALING as u32)
+        let window = video_subsystem.window("MadNes", SCREEN_WIDTH as u32 * scaling as u32, EFFECTIVE_SCREEN_HEIGHT as u32 * scaling as u32)
             .position_centered()
             .opengl()
             .build()
             .unwrap();
 
-        let mut canvas = window.into_canvas().build().unwrap();
-        canvas.set_scale(SCALING as f32, SCALING as f32).unwrap();
-
-        let things_to_draw = [[0u8; 240]; 256];
+        let mut canvas_builder = window.into_canvas();
+        if vsync {
+            canvas_builder = canvas_builder.present_vsync();
+        }
+        let mut canvas = canvas_builder.build().unwrap();
+        canvas.set_scale(scaling as f32, scaling as f32).unwrap();
 
         canvas.present();
 
         return GameWindow {
-            things_to_draw: things_to_draw, // TODO make it a color
             canvas: canvas,
             sdl_context: sdl_context,
-            controlletron: Controlletron::new()
+            palette: palette,
+            fast_forward_held: false,
+            paused: false,
+            frame_advance_requested: false
         }
     }
 
-    pub fn repaint(&mut self) {
+    // Takes a frame of palette indexes from the NES's framebuffer
+    pub fn repaint(&mut self, frame: &[u16]) {
         self.canvas.clear();
 
-        self.create_texture();
+        self.create_texture(frame);
         self.canvas.present();
-        self.things_to_draw = [[0u8; 240]; 256];
+    }
 
+    // Needs to be called every frame, whether or not anything was drawn. Otherwise the OS thinks we've stopped responding
+    pub fn poll_events(&mut self, nes: &mut Nes) {
         let mut event_pump = self.sdl_context.event_pump().unwrap();
 
         for event in event_pump.poll_iter() {
@@ -67,31 +83,75 @@ impl GameWindow {
                 Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     panic!("Exiting game");
                 },
-                Event::KeyDown { keycode, ..} => {
-                    self.controlletron.receive_key_input(keycode.unwrap(), true)
+                Event::KeyDown { keycode: Some(keycode), repeat, .. } => {
+                    self.receive_hotkey_input(keycode, true, repeat);
+                    GameWindow::receive_key_input(nes, keycode, true)
                 },
-                Event::KeyUp { keycode, ..} => {
-                    self.controlletron.receive_key_input(keycode.unwrap(), false)
+                Event::KeyUp { keycode: Some(keycode), repeat, .. } => {
+                    self.receive_hotkey_input(keycode, false, repeat);
+                    GameWindow::receive_key_input(nes, keycode, false)
                 }
                 _ => { }
             }
         }
     }
 
-    fn create_texture(&mut self) {
+    // These are all currently hard coded. But eventually, they should be remappable
+    fn receive_key_input(nes: &mut Nes, keycode: Keycode, is_keydown: bool) {
+        let button = match keycode {
+            Keycode::Z => Button::A,
+            Keycode::X => Button::B,
+            Keycode::Backspace => Button::Select,
+            Keycode::Return => Button::Start,
+            Keycode::Up => Button::Up,
+            Keycode::Down => Button::Down,
+            Keycode::Left => Button::Left,
+            Keycode::Right => Button::Right,
+            _ => return
+        };
+        nes.set_button(Player::One, button, is_keydown);
+    }
+
+    fn receive_hotkey_input(&mut self, keycode: Keycode, is_keydown: bool, is_repeat: bool) {
+        if keycode == FAST_FORWARD_KEY {
+            self.fast_forward_held = is_keydown;
+        } else if keycode == PAUSE_KEY && is_keydown && !is_repeat {
+            self.paused = !self.paused;
+        } else if keycode == FRAME_ADVANCE_KEY && is_keydown {
+            self.paused = true;
+            self.frame_advance_requested = true;
+        }
+    }
+
+    pub fn is_fast_forwarding(&self) -> bool {
+        return self.fast_forward_held;
+    }
+
+    // Frame advance lets a single frame through, even while paused
+    pub fn should_run_next_frame(&mut self) -> bool {
+        if self.frame_advance_requested {
+            self.frame_advance_requested = false;
+            return true;
+        }
+        return !self.paused;
+    }
+
+    fn create_texture(&mut self, frame: &[u16]) {
         let texture_creator = self.canvas.texture_creator();
 
         let mut texture = texture_creator.create_texture_streaming(
             PixelFormatEnum::RGB24, SCREEN_WIDTH as u32, EFFECTIVE_SCREEN_HEIGHT as u32).unwrap();
 
+        let palette = &self.palette;
         texture.with_lock(None, |buffer: &mut [u8], pitch: usize| {
             for y in 0..EFFECTIVE_SCREEN_HEIGHT {
                 for x in 0..SCREEN_WIDTH {
                     let offset = x as usize * 3 + y as usize * pitch;
-                    let color = self.get_color(self.things_to_draw[x as usize][(y + OVERSCAN_HEIGHT as u16) as usize]);
-                    buffer[offset] = color.r;
-                    buffer[offset + 1] = color.g;
-                    buffer[offset + 2] = color.b;
+                    let frame_index = (y + OVERSCAN_HEIGHT as u16) as usize * SCREEN_WIDTH as usize + x as usize;
+                    let color = palette.get_color(frame[frame_index]);
+                    buffer[offset] = color[0];
+                    buffer[offset + 1] = color[1];
+                    buffer[offset + 2] = color[2];
                 }
             }
         }).unwrap();
@@ -101,95 +161,43 @@ impl GameWindow {
                          Some(Rect::new(0, 0, SCREEN_WIDTH as u32, EFFECTIVE_SCREEN_HEIGHT as u32))
         ).unwrap();
     }
+}
 
-    // Color will need to be better than a u8 later
-    pub fn set_pixel_color(&mut self, color: u8, x: u8, y: u8) {
-        self.things_to_draw[x as usize][y as usize] = color;
-    }
+// Plays audio through an SDL audio queue. SDL pulls from the queue on its own thread, so all we have to do is keep it topped up
+pub struct SdlAudioSink {
+    queue: AudioQueue<f32>
+}
 
-    // TODO make transparency work with color
-    pub fn is_pixel_transparent(&self, x: u8, y: u8) -> bool {
-        return self.things_to_draw[x as usize][y as usize] == 0;
+impl SdlAudioSink {
+    pub fn open(sdl_context: &Sdl, sample_rate: u32) -> Result<SdlAudioSink, String> {
+        let audio_subsystem = sdl_context.audio()?;
+        let spec = AudioSpecDesired {
+            freq: Some(sample_rate as i32),
+            channels: Some(1),
+            samples: Some(1024)
+        };
+
+        let queue = audio_subsystem.open_queue::<f32, _>(None, &spec)?;
+        queue.resume();
+
+        return Ok(SdlAudioSink {
+            queue: queue
+        });
     }
+}
 
-    // Used for debugging right now
-    pub fn get_pixel_value(&self, x: u8, y: u8) -> u8 {
-        return self.things_to_draw[x as usize][y as usize];
+impl AudioSink for SdlAudioSink {
+    // SDL might not give us the rate we asked for
+    fn get_sample_rate(&self) -> u32 {
+        return self.queue.spec().freq as u32;
     }
 
-    pub fn get_color(&self, color: u8) -> Color {
-        match color {
-            0x00 => Color::RGB(84, 84, 84),
-            0x01 => Color::RGB(0, 20, 116),
-            0x02 => Color::RGB(8, 16, 144),
-            0x03 => Color::RGB(48, 0, 136),
-            0x04 => Color::RGB(68, 0, 100),
-            0x05 => Color::RGB(92, 0, 48),
-            0x06 => Color::RGB(84, 4, 0),
-            0x07 => Color::RGB(60, 24, 0),
-            0x08 => Color::RGB(32, 42, 0),
-            0x09 => Color::RGB(8, 58, 0),
-            0x0A => Color::RGB(0, 64, 0),
-            0x0B => Color::RGB(0, 60, 0),
-            0x0C => Color::RGB(0, 50, 60),
-            0x0D => Color::RGB(0, 0, 0),
-            0x0E => Color::RGB(0, 0, 0),
-            0x0F => Color::RGB(0, 0, 0),
-
-            0x10 => Color::RGB(152, 150, 152),
-            0x11 => Color::RGB(8, 76, 196),
-            0x12 => Color::RGB(48, 50, 226),
-            0x13 => Color::RGB(92, 30, 228),
-            0x14 => Color::RGB(136, 20, 176),
-            0x15 => Color::RGB(160, 20, 100),
-            0x16 => Color::RGB(152, 34, 32),
-            0x17 => Color::RGB(120, 60, 0),
-            0x18 => Color::RGB(84, 90, 0),
-            0x19 => Color::RGB(40, 114, 0),
-            0x1A => Color::RGB(8, 124, 0),
-            0x1B => Color::RGB(0, 118, 40),
-            0x1C => Color::RGB(0, 102, 120),
-            0x1D => Color::RGB(0, 0, 0),
-            0x1E => Color::RGB(0, 0, 0),
-            0x1F => Color::RGB(0, 0, 0),
-
-            0x20 => Color::RGB(236, 238, 236),
-            0x21 => Color::RGB(76, 154, 236),
-            0x22 => Color::RGB(120, 124, 236),
-            0x23 => Color::RGB(176, 98, 236),
-            0x24 => Color::RGB(228, 84, 236),
-            0x25 => Color::RGB(236, 88, 180),
-            0x26 => Color::RGB(236, 106, 100),
-            0x27 => Color::RGB(212, 136, 32),
-            0x28 => Color::RGB(160, 170, 0),
-            0x29 => Color::RGB(116, 196, 0),
-            0x2A => Color::RGB(76, 208, 32),
-            0x2B => Color::RGB(56, 204, 108),
-            0x2C => Color::RGB(56, 180, 204),
-            0x2D => Color::RGB(60, 60, 60),
-            0x2E => Color::RGB(0, 0, 0),
-            0x2F => Color::RGB(0, 0, 0),
-
-            0x30 => Color::RGB(236, 238, 236),
-            0x31 => Color::RGB(168, 204, 236),
-            0x32 => Color::RGB(188, 188, 236),
-            0x33 => Color::RGB(212, 178, 236),
-            0x34 => Color::RGB(236, 174, 236),
-            0x35 => Color::RGB(236, 174, 212),
-            0x36 => Color::RGB(236, 180, 176),
-            0x37 => Color::RGB(228, 196, 114),
-            0x38 => Color::RGB(204, 210, 120),
-            0x39 => Color::RGB(180, 222, 120),
-            0x3A => Color::RGB(168, 226, 114),
-            0x3B => Color::RGB(152, 226, 180),
-            0x3C => Color::RGB(160, 214, 228),
-            0x3D => Color::RGB(160, 162, 160),
-            0x3E => Color::RGB(0, 0, 0),
-            0x3F => Color::RGB(0, 0, 0),
-            _ => panic!("Encountered unexpected color value: {}", color)
-        }
+    fn queue_samples(&mut self, samples: &[f32]) {
+        self.queue.queue(samples);
     }
 
+    fn get_queued_seconds(&self) -> Option<f64> {
+        let queued_samples = self.queue.size() as f64 / 4.0; // size() is in bytes, and each f32 sample is 4 of them
+        return Some(queued_samples / self.get_sample_rate() as f64);
+    }
 }
-
-
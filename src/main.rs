@@ -1,3 +1,5 @@
+
+#[cfg(feature = "sdl")]
 extern crate sdl2;
 
 mod rom_header;
@@ -7,41 +9,297 @@ mod cpu;
 mod ppu;
 mod ppu_memory;
 mod instruction_set;
-mod game_window;
 mod controlletron;
+mod options;
+mod region;
+mod mapper;
+mod apu;
+mod audio;
+#[cfg(feature = "sdl")]
+mod frame_pacer;
+mod framebuffer;
+#[cfg(feature = "sdl")]
+mod palette;
+mod nes;
+mod nestest;
+#[cfg(feature = "sdl")]
+mod game_window;
 
 use rom::Rom as Rom;
-use cpu::CPU as CPU;
-use ppu::PPU as PPU;
-//use std::thread;
-//use std::time::Duration;
+use rom_header::ConsoleType;
+use rom_header::RomHeader;
+use nes::Nes as Nes;
+use region::Region as Region;
+use audio::AudioOutput;
+use audio::WavSink;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::process;
+use std::time::Instant;
+
+#[cfg(feature = "sdl")]
+use game_window::GameWindow as GameWindow;
+#[cfg(feature = "sdl")]
+use game_window::SdlAudioSink;
+#[cfg(feature = "sdl")]
+use frame_pacer::FramePacer;
+#[cfg(feature = "sdl")]
+use palette::Palette;
+#[cfg(feature = "sdl")]
+use std::thread;
+#[cfg(feature = "sdl")]
+use std::time::Duration;
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if options::wants_help(&args) {
+        println!("{}", options::USAGE);
+        return;
+    }
+
+    let options = match options::parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, options::USAGE);
+            process::exit(2);
+        }
+    };
+
+    let rom: Rom = match Rom::load(&options.rom_path) {
+        Ok(rom) => rom,
+        Err(error) => {
+            eprintln!("Could not load '{}': {}", options.rom_path, error);
+            process::exit(1);
+        }
+    };
+
+    warn_about_unemulated_hardware(&rom.rom_header);
+
+    // An explicit choice wins, then whatever the ROM says, then tags like (E) in the file name
+    let region = options.region
+        .or(rom.rom_header.get_region())
+        .or(Region::from_file_name(&options.rom_path))
+        .unwrap_or(Region::Ntsc);
+
+    let cartridge = match mapper::create_cartridge(rom) {
+        Ok(cartridge) => cartridge,
+        Err(error) => {
+            eprintln!("Could not load '{}': {}", options.rom_path, error);
+            process::exit(1);
+        }
+    };
+
+    let mut nes = Nes::new(cartridge, region);
+    nes.set_frame_skip(options.frame_skip);
+    nes.set_sprite_limit_removed(options.sprite_limit_removed);
+
+    if let Some(ref nestest_log_path) = options.nestest_log_path {
+        run_nestest(&mut nes, nestest_log_path, &options.trace_path);
+    } else if options.headless {
+        run_headless(&mut nes, &options, region);
+    } else {
+        run_windowed(&mut nes, &options, region);
+    }
+}
 
-    let rom: Rom = rom::read_file().expect("Wow just terrible");
-    let mut cpu: CPU = CPU::new();
-    cpu.init_prg_rom(rom.prg_rom);
-    let mut ppu: PPU = PPU::new(cpu.get_ppu_io_registers_address());
-    cpu.init_late_pointers(&mut ppu as *mut PPU);
-    ppu.init_chr_rom(rom.chr_rom);
+// The game will still be run, but it might not get very far
+fn warn_about_unemulated_hardware(rom_header: &RomHeader) {
+    match rom_header.get_console_type() {
+        ConsoleType::Nes => { },
+        ConsoleType::VsSystem => eprintln!("Vs. System hardware is not emulated. Running as a regular NES"),
+        ConsoleType::PlayChoice10 => eprintln!("PlayChoice-10 hardware is not emulated. Running as a regular NES"),
+        ConsoleType::Extended(console_type) => eprintln!("Console type {} is not emulated. Running as a regular NES", console_type)
+    }
 
-    // 46.561 microseconds
-//    let sleep_nanoseconds = Duration::new(0, 46_561);
+    // 0 means the ROM doesn't say, and 1 is the standard controllers
+    let expansion_device = rom_header.get_default_expansion_device();
+    if expansion_device > 1 {
+        eprintln!("The game expects expansion device {}, which is not emulated. Using the standard controllers", expansion_device);
+    }
+}
 
-    let mut clock_cycle = 0;
+// Runs as fast as it can, with nothing to show for it other than the WAV file (if one was asked for)
+fn run_headless(nes: &mut Nes, options: &options::Options, region: Region) {
+    let mut audio_output = match options.wav_path {
+        Some(ref wav_path) => Some(create_wav_output(wav_path, options.sample_rate, region)),
+        None => None
+    };
+
+    let start_time = Instant::now();
     loop {
-        // The CPU receives a clock signal only once for every 3 times the PPU does
-        clock_cycle -= 1;
-        if clock_cycle <= 0 {
-            cpu.tick();
-            clock_cycle = 3;
+        nes.run_frame(&mut audio_output);
+        if let Some(ref mut audio_output) = audio_output {
+            audio_output.end_frame();
         }
 
-        ppu.tick();
+        if nes.is_cpu_jammed() {
+            eprintln!("The CPU jammed after {} frames", nes.get_frame_count());
+            break;
+        }
+        if has_reached_frame_limit(nes, options) {
+            break;
+        }
+    }
 
-        // Jank timer 'implementation'
-//        thread::sleep(sleep_nanoseconds);
+    if options.benchmark {
+        let seconds = start_time.elapsed().as_secs_f64();
+        println!("Ran {} frames ({} instructions) in {:.2} seconds. {:.0} frames per second, {:.0} instructions per second",
+            nes.get_frame_count(), nes.get_instruction_count(), seconds,
+            nes.get_frame_count() as f64 / seconds, nes.get_instruction_count() as f64 / seconds);
     }
-//    */
 }
 
+// Compares the CPU with a reference trace of nestest. Exits with 1 if they don't match
+fn run_nestest(nes: &mut Nes, reference_log_path: &str, trace_path: &Option<String>) {
+    let reference_log = match fs::read_to_string(reference_log_path) {
+        Ok(reference_log) => reference_log,
+        Err(error) => {
+            eprintln!("Could not read '{}': {}", reference_log_path, error);
+            process::exit(1);
+        }
+    };
+
+    let mut trace_file = match *trace_path {
+        Some(ref trace_path) => match File::create(trace_path) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(error) => {
+                eprintln!("Could not create '{}': {}", trace_path, error);
+                process::exit(1);
+            }
+        },
+        None => None
+    };
+
+    let result = nestest::run(nes, &reference_log, |line| {
+        if let Some(ref mut trace_file) = trace_file {
+            if let Err(error) = writeln!(trace_file, "{}", line) {
+                eprintln!("Could not write the trace: {}", error);
+                process::exit(1);
+            }
+        }
+    });
+    if let Some(ref mut trace_file) = trace_file {
+        if let Err(error) = trace_file.flush() {
+            eprintln!("Could not write the trace: {}", error);
+            process::exit(1);
+        }
+    }
+
+    let (official_result, unofficial_result) = nestest::get_result_codes(nes);
+    match result {
+        Ok(line_count) => println!("All {} lines of the trace matched. nestest's result codes are {:02X} and {:02X}",
+            line_count, official_result, unofficial_result),
+        Err(divergence) => {
+            eprintln!("The trace stopped matching the reference log\n{}", divergence);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "sdl")]
+fn run_windowed(nes: &mut Nes, options: &options::Options, region: Region) {
+    let palette = match Palette::from_source(&options.palette) {
+        Ok(palette) => palette,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+
+    let mut game_window = GameWindow::new(options.scale, options.vsync, palette);
+    let mut audio_output = match options.wav_path {
+        Some(ref wav_path) => Some(create_wav_output(wav_path, options.sample_rate, region)),
+        None => open_sdl_audio_output(&game_window, options.sample_rate, region)
+    };
+    let mut frame_pacer = FramePacer::new(region.get_frame_rate());
+
+    let mut drawn_frame_count = 0;
+    let mut was_cpu_jammed = false;
+    loop {
+        nes.run_frame(&mut audio_output);
+        if nes.is_cpu_jammed() && !was_cpu_jammed {
+            eprintln!("The CPU jammed after {} frames. The game has crashed", nes.get_frame_count());
+            was_cpu_jammed = true;
+        }
+
+        if nes.get_drawn_frame_count() != drawn_frame_count {
+            drawn_frame_count = nes.get_drawn_frame_count();
+            game_window.repaint(nes.get_frame());
+        }
+        game_window.poll_events(nes);
+
+        pace_frame(&game_window, &mut audio_output, &mut frame_pacer, options.fast_forward_speed);
+        if !game_window.should_run_next_frame() {
+            while !game_window.should_run_next_frame() {
+                thread::sleep(Duration::from_millis(10));
+                game_window.poll_events(nes);
+            }
+            frame_pacer.reset();
+        }
+
+        if has_reached_frame_limit(nes, options) {
+            return;
+        }
+    }
+}
+
+#[cfg(not(feature = "sdl"))]
+fn run_windowed(_nes: &mut Nes, _options: &options::Options, _region: Region) {
+    eprintln!("This build has no display. Run with --headless, or rebuild with the 'sdl' feature");
+    process::exit(2);
+}
+
+fn has_reached_frame_limit(nes: &Nes, options: &options::Options) -> bool {
+    return match options.frame_limit {
+        Some(frame_limit) => nes.get_frame_count() >= frame_limit,
+        None => false
+    };
+}
+
+// Decides how long to wait before starting on the next frame. Normally the audio sets the pace, since it has to be fed at
+// exactly the right rate to keep from crackling. When there's no audio to follow (or it's being skipped) the frame pacer does it instead
+#[cfg(feature = "sdl")]
+fn pace_frame(game_window: &GameWindow, audio_output: &mut Option<AudioOutput>, frame_pacer: &mut FramePacer, fast_forward_speed: u8) {
+    let is_fast_forwarding = game_window.is_fast_forwarding();
+
+    let mut is_paced_by_audio = false;
+    if let Some(ref mut audio_output) = *audio_output {
+        if is_fast_forwarding {
+            audio_output.skip_frame();
+        } else {
+            audio_output.end_frame();
+            is_paced_by_audio = audio_output.is_real_time();
+        }
+    }
+
+    if is_paced_by_audio {
+        frame_pacer.reset();
+    } else {
+        frame_pacer.set_speed_multiplier(if is_fast_forwarding { fast_forward_speed as f64 } else { 1.0 });
+        frame_pacer.wait_for_next_frame();
+    }
+}
+
+fn create_wav_output(wav_path: &str, sample_rate: u32, region: Region) -> AudioOutput {
+    match WavSink::create(wav_path, sample_rate) {
+        Ok(wav_sink) => return AudioOutput::new(Box::new(wav_sink), region.get_cpu_clock_rate()),
+        Err(error) => {
+            eprintln!("Could not create '{}': {}", wav_path, error);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "sdl")]
+fn open_sdl_audio_output(game_window: &GameWindow, sample_rate: u32, region: Region) -> Option<AudioOutput> {
+    match SdlAudioSink::open(&game_window.sdl_context, sample_rate) {
+        Ok(sdl_sink) => return Some(AudioOutput::new(Box::new(sdl_sink), region.get_cpu_clock_rate())),
+        Err(error) => {
+            eprintln!("Could not open an audio device. Continuing without sound: {}", error);
+            return None;
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use mapper::Mapper;
+use mapper::Mirroring;
+use mapper::borrow_chr_ram;
+use mapper::copy_chr_ram;
+use mapper::create_chr_memory;
+
+// Mapper 7. Used by Battletoads, Marble Madness and most other Rare games.
+// Bits 0 - 2 of a write to $8000 - $FFFF select a 32 KB PRG bank. Instead of horizontal or vertical mirroring,
+// bit 4 selects which 1 KB page of VRAM is used for all four nametables
+pub struct Axrom {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    bank_register: u8,
+    has_bus_conflicts: bool // ANROM has them, AOROM doesn't
+}
+
+const PRG_BANK_SIZE: usize = 0x8000;
+
+impl Axrom {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, chr_ram_size: usize, has_bus_conflicts: bool) -> Axrom {
+        let (chr, chr_is_ram) = create_chr_memory(chr_rom, chr_ram_size);
+        return Axrom {
+            prg_rom: prg_rom,
+            chr: chr,
+            chr_is_ram: chr_is_ram,
+            bank_register: 0,
+            has_bus_conflicts: has_bus_conflicts
+        }
+    }
+}
+
+impl Mapper for Axrom {
+    fn read_prg(&mut self, address: u16) -> u8 {
+        if address < 0x8000 {
+            return 0;
+        }
+
+        let num_banks = (self.prg_rom.len() / PRG_BANK_SIZE).max(1);
+        let bank = (self.bank_register & 0b0111) as usize % num_banks;
+        return self.prg_rom[(bank * PRG_BANK_SIZE + address as usize - 0x8000) % self.prg_rom.len()];
+    }
+
+    fn write_prg(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.bank_register = if self.has_bus_conflicts { value & self.read_prg(address) } else { value };
+        }
+    }
+
+    fn read_chr(&mut self, address: u16) -> u8 {
+        return self.chr[address as usize % self.chr.len()];
+    }
+
+    fn write_chr(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let chr_length = self.chr.len();
+            self.chr[address as usize % chr_length] = value;
+        }
+    }
+
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return borrow_chr_ram(&self.chr, self.chr_is_ram);
+    }
+
+    fn load_chr_ram(&mut self, chr_ram: &[u8]) -> Result<(), String> {
+        return copy_chr_ram(&mut self.chr, self.chr_is_ram, chr_ram);
+    }
+
+    fn get_mirroring(&self) -> Mirroring {
+        if (self.bank_register & 0b1_0000) == 0 {
+            return Mirroring::SingleScreenLower;
+        } else {
+            return Mirroring::SingleScreenUpper;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper::Mapper;
+    use mapper::create_banked_rom;
+    use mapper::Mirroring;
+    use mapper::axrom::Axrom;
+
+    #[test]
+    fn prg_bank_switches_32k_at_a_time() {
+        let mut axrom = Axrom::new(create_banked_rom(8, 0x8000), vec!(), 0x2000, false);
+        assert_eq!(axrom.read_prg(0x8000), 0);
+
+        axrom.write_prg(0x8000, 6);
+        assert_eq!(axrom.read_prg(0x8000), 6);
+        assert_eq!(axrom.read_prg(0xFFFF), 6);
+    }
+
+    #[test]
+    fn single_screen_page_is_selectable() {
+        let mut axrom = Axrom::new(create_banked_rom(8, 0x8000), vec!(), 0x2000, false);
+        assert_eq!(axrom.get_mirroring(), Mirroring::SingleScreenLower);
+
+        axrom.write_prg(0x8000, 0b1_0000);
+        assert_eq!(axrom.get_mirroring(), Mirroring::SingleScreenUpper);
+    }
+
+    #[test]
+    fn bus_conflicts_are_optional() {
+        let mut with_conflicts = Axrom::new(create_banked_rom(8, 0x8000), vec!(), 0x2000, true);
+        with_conflicts.write_prg(0x8000, 0b1_0011); // Bank 0 is all zeros, so the write has no effect
+        assert_eq!(with_conflicts.read_prg(0x8000), 0);
+        assert_eq!(with_conflicts.get_mirroring(), Mirroring::SingleScreenLower);
+
+        let mut without_conflicts = Axrom::new(create_banked_rom(8, 0x8000), vec!(), 0x2000, false);
+        without_conflicts.write_prg(0x8000, 0b1_0011);
+        assert_eq!(without_conflicts.read_prg(0x8000), 3);
+        assert_eq!(without_conflicts.get_mirroring(), Mirroring::SingleScreenUpper);
+    }
+}
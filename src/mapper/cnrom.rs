@@ -0,0 +1,119 @@
+use mapper::Mapper;
+use mapper::Mirroring;
+use mapper::borrow_chr_ram;
+use mapper::copy_chr_ram;
+use mapper::create_chr_memory;
+
+// Mapper 3. Used by Arkanoid, Gradius and Solomon's Key.
+// PRG ROM is laid out the same as NROM. Any write to $8000 - $FFFF selects which 8 KB bank of CHR ROM is visible
+pub struct Cnrom {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    chr_bank: u8,
+    has_bus_conflicts: bool
+}
+
+const CHR_BANK_SIZE: usize = 0x2000;
+
+impl Cnrom {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, chr_ram_size: usize, mirroring: Mirroring, has_bus_conflicts: bool) -> Cnrom {
+        let (chr, chr_is_ram) = create_chr_memory(chr_rom, chr_ram_size);
+        return Cnrom {
+            prg_rom: prg_rom,
+            chr: chr,
+            chr_is_ram: chr_is_ram,
+            mirroring: mirroring,
+            chr_bank: 0,
+            has_bus_conflicts: has_bus_conflicts
+        }
+    }
+
+    fn get_chr_address(&self, address: u16) -> usize {
+        let num_banks = (self.chr.len() / CHR_BANK_SIZE).max(1);
+        let bank = self.chr_bank as usize % num_banks;
+        return (bank * CHR_BANK_SIZE + address as usize) % self.chr.len();
+    }
+}
+
+impl Mapper for Cnrom {
+    fn read_prg(&mut self, address: u16) -> u8 {
+        if address < 0x8000 {
+            return 0;
+        }
+        return self.prg_rom[(address as usize - 0x8000) % self.prg_rom.len()];
+    }
+
+    fn write_prg(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.chr_bank = if self.has_bus_conflicts { value & self.read_prg(address) } else { value };
+        }
+    }
+
+    fn read_chr(&mut self, address: u16) -> u8 {
+        return self.chr[self.get_chr_address(address)];
+    }
+
+    fn write_chr(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let chr_address = self.get_chr_address(address);
+            self.chr[chr_address] = value;
+        }
+    }
+
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return borrow_chr_ram(&self.chr, self.chr_is_ram);
+    }
+
+    fn load_chr_ram(&mut self, chr_ram: &[u8]) -> Result<(), String> {
+        return copy_chr_ram(&mut self.chr, self.chr_is_ram, chr_ram);
+    }
+
+    fn get_mirroring(&self) -> Mirroring {
+        return self.mirroring;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper::Mapper;
+    use mapper::create_banked_rom;
+    use mapper::Mirroring;
+    use mapper::cnrom::Cnrom;
+
+    #[test]
+    fn chr_bank_switches() {
+        let mut cnrom = Cnrom::new(vec![0xFF; 0x8000], create_banked_rom(4, 0x2000), 0, Mirroring::Horizontal, true);
+        assert_eq!(cnrom.read_chr(0x0000), 0);
+
+        cnrom.write_prg(0x8000, 3);
+        assert_eq!(cnrom.read_chr(0x0000), 3);
+        assert_eq!(cnrom.read_chr(0x1FFF), 3);
+    }
+
+    #[test]
+    fn bus_conflicts_and_the_written_value_with_rom() {
+        let mut prg_rom = vec![0xFF; 0x8000];
+        prg_rom[0x0100] = 0b0000_0001;
+
+        let mut cnrom = Cnrom::new(prg_rom, create_banked_rom(4, 0x2000), 0, Mirroring::Horizontal, true);
+        cnrom.write_prg(0x8100, 3);
+        assert_eq!(cnrom.read_chr(0x0000), 1);
+    }
+
+    #[test]
+    fn chr_ram_is_used_when_there_is_no_chr_rom() {
+        let mut cnrom = Cnrom::new(vec![0xFF; 0x8000], vec!(), 0x2000, Mirroring::Horizontal, false);
+        assert_eq!(cnrom.read_chr(0x1FFF), 0);
+
+        cnrom.write_chr(0x1FFF, 0x42);
+        cnrom.write_prg(0x8000, 3);
+        assert_eq!(cnrom.read_chr(0x1FFF), 0x42);
+
+        let saved_chr_ram = cnrom.get_chr_ram().unwrap().to_vec();
+        cnrom.write_chr(0x1FFF, 0);
+        cnrom.load_chr_ram(&saved_chr_ram).unwrap();
+        assert_eq!(cnrom.read_chr(0x1FFF), 0x42);
+    }
+}
@@ -0,0 +1,126 @@
+use mapper::Mapper;
+use mapper::Mirroring;
+use mapper::borrow_chr_ram;
+use mapper::copy_chr_ram;
+use mapper::create_chr_memory;
+
+// Mapper 66. Used by Super Mario Bros. + Duck Hunt and Dragon Power.
+// A write to $8000 - $FFFF selects a 32 KB PRG bank with bits 4 - 5, and an 8 KB CHR bank with bits 0 - 1
+pub struct Gxrom {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    bank_register: u8,
+    has_bus_conflicts: bool
+}
+
+const PRG_BANK_SIZE: usize = 0x8000;
+const CHR_BANK_SIZE: usize = 0x2000;
+
+impl Gxrom {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, chr_ram_size: usize, mirroring: Mirroring, has_bus_conflicts: bool) -> Gxrom {
+        let (chr, chr_is_ram) = create_chr_memory(chr_rom, chr_ram_size);
+        return Gxrom {
+            prg_rom: prg_rom,
+            chr: chr,
+            chr_is_ram: chr_is_ram,
+            mirroring: mirroring,
+            bank_register: 0,
+            has_bus_conflicts: has_bus_conflicts
+        }
+    }
+
+    fn get_chr_address(&self, address: u16) -> usize {
+        let num_banks = (self.chr.len() / CHR_BANK_SIZE).max(1);
+        let bank = (self.bank_register & 0b0000_0011) as usize % num_banks;
+        return (bank * CHR_BANK_SIZE + address as usize) % self.chr.len();
+    }
+}
+
+impl Mapper for Gxrom {
+    fn read_prg(&mut self, address: u16) -> u8 {
+        if address < 0x8000 {
+            return 0;
+        }
+
+        let num_banks = (self.prg_rom.len() / PRG_BANK_SIZE).max(1);
+        let bank = ((self.bank_register & 0b0011_0000) >> 4) as usize % num_banks;
+        return self.prg_rom[(bank * PRG_BANK_SIZE + address as usize - 0x8000) % self.prg_rom.len()];
+    }
+
+    fn write_prg(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.bank_register = if self.has_bus_conflicts { value & self.read_prg(address) } else { value };
+        }
+    }
+
+    fn read_chr(&mut self, address: u16) -> u8 {
+        return self.chr[self.get_chr_address(address)];
+    }
+
+    fn write_chr(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let chr_address = self.get_chr_address(address);
+            self.chr[chr_address] = value;
+        }
+    }
+
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return borrow_chr_ram(&self.chr, self.chr_is_ram);
+    }
+
+    fn load_chr_ram(&mut self, chr_ram: &[u8]) -> Result<(), String> {
+        return copy_chr_ram(&mut self.chr, self.chr_is_ram, chr_ram);
+    }
+
+    fn get_mirroring(&self) -> Mirroring {
+        return self.mirroring;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper::Mapper;
+    use mapper::create_banked_rom;
+    use mapper::Mirroring;
+    use mapper::gxrom::Gxrom;
+
+    #[test]
+    fn prg_and_chr_banks_switch_together() {
+        let mut gxrom = Gxrom::new(create_banked_rom(4, 0x8000), create_banked_rom(4, 0x2000), 0, Mirroring::Vertical, false);
+        assert_eq!(gxrom.read_prg(0x8000), 0);
+        assert_eq!(gxrom.read_chr(0x0000), 0);
+
+        gxrom.write_prg(0x8000, 0b0010_0011);
+        assert_eq!(gxrom.read_prg(0x8000), 2);
+        assert_eq!(gxrom.read_prg(0xFFFF), 2);
+        assert_eq!(gxrom.read_chr(0x0000), 3);
+    }
+
+    #[test]
+    fn bus_conflicts_and_the_written_value_with_rom() {
+        let mut prg_rom = create_banked_rom(4, 0x8000);
+        prg_rom[0x0000] = 0b0001_0001;
+
+        let mut gxrom = Gxrom::new(prg_rom, create_banked_rom(4, 0x2000), 0, Mirroring::Vertical, true);
+        gxrom.write_prg(0x8000, 0b0011_0011);
+        assert_eq!(gxrom.read_prg(0x8001), 1);
+        assert_eq!(gxrom.read_chr(0x0000), 1);
+    }
+
+    #[test]
+    fn chr_ram_is_used_when_there_is_no_chr_rom() {
+        let mut gxrom = Gxrom::new(create_banked_rom(4, 0x8000), vec!(), 0x2000, Mirroring::Vertical, false);
+        assert_eq!(gxrom.read_chr(0x1FFF), 0);
+
+        gxrom.write_chr(0x1FFF, 0x42);
+        gxrom.write_prg(0x8000, 0b0010_0011);
+        assert_eq!(gxrom.read_chr(0x1FFF), 0x42);
+
+        let saved_chr_ram = gxrom.get_chr_ram().unwrap().to_vec();
+        gxrom.write_chr(0x1FFF, 0);
+        gxrom.load_chr_ram(&saved_chr_ram).unwrap();
+        assert_eq!(gxrom.read_chr(0x1FFF), 0x42);
+    }
+}
@@ -0,0 +1,281 @@
+use mapper::Mapper;
+use mapper::Mirroring;
+use mapper::borrow_chr_ram;
+use mapper::copy_chr_ram;
+use mapper::create_chr_memory;
+
+// Mapper 1. Used by Zelda, Metroid, Mega Man 2 and a lot of others.
+// The CPU can't write to the bank registers directly. Instead, each write to $8000 - $FFFF shifts a single bit
+// (bit 0 of the value) into a 5 bit shift register. On the 5th write, the whole value is copied into the register
+// chosen by bits 13 and 14 of the address of that 5th write. Writing a value with bit 7 set resets the shift register
+pub struct Mmc1 {
+    prg_rom: Vec<u8>,
+    prg_ram: [u8; 0x2000],
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    shift_register: u8,
+    shift_count: u8, // How many bits have been shifted in so far
+    control: u8, // $8000 - $9FFF 4bit0: CHR mode, 3bit2: PRG mode, 1bit0: mirroring
+    chr_bank_0: u8, // $A000 - $BFFF
+    chr_bank_1: u8, // $C000 - $DFFF
+    prg_bank: u8 // $E000 - $FFFF Bit 4 disables PRG RAM
+}
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x1000;
+// Boards with 512 KB of PRG ROM (SUROM) use bit 4 of the CHR bank registers to select which 256 KB half is in use
+const PRG_OUTER_BANK_SIZE: usize = 0x40000;
+
+impl Mmc1 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, chr_ram_size: usize) -> Mmc1 {
+        let (chr, chr_is_ram) = create_chr_memory(chr_rom, chr_ram_size);
+        return Mmc1 {
+            prg_rom: prg_rom,
+            prg_ram: [0; 0x2000],
+            chr: chr,
+            chr_is_ram: chr_is_ram,
+            shift_register: 0,
+            shift_count: 0,
+            control: 0x0C, // Power on in PRG mode 3 so the reset vector in the last bank is visible
+            chr_bank_0: 0,
+            chr_bank_1: 0,
+            prg_bank: 0
+        }
+    }
+
+    fn write_shift_register(&mut self, address: u16, value: u8) {
+        if value & 0x80 != 0 {
+            self.shift_register = 0;
+            self.shift_count = 0;
+            self.control |= 0x0C;
+            return;
+        }
+
+        self.shift_register |= (value & 1) << self.shift_count;
+        self.shift_count += 1;
+
+        if self.shift_count == 5 {
+            let register_value = self.shift_register;
+            match address {
+                0x8000..=0x9FFF => self.control = register_value,
+                0xA000..=0xBFFF => self.chr_bank_0 = register_value,
+                0xC000..=0xDFFF => self.chr_bank_1 = register_value,
+                _ => self.prg_bank = register_value
+            }
+            self.shift_register = 0;
+            self.shift_count = 0;
+        }
+    }
+
+    fn get_prg_mode(&self) -> u8 {
+        return (self.control & 0b0_1100) >> 2;
+    }
+
+    fn is_chr_in_4k_mode(&self) -> bool {
+        return (self.control & 0b1_0000) != 0;
+    }
+
+    fn is_prg_ram_enabled(&self) -> bool {
+        return (self.prg_bank & 0b1_0000) == 0;
+    }
+
+    fn get_prg_rom_offset(&self, address: u16) -> usize {
+        let outer_bank_offset = if self.prg_rom.len() > PRG_OUTER_BANK_SIZE { (self.chr_bank_0 as usize & 0b1_0000) >> 4 } else { 0 } * PRG_OUTER_BANK_SIZE;
+        let banks_in_outer_bank = (self.prg_rom.len().min(PRG_OUTER_BANK_SIZE) / PRG_BANK_SIZE).max(1);
+        let selected_bank = (self.prg_bank & 0x0F) as usize;
+        let is_upper_half = address >= 0xC000;
+
+        let bank = match self.get_prg_mode() {
+            // 32 KB mode. The low bit of the bank number is ignored
+            0 | 1 => (selected_bank & !1) + if is_upper_half { 1 } else { 0 },
+            // First bank is fixed at $8000, $C000 is switchable
+            2 => if is_upper_half { selected_bank } else { 0 },
+            // Last bank is fixed at $C000, $8000 is switchable
+            _ => if is_upper_half { banks_in_outer_bank - 1 } else { selected_bank }
+        };
+
+        let offset_in_bank = address as usize & (PRG_BANK_SIZE - 1);
+        return (outer_bank_offset + (bank % banks_in_outer_bank) * PRG_BANK_SIZE + offset_in_bank) % self.prg_rom.len();
+    }
+
+    fn get_chr_offset(&self, address: u16) -> usize {
+        let bank = if self.is_chr_in_4k_mode() {
+            if address < 0x1000 { self.chr_bank_0 as usize } else { self.chr_bank_1 as usize }
+        } else {
+            // 8 KB mode. The low bit of the bank number is ignored
+            (self.chr_bank_0 as usize & !1) + if address < 0x1000 { 0 } else { 1 }
+        };
+
+        let offset_in_bank = address as usize & (CHR_BANK_SIZE - 1);
+        return (bank * CHR_BANK_SIZE + offset_in_bank) % self.chr.len();
+    }
+}
+
+impl Mapper for Mmc1 {
+    fn read_prg(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            return self.prg_rom[self.get_prg_rom_offset(address)];
+        } else if address >= 0x6000 && self.is_prg_ram_enabled() {
+            return self.prg_ram[address as usize - 0x6000];
+        }
+        return 0;
+    }
+
+    fn write_prg(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.write_shift_register(address, value);
+        } else if address >= 0x6000 && self.is_prg_ram_enabled() {
+            self.prg_ram[address as usize - 0x6000] = value;
+        }
+    }
+
+    fn read_chr(&mut self, address: u16) -> u8 {
+        return self.chr[self.get_chr_offset(address)];
+    }
+
+    fn write_chr(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let offset = self.get_chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return borrow_chr_ram(&self.chr, self.chr_is_ram);
+    }
+
+    fn load_chr_ram(&mut self, chr_ram: &[u8]) -> Result<(), String> {
+        return copy_chr_ram(&mut self.chr, self.chr_is_ram, chr_ram);
+    }
+
+    fn get_mirroring(&self) -> Mirroring {
+        match self.control & 0b0_0011 {
+            0 => Mirroring::SingleScreenLower,
+            1 => Mirroring::SingleScreenUpper,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper::Mapper;
+    use mapper::create_banked_rom;
+    use mapper::Mirroring;
+    use mapper::mmc1::Mmc1;
+
+    fn write_register(mmc1: &mut Mmc1, address: u16, value: u8) {
+        for bit in 0..5 {
+            mmc1.write_prg(address, (value >> bit) & 1);
+        }
+    }
+
+    #[test]
+    fn last_prg_bank_is_fixed_at_power_on() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(8, 0x4000), vec!(), 0x2000);
+
+        assert_eq!(mmc1.read_prg(0x8000), 0);
+        assert_eq!(mmc1.read_prg(0xFFFF), 7);
+    }
+
+    #[test]
+    fn register_is_only_written_on_the_fifth_write() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(8, 0x4000), vec!(), 0x2000);
+
+        for _ in 0..4 {
+            mmc1.write_prg(0xE000, 1);
+        }
+        assert_eq!(mmc1.read_prg(0x8000), 0);
+
+        mmc1.write_prg(0xE000, 0);
+        assert_eq!(mmc1.read_prg(0x8000), 0x0F % 8);
+    }
+
+    #[test]
+    fn bit_7_resets_the_shift_register() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(8, 0x4000), vec!(), 0x2000);
+
+        mmc1.write_prg(0xE000, 1);
+        mmc1.write_prg(0xE000, 1);
+        mmc1.write_prg(0xE000, 0x80);
+        write_register(&mut mmc1, 0xE000, 3);
+
+        assert_eq!(mmc1.read_prg(0x8000), 3);
+    }
+
+    #[test]
+    fn prg_bank_modes() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(8, 0x4000), vec!(), 0x2000);
+        write_register(&mut mmc1, 0xE000, 5);
+
+        // Mode 3. $8000 switchable, $C000 fixed to the last bank
+        assert_eq!(mmc1.read_prg(0x8000), 5);
+        assert_eq!(mmc1.read_prg(0xC000), 7);
+
+        // Mode 2. $8000 fixed to the first bank, $C000 switchable
+        write_register(&mut mmc1, 0x8000, 0b0_1000);
+        assert_eq!(mmc1.read_prg(0x8000), 0);
+        assert_eq!(mmc1.read_prg(0xC000), 5);
+
+        // Mode 0. 32 KB at a time, ignoring the low bit
+        write_register(&mut mmc1, 0x8000, 0b0_0000);
+        assert_eq!(mmc1.read_prg(0x8000), 4);
+        assert_eq!(mmc1.read_prg(0xC000), 5);
+    }
+
+    #[test]
+    fn chr_bank_modes() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(2, 0x4000), create_banked_rom(8, 0x1000), 0);
+
+        // 8 KB mode ignores the low bit and CHR bank 1
+        write_register(&mut mmc1, 0xA000, 3);
+        write_register(&mut mmc1, 0xC000, 6);
+        assert_eq!(mmc1.read_chr(0x0000), 2);
+        assert_eq!(mmc1.read_chr(0x1000), 3);
+
+        // 4 KB mode switches both halves separately
+        write_register(&mut mmc1, 0x8000, 0b1_1100);
+        assert_eq!(mmc1.read_chr(0x0000), 3);
+        assert_eq!(mmc1.read_chr(0x1000), 6);
+    }
+
+    #[test]
+    fn mirroring_is_switchable() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(2, 0x4000), vec!(), 0x2000);
+
+        write_register(&mut mmc1, 0x8000, 0b0_1100);
+        assert_eq!(mmc1.get_mirroring(), Mirroring::SingleScreenLower);
+        write_register(&mut mmc1, 0x8000, 0b0_1101);
+        assert_eq!(mmc1.get_mirroring(), Mirroring::SingleScreenUpper);
+        write_register(&mut mmc1, 0x8000, 0b0_1110);
+        assert_eq!(mmc1.get_mirroring(), Mirroring::Vertical);
+        write_register(&mut mmc1, 0x8000, 0b0_1111);
+        assert_eq!(mmc1.get_mirroring(), Mirroring::Horizontal);
+    }
+
+    #[test]
+    fn prg_ram_can_be_disabled() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(2, 0x4000), vec!(), 0x2000);
+
+        mmc1.write_prg(0x6000, 0x42);
+        assert_eq!(mmc1.read_prg(0x6000), 0x42);
+
+        write_register(&mut mmc1, 0xE000, 0b1_0000);
+        mmc1.write_prg(0x6000, 0x11);
+        assert_eq!(mmc1.read_prg(0x6000), 0);
+
+        write_register(&mut mmc1, 0xE000, 0b0_0000);
+        assert_eq!(mmc1.read_prg(0x6000), 0x42);
+    }
+
+    #[test]
+    fn surom_uses_chr_bank_to_select_prg_outer_bank() {
+        let mut mmc1 = Mmc1::new(create_banked_rom(32, 0x4000), vec!(), 0x2000);
+
+        assert_eq!(mmc1.read_prg(0xC000), 15);
+        write_register(&mut mmc1, 0xA000, 0b1_0000);
+        assert_eq!(mmc1.read_prg(0x8000), 16);
+        assert_eq!(mmc1.read_prg(0xC000), 31);
+    }
+}
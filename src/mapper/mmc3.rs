@@ -0,0 +1,340 @@
+use mapper::Mapper;
+use mapper::Mirroring;
+use mapper::borrow_chr_ram;
+use mapper::copy_chr_ram;
+use mapper::create_chr_memory;
+
+// Mapper 4. Used by Super Mario Bros 3, Kirby's Adventure, Mega Man 3 - 6 and many more.
+// PRG ROM is switched in 8 KB banks and CHR in 1 KB / 2 KB banks. It also has a scanline counter that can fire an IRQ.
+// The counter is clocked by rising edges on PPU address line A12, which (with sprites in the $1000 pattern table and the
+// background in $0000) rises once per scanline when the PPU starts fetching sprite patterns
+pub struct Mmc3 {
+    prg_rom: Vec<u8>,
+    prg_ram: [u8; 0x2000],
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    bank_select: u8, // $8000 2bit0: which bank register $8001 writes to. bit6: PRG mode. bit7: CHR A12 inversion
+    bank_registers: [u8; 8], // R0 - R5 are CHR banks, R6 and R7 are PRG banks
+    mirroring: Mirroring,
+    prg_ram_protect: u8, // $A001 bit7: PRG RAM enabled. bit6: writes denied
+    irq_latch: u8, // $C000 The value the counter is reloaded with
+    irq_counter: u8,
+    irq_reload: bool, // $C001 sets this so the counter is reloaded on the next A12 rise
+    irq_enabled: bool, // $E000 disables, $E001 enables
+    irq_pending: bool,
+    last_a12: bool, // Value of A12 during the previous PPU pattern fetch
+    a12_low_cycles: u8 // How many CPU cycles A12 has been low. Short pulses are filtered out by the board
+}
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x0400;
+// A12 must stay low for a few CPU cycles before a rise counts. This keeps the sprite fetches from clocking the counter
+// several times per scanline, since A12 briefly drops low between each of them when the PPU reads the nametables
+const A12_LOW_CYCLES_REQUIRED: u8 = 3;
+
+impl Mmc3 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, chr_ram_size: usize, mirroring: Mirroring) -> Mmc3 {
+        let (chr, chr_is_ram) = create_chr_memory(chr_rom, chr_ram_size);
+        return Mmc3 {
+            prg_rom: prg_rom,
+            prg_ram: [0; 0x2000],
+            chr: chr,
+            chr_is_ram: chr_is_ram,
+            bank_select: 0,
+            bank_registers: [0, 2, 4, 5, 6, 7, 0, 1],
+            mirroring: mirroring,
+            prg_ram_protect: 0x80, // Games are supposed to enable this themselves, but plenty forget to
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_pending: false,
+            last_a12: false,
+            a12_low_cycles: 0
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        let is_even = address % 2 == 0;
+        match (address & 0xE000, is_even) {
+            (0x8000, true) => self.bank_select = value,
+            (0x8000, false) => self.bank_registers[(self.bank_select & 0b111) as usize] = value,
+            (0xA000, true) => {
+                // Boards with four screen VRAM have their mirroring hardwired
+                if self.mirroring != Mirroring::FourScreen {
+                    self.mirroring = if value & 1 == 0 { Mirroring::Vertical } else { Mirroring::Horizontal };
+                }
+            },
+            (0xA000, false) => self.prg_ram_protect = value,
+            (0xC000, true) => self.irq_latch = value,
+            (0xC000, false) => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            },
+            (0xE000, true) => {
+                self.irq_enabled = false;
+                self.irq_pending = false; // Disabling also acknowledges any pending interrupt
+            },
+            _ => self.irq_enabled = true
+        }
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+
+    fn watch_a12(&mut self, address: u16) {
+        let a12 = (address & 0x1000) != 0;
+        if a12 && !self.last_a12 && self.a12_low_cycles >= A12_LOW_CYCLES_REQUIRED {
+            self.clock_irq_counter();
+        }
+        if a12 {
+            self.a12_low_cycles = 0;
+        }
+        self.last_a12 = a12;
+    }
+
+    fn get_prg_rom_offset(&self, address: u16) -> usize {
+        let num_banks = (self.prg_rom.len() / PRG_BANK_SIZE).max(1);
+        let second_last_bank = num_banks.saturating_sub(2);
+        let swap_prg_banks = (self.bank_select & 0b0100_0000) != 0;
+
+        let bank = match (address & 0xE000, swap_prg_banks) {
+            (0x8000, false) => self.bank_registers[6] as usize,
+            (0x8000, true) => second_last_bank,
+            (0xA000, _) => self.bank_registers[7] as usize,
+            (0xC000, false) => second_last_bank,
+            (0xC000, true) => self.bank_registers[6] as usize,
+            _ => num_banks - 1
+        };
+
+        return ((bank % num_banks) * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_rom.len();
+    }
+
+    fn get_chr_offset(&self, address: u16) -> usize {
+        // With A12 inversion, the two 2 KB banks are at $1000 instead of $0000 and the 1 KB banks move down to $0000
+        let inverted = (self.bank_select & 0b1000_0000) != 0;
+        let address = if inverted { address ^ 0x1000 } else { address };
+
+        let bank = match address {
+            0x0000..=0x07FF => (self.bank_registers[0] & 0xFE) as usize + (address as usize / CHR_BANK_SIZE) % 2,
+            0x0800..=0x0FFF => (self.bank_registers[1] & 0xFE) as usize + (address as usize / CHR_BANK_SIZE) % 2,
+            0x1000..=0x13FF => self.bank_registers[2] as usize,
+            0x1400..=0x17FF => self.bank_registers[3] as usize,
+            0x1800..=0x1BFF => self.bank_registers[4] as usize,
+            _ => self.bank_registers[5] as usize
+        };
+
+        return (bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))) % self.chr.len();
+    }
+
+    fn is_prg_ram_enabled(&self) -> bool {
+        return (self.prg_ram_protect & 0b1000_0000) != 0;
+    }
+
+    fn is_prg_ram_writable(&self) -> bool {
+        return self.is_prg_ram_enabled() && (self.prg_ram_protect & 0b0100_0000) == 0;
+    }
+}
+
+impl Mapper for Mmc3 {
+    fn read_prg(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            return self.prg_rom[self.get_prg_rom_offset(address)];
+        } else if address >= 0x6000 && self.is_prg_ram_enabled() {
+            return self.prg_ram[address as usize - 0x6000];
+        }
+        return 0;
+    }
+
+    fn write_prg(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.write_register(address, value);
+        } else if address >= 0x6000 && self.is_prg_ram_writable() {
+            self.prg_ram[address as usize - 0x6000] = value;
+        }
+    }
+
+    fn read_chr(&mut self, address: u16) -> u8 {
+        self.watch_a12(address);
+        return self.chr[self.get_chr_offset(address)];
+    }
+
+    fn write_chr(&mut self, address: u16, value: u8) {
+        self.watch_a12(address);
+        if self.chr_is_ram {
+            let offset = self.get_chr_offset(address);
+            self.chr[offset] = value;
+        }
+    }
+
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return borrow_chr_ram(&self.chr, self.chr_is_ram);
+    }
+
+    fn load_chr_ram(&mut self, chr_ram: &[u8]) -> Result<(), String> {
+        return copy_chr_ram(&mut self.chr, self.chr_is_ram, chr_ram);
+    }
+
+    fn get_mirroring(&self) -> Mirroring {
+        return self.mirroring;
+    }
+
+    fn cpu_tick(&mut self) {
+        if !self.last_a12 && self.a12_low_cycles < A12_LOW_CYCLES_REQUIRED {
+            self.a12_low_cycles += 1;
+        }
+    }
+
+    fn is_irq_asserted(&self) -> bool {
+        return self.irq_pending;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper::Mapper;
+    use mapper::create_banked_rom;
+    use mapper::Mirroring;
+    use mapper::mmc3::Mmc3;
+
+    fn create_test_mmc3() -> Mmc3 {
+        return Mmc3::new(create_banked_rom(16, 0x2000), create_banked_rom(32, 0x400), 0, Mirroring::Vertical);
+    }
+
+    // Mimics one scanline of PPU fetches with the background at $0000 and sprites at $1000
+    fn render_scanline(mmc3: &mut Mmc3) {
+        mmc3.read_chr(0x0000);
+        for _ in 0..80 {
+            mmc3.cpu_tick();
+        }
+        for _ in 0..8 {
+            mmc3.read_chr(0x1FF0);
+            mmc3.cpu_tick();
+        }
+        mmc3.read_chr(0x0000);
+        for _ in 0..25 {
+            mmc3.cpu_tick();
+        }
+    }
+
+    #[test]
+    fn prg_banks_switch_in_both_modes() {
+        let mut mmc3 = create_test_mmc3();
+        mmc3.write_prg(0x8000, 6);
+        mmc3.write_prg(0x8001, 3);
+        mmc3.write_prg(0x8000, 7);
+        mmc3.write_prg(0x8001, 5);
+
+        assert_eq!(mmc3.read_prg(0x8000), 3);
+        assert_eq!(mmc3.read_prg(0xA000), 5);
+        assert_eq!(mmc3.read_prg(0xC000), 14);
+        assert_eq!(mmc3.read_prg(0xE000), 15);
+
+        mmc3.write_prg(0x8000, 0b0100_0000);
+        assert_eq!(mmc3.read_prg(0x8000), 14);
+        assert_eq!(mmc3.read_prg(0xA000), 5);
+        assert_eq!(mmc3.read_prg(0xC000), 3);
+        assert_eq!(mmc3.read_prg(0xE000), 15);
+    }
+
+    #[test]
+    fn prg_smaller_than_a_bank_is_mirrored() {
+        let mut mmc3 = Mmc3::new(create_banked_rom(1, 0x1000), create_banked_rom(32, 0x400), 0, Mirroring::Vertical);
+        mmc3.write_prg(0x8000, 6);
+        mmc3.write_prg(0x8001, 3);
+        assert_eq!(mmc3.read_prg(0x8000), 0);
+        assert_eq!(mmc3.read_prg(0x9FFF), 0);
+        assert_eq!(mmc3.read_prg(0xFFFF), 0);
+    }
+
+    #[test]
+    fn chr_banks_switch_and_invert() {
+        let mut mmc3 = create_test_mmc3();
+        for register in 0..6 {
+            mmc3.write_prg(0x8000, register);
+            mmc3.write_prg(0x8001, 10 + register * 2);
+        }
+
+        assert_eq!(mmc3.read_chr(0x0000), 10);
+        assert_eq!(mmc3.read_chr(0x0400), 11);
+        assert_eq!(mmc3.read_chr(0x0800), 12);
+        assert_eq!(mmc3.read_chr(0x0C00), 13);
+        assert_eq!(mmc3.read_chr(0x1000), 14);
+        assert_eq!(mmc3.read_chr(0x1C00), 20);
+
+        mmc3.write_prg(0x8000, 0b1000_0000);
+        assert_eq!(mmc3.read_chr(0x0000), 14);
+        assert_eq!(mmc3.read_chr(0x0C00), 20);
+        assert_eq!(mmc3.read_chr(0x1000), 10);
+        assert_eq!(mmc3.read_chr(0x1C00), 13);
+    }
+
+    #[test]
+    fn mirroring_is_switchable() {
+        let mut mmc3 = create_test_mmc3();
+        mmc3.write_prg(0xA000, 1);
+        assert_eq!(mmc3.get_mirroring(), Mirroring::Horizontal);
+        mmc3.write_prg(0xA000, 0);
+        assert_eq!(mmc3.get_mirroring(), Mirroring::Vertical);
+    }
+
+    #[test]
+    fn prg_ram_write_protection() {
+        let mut mmc3 = create_test_mmc3();
+        mmc3.write_prg(0x6000, 0x42);
+        assert_eq!(mmc3.read_prg(0x6000), 0x42);
+
+        mmc3.write_prg(0xA001, 0b1100_0000);
+        mmc3.write_prg(0x6000, 0x11);
+        assert_eq!(mmc3.read_prg(0x6000), 0x42);
+
+        mmc3.write_prg(0xA001, 0b0000_0000);
+        assert_eq!(mmc3.read_prg(0x6000), 0);
+    }
+
+    #[test]
+    fn irq_fires_after_latch_scanlines() {
+        let mut mmc3 = create_test_mmc3();
+        mmc3.write_prg(0xC000, 3);
+        mmc3.write_prg(0xC001, 0);
+        mmc3.write_prg(0xE001, 0);
+
+        render_scanline(&mut mmc3); // Reloads the counter to 3
+        render_scanline(&mut mmc3);
+        render_scanline(&mut mmc3);
+        assert_eq!(mmc3.is_irq_asserted(), false);
+
+        render_scanline(&mut mmc3);
+        assert_eq!(mmc3.is_irq_asserted(), true);
+
+        // Writing to $E000 acknowledges the IRQ
+        mmc3.write_prg(0xE000, 0);
+        assert_eq!(mmc3.is_irq_asserted(), false);
+    }
+
+    #[test]
+    fn quick_a12_toggles_are_filtered() {
+        let mut mmc3 = create_test_mmc3();
+        mmc3.write_prg(0xC000, 2);
+        mmc3.write_prg(0xE001, 0);
+
+        render_scanline(&mut mmc3); // Reloads the counter to 2
+
+        // Only the first rise counts, since A12 has been low for a while by then. The rest are too close together
+        for _ in 0..8 {
+            mmc3.read_chr(0x1000);
+            mmc3.read_chr(0x0000);
+        }
+        assert_eq!(mmc3.is_irq_asserted(), false);
+    }
+}
@@ -0,0 +1,276 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rom::Rom as Rom;
+use rom_header::RomHeader as RomHeader;
+
+mod nrom;
+mod mmc1;
+mod mmc3;
+mod uxrom;
+mod cnrom;
+mod axrom;
+mod gxrom;
+
+pub use self::nrom::Nrom;
+use self::mmc1::Mmc1;
+use self::mmc3::Mmc3;
+use self::uxrom::Uxrom;
+use self::cnrom::Cnrom;
+use self::axrom::Axrom;
+use self::gxrom::Gxrom;
+
+// Cartridges contain a lot more than just ROM data. The board decides how the PRG and CHR data is laid out in the
+// CPU and PPU address spaces, and many boards contain extra hardware (bank switching registers, RAM, IRQ counters).
+// Each board type is assigned a 'mapper number' in the ROM header, and each mapper number gets its own implementation
+pub trait Mapper {
+    // CPU reads and writes in the range $4020 - $FFFF. This is where PRG ROM and PRG RAM live
+    fn read_prg(&mut self, address: u16) -> u8;
+    fn write_prg(&mut self, address: u16, value: u8);
+
+    // PPU reads and writes in the range $0000 - $1FFF. This is where the pattern tables (CHR ROM or CHR RAM) live
+    fn read_chr(&mut self, address: u16) -> u8;
+    fn write_chr(&mut self, address: u16, value: u8);
+
+    // How the PPU's 2KB of nametable memory is arranged into the 4 nametables it can address
+    fn get_mirroring(&self) -> Mirroring;
+
+    // Called once for every CPU cycle (the M2 pin of the cartridge connector). Boards with timers or filters use this
+    fn cpu_tick(&mut self) {
+    }
+
+    // Whether the cartridge is currently pulling the CPU's IRQ line. The line stays asserted until the game acknowledges it
+    fn is_irq_asserted(&self) -> bool {
+        return false;
+    }
+
+    // Unlike CHR ROM, the contents of CHR RAM can't be reloaded from the ROM file, so save states have to carry them.
+    // None for boards that only have CHR ROM
+    #[allow(dead_code)] // Only used by tests until save states are hooked up to a frontend
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return None;
+    }
+
+    #[allow(dead_code)] // Only used by tests until save states are hooked up to a frontend
+    fn load_chr_ram(&mut self, _chr_ram: &[u8]) -> Result<(), String> {
+        return Err("This cartridge has no CHR RAM".to_owned());
+    }
+}
+
+const DEFAULT_CHR_RAM_SIZE: usize = 0x2000;
+
+// The cartridge is plugged into both the CPU and PPU buses, so both of them hold on to it
+pub type Cartridge = Rc<RefCell<dyn Mapper>>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mirroring {
+    Horizontal, // $2000 and $2400 share memory, as do $2800 and $2C00. Used by vertically scrolling games
+    Vertical, // $2000 and $2800 share memory, as do $2400 and $2C00. Used by horizontally scrolling games
+    FourScreen, // The cartridge provides extra VRAM so each nametable is unique
+    SingleScreenLower, // All 4 nametables use the first 1KB of VRAM
+    SingleScreenUpper // All 4 nametables use the second 1KB of VRAM
+}
+
+pub fn create_cartridge(rom: Rom) -> Result<Cartridge, String> {
+    let mirroring = get_header_mirroring(&rom.rom_header);
+    let chr_ram_size = rom.rom_header.get_chr_ram_size() + rom.rom_header.get_chr_nvram_size();
+    if rom.prg_rom.is_empty() {
+        return Err("The ROM has no PRG ROM".to_owned());
+    }
+
+    let cartridge: Cartridge = match rom.rom_header.get_mapper_number() {
+        0 => Rc::new(RefCell::new(Nrom::new(rom.prg_rom, rom.chr_rom, chr_ram_size, mirroring))),
+        1 => Rc::new(RefCell::new(Mmc1::new(rom.prg_rom, rom.chr_rom, chr_ram_size))),
+        2 => Rc::new(RefCell::new(Uxrom::new(rom.prg_rom, rom.chr_rom, chr_ram_size, mirroring, true))),
+        3 => Rc::new(RefCell::new(Cnrom::new(rom.prg_rom, rom.chr_rom, chr_ram_size, mirroring, true))),
+        4 => Rc::new(RefCell::new(Mmc3::new(rom.prg_rom, rom.chr_rom, chr_ram_size, mirroring))),
+        // AOROM (no bus conflicts) is much more common than ANROM, so assume that's what we have
+        7 => Rc::new(RefCell::new(Axrom::new(rom.prg_rom, rom.chr_rom, chr_ram_size, false))),
+        66 => Rc::new(RefCell::new(Gxrom::new(rom.prg_rom, rom.chr_rom, chr_ram_size, mirroring, true))),
+        mapper_number => return Err(format!("Mapper {} is not yet supported", mapper_number))
+    };
+
+    load_trainer(&cartridge, &rom.trainer);
+    return Ok(cartridge);
+}
+
+// Trainers were patches added by copiers, which loaded them into PRG RAM at $7000 before starting the game. Boards without
+// any PRG RAM just ignore the writes, same as they would have on a copier
+fn load_trainer(cartridge: &Cartridge, trainer: &[u8]) {
+    let mut mapper = cartridge.borrow_mut();
+    for (offset, &value) in trainer.iter().enumerate() {
+        mapper.write_prg(0x7000 + offset as u16, value);
+    }
+}
+
+fn get_header_mirroring(header: &RomHeader) -> Mirroring {
+    if header.has_four_screen_vram() {
+        return Mirroring::FourScreen;
+    } else if header.has_vertical_mirroring() {
+        return Mirroring::Vertical;
+    } else {
+        return Mirroring::Horizontal;
+    }
+}
+
+// Boards without any CHR ROM have CHR RAM instead, which the game fills with its own tile data. That's 8 KB unless the
+// ROM header says otherwise
+pub fn create_chr_memory(chr_rom: Vec<u8>, chr_ram_size: usize) -> (Vec<u8>, bool) {
+    if chr_rom.len() == 0 {
+        return (vec![0; if chr_ram_size == 0 { DEFAULT_CHR_RAM_SIZE } else { chr_ram_size }], true);
+    }
+    return (chr_rom, false);
+}
+
+// What Mapper::get_chr_ram hands out for a board's CHR memory
+#[allow(dead_code)] // Only used by tests until save states are hooked up to a frontend
+pub fn borrow_chr_ram(chr: &[u8], chr_is_ram: bool) -> Option<&[u8]> {
+    return if chr_is_ram { Some(chr) } else { None };
+}
+
+// Restores CHR RAM saved with Mapper::get_chr_ram
+#[allow(dead_code)] // Only used by tests until save states are hooked up to a frontend
+pub fn copy_chr_ram(chr: &mut [u8], chr_is_ram: bool, chr_ram: &[u8]) -> Result<(), String> {
+    if !chr_is_ram {
+        return Err("This cartridge has no CHR RAM".to_owned());
+    } else if chr.len() != chr_ram.len() {
+        return Err(format!("Expected {} bytes of CHR RAM but got {}", chr.len(), chr_ram.len()));
+    }
+    chr.copy_from_slice(chr_ram);
+    return Ok(());
+}
+
+// Each bank of the returned ROM is filled with its own bank number, so tests can tell which bank they're reading
+#[cfg(test)]
+pub fn create_banked_rom(num_banks: usize, bank_size: usize) -> Vec<u8> {
+    let mut rom: Vec<u8> = vec!();
+    for bank in 0..num_banks {
+        rom.extend(vec![bank as u8; bank_size]);
+    }
+    return rom;
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper;
+    use mapper::Mirroring;
+    use rom::Rom;
+
+    fn build_rom(mapper_number: u8, flags6: u8) -> Rom {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 1, 1, flags6 | (mapper_number << 4), mapper_number & 0xF0, 0, 0, 0, 0, 0, 0, 0, 0);
+        file.extend(vec![0; 0x4000 + 0x2000]);
+        return Rom::from_bytes(&file).unwrap();
+    }
+
+    #[test]
+    fn nrom_is_created_for_mapper_0() {
+        assert_eq!(mapper::create_cartridge(build_rom(0, 0)).is_ok(), true);
+    }
+
+    #[test]
+    fn mmc1_is_created_for_mapper_1() {
+        assert_eq!(mapper::create_cartridge(build_rom(1, 0)).is_ok(), true);
+    }
+
+    #[test]
+    fn mmc3_is_created_for_mapper_4() {
+        assert_eq!(mapper::create_cartridge(build_rom(4, 0)).is_ok(), true);
+    }
+
+    #[test]
+    fn discrete_logic_mappers_are_supported() {
+        for &mapper_number in [2, 3, 7, 66].iter() {
+            assert_eq!(mapper::create_cartridge(build_rom(mapper_number, 0)).is_ok(), true);
+        }
+    }
+
+    #[test]
+    fn unknown_mappers_are_an_error() {
+        assert_eq!(mapper::create_cartridge(build_rom(0xFF, 0)).is_err(), true);
+    }
+
+    #[test]
+    fn roms_without_prg_rom_are_an_error() {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 0, 1, 0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+        file.extend(vec![0; 0x2000]);
+        assert_eq!(mapper::create_cartridge(Rom::from_bytes(&file).unwrap()).is_err(), true);
+    }
+
+    #[test]
+    fn mirroring_comes_from_the_header() {
+        let horizontal = mapper::create_cartridge(build_rom(0, 0b0000)).unwrap();
+        let vertical = mapper::create_cartridge(build_rom(0, 0b0001)).unwrap();
+        let four_screen = mapper::create_cartridge(build_rom(0, 0b1000)).unwrap();
+
+        assert_eq!(horizontal.borrow().get_mirroring(), Mirroring::Horizontal);
+        assert_eq!(vertical.borrow().get_mirroring(), Mirroring::Vertical);
+        assert_eq!(four_screen.borrow().get_mirroring(), Mirroring::FourScreen);
+    }
+
+    #[test]
+    fn trainer_is_loaded_at_7000() {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 1, 1, 0b0100, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+        file.extend((0..512).map(|i| i as u8));
+        file.extend(vec![0; 0x4000 + 0x2000]);
+        let cartridge = mapper::create_cartridge(Rom::from_bytes(&file).unwrap()).unwrap();
+
+        assert_eq!(cartridge.borrow_mut().read_prg(0x6FFF), 0);
+        assert_eq!(cartridge.borrow_mut().read_prg(0x7000), 0);
+        assert_eq!(cartridge.borrow_mut().read_prg(0x7001), 1);
+        assert_eq!(cartridge.borrow_mut().read_prg(0x71FF), 0xFF);
+        assert_eq!(cartridge.borrow_mut().read_prg(0x7200), 0);
+    }
+
+    #[test]
+    fn chr_ram_size_comes_from_the_nes2_header() {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 1, 0, 0, 0x08, 0, 0, 0, 0x09, 0, 0, 0, 0);
+        file.extend(vec![0; 0x4000]);
+        let cartridge = mapper::create_cartridge(Rom::from_bytes(&file).unwrap()).unwrap();
+
+        assert_eq!(cartridge.borrow().get_chr_ram().unwrap().len(), 0x8000);
+        cartridge.borrow_mut().write_chr(0x2000, 0x42);
+        assert_eq!(cartridge.borrow_mut().read_chr(0x2000), 0x42);
+        assert_eq!(cartridge.borrow_mut().read_chr(0x0000), 0);
+    }
+
+    #[test]
+    fn chr_nvram_is_added_to_chr_ram() {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 1, 0, 0, 0x08, 0, 0, 0, 0x77, 0, 0, 0, 0);
+        file.extend(vec![0; 0x4000]);
+        let cartridge = mapper::create_cartridge(Rom::from_bytes(&file).unwrap()).unwrap();
+
+        assert_eq!(cartridge.borrow().get_chr_ram().unwrap().len(), 0x4000);
+        cartridge.borrow_mut().write_chr(0x2000, 0x42);
+        assert_eq!(cartridge.borrow_mut().read_chr(0x2000), 0x42);
+        assert_eq!(cartridge.borrow_mut().read_chr(0x0000), 0);
+    }
+
+    #[test]
+    fn chr_ram_defaults_to_8kb() {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+        file.extend(vec![0; 0x4000]);
+        let cartridge = mapper::create_cartridge(Rom::from_bytes(&file).unwrap()).unwrap();
+
+        assert_eq!(cartridge.borrow().get_chr_ram().unwrap().len(), 0x2000);
+    }
+
+    #[test]
+    fn chr_rom_is_not_saved() {
+        let cartridge = mapper::create_cartridge(build_rom(0, 0)).unwrap();
+
+        assert_eq!(cartridge.borrow().get_chr_ram().is_none(), true);
+        assert_eq!(cartridge.borrow_mut().load_chr_ram(&[0; 0x2000]).is_err(), true);
+    }
+
+    #[test]
+    fn chr_banked_mappers_work_without_chr_rom() {
+        for &mapper_number in [3, 66].iter() {
+            let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 2, 0, mapper_number << 4, mapper_number & 0xF0, 0, 0, 0, 0, 0, 0, 0, 0);
+            file.extend(vec![0; 0x8000]);
+            let cartridge = mapper::create_cartridge(Rom::from_bytes(&file).unwrap()).unwrap();
+
+            cartridge.borrow_mut().write_chr(0x0ABC, 0x42);
+            assert_eq!(cartridge.borrow_mut().read_chr(0x0ABC), 0x42);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use mapper::Mapper;
+use mapper::Mirroring;
+use mapper::borrow_chr_ram;
+use mapper::copy_chr_ram;
+use mapper::create_chr_memory;
+
+// Mapper 0. No bank switching at all. 16 or 32 KB of PRG ROM and 8 KB of CHR.
+// Boards with 16 KB of PRG ROM have it mirrored into both $8000 and $C000
+pub struct Nrom {
+    prg_rom: Vec<u8>,
+    prg_ram: [u8; 0x2000], // Only present on a couple of boards (Family BASIC) but harmless to have on all of them
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring
+}
+
+impl Nrom {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, chr_ram_size: usize, mirroring: Mirroring) -> Nrom {
+        let (chr, chr_is_ram) = create_chr_memory(chr_rom, chr_ram_size);
+        return Nrom {
+            prg_rom: prg_rom,
+            prg_ram: [0; 0x2000],
+            chr: chr,
+            chr_is_ram: chr_is_ram,
+            mirroring: mirroring
+        }
+    }
+}
+
+impl Mapper for Nrom {
+    fn read_prg(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            return self.prg_rom[(address as usize - 0x8000) % self.prg_rom.len()];
+        } else if address >= 0x6000 {
+            return self.prg_ram[address as usize - 0x6000];
+        }
+        return 0;
+    }
+
+    fn write_prg(&mut self, address: u16, value: u8) {
+        // Writes to ROM are simply ignored
+        if address >= 0x6000 && address < 0x8000 {
+            self.prg_ram[address as usize - 0x6000] = value;
+        }
+    }
+
+    fn read_chr(&mut self, address: u16) -> u8 {
+        return self.chr[address as usize % self.chr.len()];
+    }
+
+    fn write_chr(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let chr_length = self.chr.len();
+            self.chr[address as usize % chr_length] = value;
+        }
+    }
+
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return borrow_chr_ram(&self.chr, self.chr_is_ram);
+    }
+
+    fn load_chr_ram(&mut self, chr_ram: &[u8]) -> Result<(), String> {
+        return copy_chr_ram(&mut self.chr, self.chr_is_ram, chr_ram);
+    }
+
+    fn get_mirroring(&self) -> Mirroring {
+        return self.mirroring;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper::Mapper;
+    use mapper::Mirroring;
+    use mapper::Nrom;
+
+    #[test]
+    fn single_prg_bank_is_mirrored() {
+        let mut prg_rom = vec![0; 0x4000];
+        prg_rom[0x0010] = 0x42;
+        let mut nrom = Nrom::new(prg_rom, vec![0; 0x2000], 0, Mirroring::Horizontal);
+
+        assert_eq!(nrom.read_prg(0x8010), 0x42);
+        assert_eq!(nrom.read_prg(0xC010), 0x42);
+    }
+
+    #[test]
+    fn prg_rom_is_read_only() {
+        let mut nrom = Nrom::new(vec![0x11; 0x8000], vec![0; 0x2000], 0, Mirroring::Horizontal);
+        nrom.write_prg(0x8000, 0x42);
+        assert_eq!(nrom.read_prg(0x8000), 0x11);
+    }
+
+    #[test]
+    fn prg_ram_is_writable() {
+        let mut nrom = Nrom::new(vec![0; 0x8000], vec![0; 0x2000], 0, Mirroring::Horizontal);
+        nrom.write_prg(0x6123, 0x42);
+        assert_eq!(nrom.read_prg(0x6123), 0x42);
+    }
+
+    #[test]
+    fn only_chr_ram_is_writable() {
+        let mut with_chr_rom = Nrom::new(vec![0; 0x8000], vec![0x11; 0x2000], 0, Mirroring::Horizontal);
+        with_chr_rom.write_chr(0x1000, 0x42);
+        assert_eq!(with_chr_rom.read_chr(0x1000), 0x11);
+
+        let mut with_chr_ram = Nrom::new(vec![0; 0x8000], vec!(), 0x2000, Mirroring::Horizontal);
+        with_chr_ram.write_chr(0x1000, 0x42);
+        assert_eq!(with_chr_ram.read_chr(0x1000), 0x42);
+    }
+
+    #[test]
+    fn chr_ram_can_be_saved_and_restored() {
+        let mut nrom = Nrom::new(vec![0; 0x8000], vec!(), 0x2000, Mirroring::Horizontal);
+        nrom.write_chr(0x0123, 0x42);
+        let saved_chr_ram = nrom.get_chr_ram().unwrap().to_vec();
+
+        nrom.write_chr(0x0123, 0x00);
+        nrom.load_chr_ram(&saved_chr_ram).unwrap();
+        assert_eq!(nrom.read_chr(0x0123), 0x42);
+        assert_eq!(nrom.load_chr_ram(&[0; 0x1000]).is_err(), true);
+    }
+}
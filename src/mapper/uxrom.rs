@@ -0,0 +1,118 @@
+use mapper::Mapper;
+use mapper::Mirroring;
+use mapper::borrow_chr_ram;
+use mapper::copy_chr_ram;
+use mapper::create_chr_memory;
+
+// Mapper 2. Used by Mega Man, Castlevania, Contra and Duck Tales.
+// $8000 - $BFFF is a switchable 16 KB PRG bank and $C000 - $FFFF is fixed to the last bank. CHR is almost always RAM
+pub struct Uxrom {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    prg_bank: u8,
+    has_bus_conflicts: bool
+}
+
+const PRG_BANK_SIZE: usize = 0x4000;
+
+impl Uxrom {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, chr_ram_size: usize, mirroring: Mirroring, has_bus_conflicts: bool) -> Uxrom {
+        let (chr, chr_is_ram) = create_chr_memory(chr_rom, chr_ram_size);
+        return Uxrom {
+            prg_rom: prg_rom,
+            chr: chr,
+            chr_is_ram: chr_is_ram,
+            mirroring: mirroring,
+            prg_bank: 0,
+            has_bus_conflicts: has_bus_conflicts
+        }
+    }
+}
+
+impl Mapper for Uxrom {
+    fn read_prg(&mut self, address: u16) -> u8 {
+        if address < 0x8000 {
+            return 0;
+        }
+
+        let num_banks = (self.prg_rom.len() / PRG_BANK_SIZE).max(1);
+        let bank = if address < 0xC000 { self.prg_bank as usize % num_banks } else { num_banks - 1 };
+        return self.prg_rom[(bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))) % self.prg_rom.len()];
+    }
+
+    fn write_prg(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.prg_bank = if self.has_bus_conflicts { value & self.read_prg(address) } else { value };
+        }
+    }
+
+    fn read_chr(&mut self, address: u16) -> u8 {
+        return self.chr[address as usize % self.chr.len()];
+    }
+
+    fn write_chr(&mut self, address: u16, value: u8) {
+        if self.chr_is_ram {
+            let chr_length = self.chr.len();
+            self.chr[address as usize % chr_length] = value;
+        }
+    }
+
+    fn get_chr_ram(&self) -> Option<&[u8]> {
+        return borrow_chr_ram(&self.chr, self.chr_is_ram);
+    }
+
+    fn load_chr_ram(&mut self, chr_ram: &[u8]) -> Result<(), String> {
+        return copy_chr_ram(&mut self.chr, self.chr_is_ram, chr_ram);
+    }
+
+    fn get_mirroring(&self) -> Mirroring {
+        return self.mirroring;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mapper::Mapper;
+    use mapper::create_banked_rom;
+    use mapper::Mirroring;
+    use mapper::uxrom::Uxrom;
+
+    #[test]
+    fn lower_bank_switches_and_upper_bank_is_fixed() {
+        let mut uxrom = Uxrom::new(create_banked_rom(8, 0x4000), vec!(), 0x2000, Mirroring::Vertical, false);
+        assert_eq!(uxrom.read_prg(0x8000), 0);
+        assert_eq!(uxrom.read_prg(0xC000), 7);
+
+        uxrom.write_prg(0x8000, 5);
+        assert_eq!(uxrom.read_prg(0xBFFF), 5);
+        assert_eq!(uxrom.read_prg(0xFFFF), 7);
+    }
+
+    #[test]
+    fn prg_smaller_than_a_bank_is_mirrored() {
+        let mut uxrom = Uxrom::new(create_banked_rom(1, 0x2000), vec!(), 0x2000, Mirroring::Vertical, false);
+        uxrom.write_prg(0x8000, 3);
+        assert_eq!(uxrom.read_prg(0x8000), 0);
+        assert_eq!(uxrom.read_prg(0xA000), 0);
+        assert_eq!(uxrom.read_prg(0xFFFF), 0);
+    }
+
+    #[test]
+    fn bus_conflicts_and_the_written_value_with_rom() {
+        let mut prg_rom = create_banked_rom(8, 0x4000);
+        prg_rom[0x1FFF0] = 0b0000_0110; // Byte in the fixed bank the game writes over
+
+        let mut uxrom = Uxrom::new(prg_rom, vec!(), 0x2000, Mirroring::Vertical, true);
+        uxrom.write_prg(0xFFF0, 0b0000_0011);
+        assert_eq!(uxrom.read_prg(0x8000), 0b0000_0010);
+    }
+
+    #[test]
+    fn chr_ram_is_writable() {
+        let mut uxrom = Uxrom::new(create_banked_rom(2, 0x4000), vec!(), 0x2000, Mirroring::Vertical, false);
+        uxrom.write_chr(0x1234, 0x42);
+        assert_eq!(uxrom.read_chr(0x1234), 0x42);
+    }
+}
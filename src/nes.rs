@@ -0,0 +1,309 @@
+use cpu::CPU;
+use cpu::CpuState;
+use ppu::PPU;
+use apu::APU;
+use audio::AudioOutput;
+#[cfg(any(feature = "sdl", test))]
+use controlletron::Button;
+use controlletron::Controlletron;
+#[cfg(any(feature = "sdl", test))]
+use controlletron::Player;
+use mapper::Cartridge;
+use region::Region;
+
+const RESET_SEQUENCE_CYCLES: u64 = 7; // The CPU spends these reading the reset vector before it runs the first instruction
+
+// The whole console, minus any way to see or hear it. Frontends feed it input and pull frames and audio back out,
+// so it can run in a window, in a test, or on a machine with no display at all
+pub struct Nes {
+    // The chips keep raw pointers to each other, so they're boxed to keep them from moving around
+    cpu: Box<CPU>,
+    ppu: Box<PPU>,
+    apu: Box<APU>,
+    #[cfg_attr(not(feature = "sdl"), allow(dead_code))] // Nothing presses buttons without a window, but the CPU still reads the controllers through it
+    controlletron: Box<Controlletron>,
+    cpu_clock_divider: u8, // In master clock cycles
+    ppu_clock_divider: u8,
+    master_clock_cycles: u8 // How far the CPU has gotten towards its next cycle
+}
+
+impl Nes {
+    pub fn new(cartridge: Cartridge, region: Region) -> Nes {
+        let mut cpu = Box::new(CPU::new());
+        cpu.insert_cartridge(cartridge.clone());
+
+        let mut ppu = Box::new(PPU::new(cpu.get_ppu_io_registers_address(), region));
+        ppu.insert_cartridge(cartridge);
+
+        let mut apu = Box::new(APU::new(region));
+        let mut controlletron = Box::new(Controlletron::new());
+        cpu.init_late_pointers(&mut *ppu as *mut PPU, &mut *apu as *mut APU, &mut *controlletron as *mut Controlletron);
+
+        return Nes {
+            cpu: cpu,
+            ppu: ppu,
+            apu: apu,
+            controlletron: controlletron,
+            cpu_clock_divider: region.get_cpu_clock_divider(),
+            ppu_clock_divider: region.get_ppu_clock_divider(),
+            master_clock_cycles: 0
+        }
+    }
+
+    pub fn set_frame_skip(&mut self, frame_skip: u8) {
+        self.ppu.set_frame_skip(frame_skip);
+    }
+
+    pub fn set_sprite_limit_removed(&mut self, sprite_limit_removed: bool) {
+        self.ppu.set_sprite_limit_removed(sprite_limit_removed);
+    }
+
+    // Runs until the PPU finishes the current frame. The APU's output for every CPU cycle is sent to the audio output, if there is one
+    pub fn run_frame(&mut self, audio_output: &mut Option<AudioOutput>) {
+        let frame_count = self.ppu.get_frame_count();
+        while self.ppu.get_frame_count() == frame_count {
+            self.tick(audio_output);
+        }
+    }
+
+    // Runs until the CPU is about to fetch its next opcode. Stops early if the CPU is jammed, since it never will
+    pub fn run_instruction(&mut self) {
+        loop {
+            self.tick(&mut None);
+            let is_cpu_next = self.master_clock_cycles < self.ppu_clock_divider;
+            if (is_cpu_next && self.cpu.is_between_instructions()) || self.cpu.is_jammed() {
+                return;
+            }
+        }
+    }
+
+    // Starts running from an address other than the one in the reset vector, the way nestest's automated mode is started.
+    // The PPU is run through the reset sequence (but the CPU skips it), so the timing matches a console that was just reset
+    pub fn start_at(&mut self, address: u16) {
+        self.cpu.start_at(address, RESET_SEQUENCE_CYCLES);
+        let ppu_cycles = RESET_SEQUENCE_CYCLES * self.cpu_clock_divider as u64 / self.ppu_clock_divider as u64;
+        for _ in 0..ppu_cycles {
+            self.ppu.tick();
+        }
+    }
+
+    // A single PPU cycle
+    fn tick(&mut self, audio_output: &mut Option<AudioOutput>) {
+        // Both chips divide down the same master clock. The CPU (and the APU inside of it) divides it further than the PPU does,
+        // so it only gets a clock signal once for every 3 times the PPU does. Or 3.2 times, on PAL
+        if self.master_clock_cycles < self.ppu_clock_divider {
+            self.cpu.tick();
+            self.apu.tick();
+            if let Some(ref mut audio_output) = *audio_output {
+                audio_output.add_sample(self.apu.get_output());
+            }
+            self.master_clock_cycles += self.cpu_clock_divider;
+        }
+        self.master_clock_cycles -= self.ppu_clock_divider;
+
+        self.ppu.tick();
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        return self.ppu.get_frame_count();
+    }
+
+    pub fn get_cpu_state(&self) -> CpuState {
+        return self.cpu.get_state();
+    }
+
+    // The scanline and dot the PPU is about to run
+    pub fn get_ppu_position(&self) -> (u16, u16) {
+        return self.ppu.get_position();
+    }
+
+    // What the CPU would read from an address, without the side effects a real read might have
+    pub fn read_memory_for_debugging(&self, address: u16) -> u8 {
+        return self.cpu.read_memory_for_debugging(address);
+    }
+
+    // How many instructions the CPU has run since power on
+    pub fn get_instruction_count(&self) -> u64 {
+        return self.cpu.get_instruction_count();
+    }
+
+    // A game that runs a KIL instruction has crashed the console. Everything but the CPU keeps going, so it looks frozen
+    pub fn is_cpu_jammed(&self) -> bool {
+        return self.cpu.is_jammed();
+    }
+
+    // The last frame the PPU finished drawing. 256x240 palette indexes, one row after another
+    #[cfg(any(feature = "sdl", test))]
+    pub fn get_frame(&self) -> &[u16] {
+        return self.ppu.get_framebuffer().get_finished_frame();
+    }
+
+    // How many frames have actually been drawn. Skipped frames don't count, so a frontend can tell when there's something new to show
+    #[cfg(feature = "sdl")]
+    pub fn get_drawn_frame_count(&self) -> u32 {
+        return self.ppu.get_framebuffer().get_finished_frame_count();
+    }
+
+    #[cfg(any(feature = "sdl", test))]
+    pub fn set_button(&mut self, player: Player, button: Button, is_pressed: bool) {
+        self.controlletron.set_button(player, button, is_pressed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nes::Nes;
+    use controlletron::Button;
+    use controlletron::Player;
+    use framebuffer::SCREEN_HEIGHT;
+    use framebuffer::SCREEN_WIDTH;
+    use mapper::Mirroring;
+    use mapper::Nrom;
+    use region::Region;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Instant;
+
+    // A program that copies the A button into $00 over and over again
+    fn create_controller_reading_nes() -> Nes {
+        return create_controller_reading_nes_for_region(Region::Ntsc);
+    }
+
+    fn create_controller_reading_nes_for_region(region: Region) -> Nes {
+        let program: Vec<u8> = vec!(
+            0xA9, 0x01,       // LDA #$01
+            0x8D, 0x16, 0x40, // STA $4016 (Strobe the controllers)
+            0xA9, 0x00,       // LDA #$00
+            0x8D, 0x16, 0x40, // STA $4016 (And latch their buttons)
+            0xAD, 0x16, 0x40, // LDA $4016 (Reads A first)
+            0x85, 0x00,       // STA $00
+            0x4C, 0x00, 0x80  // JMP $8000
+        );
+
+        let mut prg_rom: Vec<u8> = vec![0; 0x8000];
+        prg_rom[..program.len()].copy_from_slice(&program);
+        prg_rom[0x7FFC] = 0x00; // Reset vector
+        prg_rom[0x7FFD] = 0x80;
+
+        return Nes::new(Rc::new(RefCell::new(Nrom::new(prg_rom, vec!(), 0x2000, Mirroring::Horizontal))), region);
+    }
+
+    // A busy loop through most of the addressing modes that leaves the PPU alone, so nearly all the time goes to the CPU
+    fn create_cpu_heavy_nes() -> Nes {
+        let program: Vec<u8> = vec!(
+            0xA9, 0x00,       // LDA #$00
+            0x85, 0x10,       // STA $10
+            0xA9, 0x03,       // LDA #$03
+            0x85, 0x11,       // STA $11 (Pointer to $0300)
+            0xA0, 0x00,       // LDY #$00
+            0xB1, 0x10,       // LDA ($10),Y
+            0x18,             // CLC
+            0x65, 0x20,       // ADC $20
+            0x85, 0x20,       // STA $20
+            0xB9, 0x00, 0x04, // LDA $0400,Y
+            0x49, 0x5A,       // EOR #$5A
+            0x99, 0x00, 0x04, // STA $0400,Y
+            0xE6, 0x21,       // INC $21
+            0x06, 0x22,       // ASL $22
+            0x66, 0x23,       // ROR $23
+            0xBD, 0x80, 0x05, // LDA $0580,X
+            0x20, 0x00, 0x90, // JSR $9000
+            0xC8,             // INY
+            0xD0, 0xE2,       // BNE $800A
+            0x4C, 0x00, 0x80  // JMP $8000
+        );
+        let subroutine: Vec<u8> = vec!(
+            0x48, // PHA
+            0x8A, // TXA
+            0x48, // PHA
+            0x68, // PLA
+            0xAA, // TAX
+            0x68, // PLA
+            0x60  // RTS
+        );
+
+        let mut prg_rom: Vec<u8> = vec![0; 0x8000];
+        prg_rom[..program.len()].copy_from_slice(&program);
+        prg_rom[0x1000..0x1000 + subroutine.len()].copy_from_slice(&subroutine);
+        prg_rom[0x7FFC] = 0x00; // Reset vector
+        prg_rom[0x7FFD] = 0x80;
+
+        return Nes::new(Rc::new(RefCell::new(Nrom::new(prg_rom, vec!(), 0x2000, Mirroring::Horizontal))), Region::Ntsc);
+    }
+
+    #[test]
+    fn instructions_are_counted() {
+        let mut nes = create_cpu_heavy_nes();
+        nes.run_frame(&mut None);
+
+        // A frame is a little under 29781 CPU cycles, and none of the instructions take fewer than 2
+        let instruction_count = nes.get_instruction_count();
+        assert_eq!(instruction_count > 29781 / 7 && instruction_count < 29781 / 2, true);
+    }
+
+    #[test]
+    #[ignore] // A benchmark rather than a test. Run it with cargo test --release cpu_benchmark -- --ignored --nocapture
+    fn cpu_benchmark() {
+        let mut nes = create_cpu_heavy_nes();
+        let start_time = Instant::now();
+        for _ in 0..600 {
+            nes.run_frame(&mut None);
+        }
+
+        let seconds = start_time.elapsed().as_secs_f64();
+        println!("{} instructions in {:.2} seconds. {:.0} instructions per second", nes.get_instruction_count(), seconds,
+            nes.get_instruction_count() as f64 / seconds);
+    }
+
+    #[test]
+    fn frames_can_be_run_without_a_display() {
+        let mut nes = create_controller_reading_nes();
+        nes.run_frame(&mut None);
+        nes.run_frame(&mut None);
+
+        assert_eq!(nes.get_frame_count(), 2);
+        assert_eq!(nes.get_frame().len(), SCREEN_WIDTH * SCREEN_HEIGHT);
+    }
+
+    #[test]
+    fn buttons_are_visible_to_the_game() {
+        let mut nes = create_controller_reading_nes();
+        nes.run_frame(&mut None);
+        assert_eq!(nes.cpu.read_memory_for_debugging(0x00), 0);
+
+        nes.set_button(Player::One, Button::A, true);
+        nes.run_frame(&mut None);
+        assert_eq!(nes.cpu.read_memory_for_debugging(0x00), 1);
+    }
+
+    // Returns how many PPU cycles the frame took
+    fn count_frame_ppu_cycles(nes: &mut Nes) -> u32 {
+        let frame_count = nes.get_frame_count();
+        let mut ppu_cycles = 0;
+        while nes.get_frame_count() == frame_count {
+            nes.tick(&mut None);
+            ppu_cycles += 1;
+        }
+        return ppu_cycles;
+    }
+
+    #[test]
+    fn pal_frames_are_longer() {
+        let mut nes = create_controller_reading_nes_for_region(Region::Pal);
+        nes.run_frame(&mut None);
+        assert_eq!(count_frame_ppu_cycles(&mut nes), 312 * 341);
+    }
+
+    #[test]
+    fn pal_cpu_runs_once_for_every_3_point_2_ppu_cycles() {
+        let mut nes = create_controller_reading_nes_for_region(Region::Pal);
+        let mut cpu_cycles = 0;
+        for _ in 0..1600 {
+            if nes.master_clock_cycles < nes.ppu_clock_divider {
+                cpu_cycles += 1;
+            }
+            nes.tick(&mut None);
+        }
+        assert_eq!(cpu_cycles, 500);
+    }
+}
@@ -0,0 +1,264 @@
+use cpu::CpuState;
+use instruction_set;
+use instruction_set::AddressingMode;
+use instruction_set::InstructionType;
+use instruction_set::Mnemonic;
+use nes::Nes;
+use std::fmt;
+
+// nestest's automated mode, which runs every test one after the other without needing a controller or a screen
+const START_ADDRESS: u16 = 0xC000;
+
+// Where the trace first stopped matching the reference log
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub line_number: usize, // Starting from 1, like a text editor
+    pub expected: String,
+    pub actual: String
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let column = self.expected.chars().zip(self.actual.chars()).take_while(|&(expected, actual)| expected == actual).count();
+        return write!(f, "Line {} is different\n  Expected: {}\n  Actual:   {}\n            {}^", self.line_number, self.expected,
+            self.actual, " ".repeat(column));
+    }
+}
+
+// Runs nestest from its automated mode for as many instructions as the reference log has lines, comparing each line of
+// the trace as it goes. Every line is passed to on_line as well, so it can be saved. Returns how many lines matched
+pub fn run<F: FnMut(&str)>(nes: &mut Nes, reference_log: &str, mut on_line: F) -> Result<usize, Divergence> {
+    nes.start_at(START_ADDRESS);
+
+    let mut line_count = 0;
+    for expected in reference_log.lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()) {
+        if line_count > 0 {
+            nes.run_instruction();
+        }
+        line_count += 1;
+
+        let actual = format_trace_line(nes);
+        on_line(&actual);
+        if actual != expected {
+            return Err(Divergence { line_number: line_count, expected: expected.to_owned(), actual: actual });
+        }
+    }
+    return Ok(line_count);
+}
+
+// nestest keeps the number of the first official and unofficial opcode test that failed at $02 and $03. 0 means they all passed
+pub fn get_result_codes(nes: &Nes) -> (u8, u8) {
+    return (nes.read_memory_for_debugging(0x02), nes.read_memory_for_debugging(0x03));
+}
+
+// The instruction that's about to run and the state of the console before it runs, in the format of the nestest.log that
+// comes with nestest:
+// C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+// Unofficial opcodes have a * in front of them
+pub fn format_trace_line(nes: &Nes) -> String {
+    let state = nes.get_cpu_state();
+    let (scanline, dot) = nes.get_ppu_position();
+    let opcode = nes.read_memory_for_debugging(state.program_counter);
+    let instruction = instruction_set::get_instruction(opcode);
+
+    let bytes: Vec<String> = (0..instruction.num_bytes as u16)
+        .map(|offset| format!("{:02X}", nes.read_memory_for_debugging(state.program_counter.wrapping_add(offset))))
+        .collect();
+    let marker = if instruction_set::is_unofficial(opcode) { "*" } else { " " };
+    let disassembly = format!("{:?} {}", instruction.mnemonic, format_operand(nes, &state, instruction)).to_uppercase();
+
+    return format!("{:04X}  {:<9}{}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:>3},{:>3} CYC:{}",
+        state.program_counter, bytes.join(" "), marker, disassembly.trim_end(), state.accumulator, state.x_register,
+        state.y_register, state.status_register, state.stack_pointer, scanline, dot, state.cycle_count);
+}
+
+// Anything the instruction reads or writes is shown along with the value that's there before it runs. So are the addresses
+// that indexing and indirection end up at
+fn format_operand(nes: &Nes, state: &CpuState, instruction: InstructionType) -> String {
+    let low_byte = nes.read_memory_for_debugging(state.program_counter.wrapping_add(1));
+    let high_byte = nes.read_memory_for_debugging(state.program_counter.wrapping_add(2));
+    let absolute_address = (high_byte as u16) << 8 | low_byte as u16;
+
+    match instruction.addressing_mode {
+        AddressingMode::Implied => return String::new(),
+        AddressingMode::Accumulator => return "A".to_owned(),
+        AddressingMode::Immediate => return format!("#${:02X}", low_byte),
+        AddressingMode::ZeroPageAbsolute => {
+            return format!("${:02X} = {:02X}", low_byte, read_value(nes, low_byte as u16));
+        },
+        AddressingMode::ZeroPageAbsoluteX | AddressingMode::ZeroPageAbsoluteY => {
+            let (index_name, index) = get_index(state, instruction.addressing_mode);
+            let address = low_byte.wrapping_add(index);
+            return format!("${:02X},{} @ {:02X} = {:02X}", low_byte, index_name, address, read_value(nes, address as u16));
+        },
+        AddressingMode::Absolute => {
+            if instruction.mnemonic == Mnemonic::Jmp || instruction.mnemonic == Mnemonic::Jsr {
+                return format!("${:04X}", absolute_address);
+            }
+            return format!("${:04X} = {:02X}", absolute_address, read_value(nes, absolute_address));
+        },
+        AddressingMode::AbsoluteX | AddressingMode::AbsoluteY => {
+            let (index_name, index) = get_index(state, instruction.addressing_mode);
+            let address = absolute_address.wrapping_add(index as u16);
+            return format!("${:04X},{} @ {:04X} = {:02X}", absolute_address, index_name, address, read_value(nes, address));
+        },
+        AddressingMode::Indirect => {
+            // The high byte of the target comes from the start of the same page when the pointer is at the end of one
+            let high_byte_address = (absolute_address & 0xFF00) | (absolute_address as u8).wrapping_add(1) as u16;
+            let target = (nes.read_memory_for_debugging(high_byte_address) as u16) << 8 | nes.read_memory_for_debugging(absolute_address) as u16;
+            return format!("(${:04X}) = {:04X}", absolute_address, target);
+        },
+        AddressingMode::PreIndexedIndirect => {
+            let pointer = low_byte.wrapping_add(state.x_register);
+            let address = read_zero_page_pointer(nes, pointer);
+            return format!("(${:02X},X) @ {:02X} = {:04X} = {:02X}", low_byte, pointer, address, read_value(nes, address));
+        },
+        AddressingMode::PostIndexedIndirect => {
+            let base_address = read_zero_page_pointer(nes, low_byte);
+            let address = base_address.wrapping_add(state.y_register as u16);
+            return format!("(${:02X}),Y = {:04X} @ {:04X} = {:02X}", low_byte, base_address, address, read_value(nes, address));
+        },
+        AddressingMode::Relative => {
+            let target = state.program_counter.wrapping_add(2).wrapping_add(low_byte as i8 as u16);
+            return format!("${:04X}", target);
+        }
+    }
+}
+
+fn get_index(state: &CpuState, addressing_mode: AddressingMode) -> (&'static str, u8) {
+    match addressing_mode {
+        AddressingMode::ZeroPageAbsoluteX | AddressingMode::AbsoluteX => return ("X", state.x_register),
+        _ => return ("Y", state.y_register)
+    }
+}
+
+// Pointers wrap around within the zero page
+fn read_zero_page_pointer(nes: &Nes, pointer: u8) -> u16 {
+    let low_byte = nes.read_memory_for_debugging(pointer as u16) as u16;
+    let high_byte = nes.read_memory_for_debugging(pointer.wrapping_add(1) as u16) as u16;
+    return high_byte << 8 | low_byte;
+}
+
+// The PPU, APU and IO registers show up as FF in nestest.log, since reading them for real could change them
+fn read_value(nes: &Nes, address: u16) -> u8 {
+    if address >= 0x2000 && address < 0x4020 {
+        return 0xFF;
+    }
+    return nes.read_memory_for_debugging(address);
+}
+
+#[cfg(test)]
+mod tests {
+    use nestest;
+    use nestest::Divergence;
+    use nes::Nes;
+    use mapper;
+    use mapper::Mirroring;
+    use mapper::Nrom;
+    use region::Region;
+    use rom::Rom;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::rc::Rc;
+
+    // nestest.nes and its nestest.log aren't distributed with the emulator, so they have to be put here to run the test against them
+    const NESTEST_ROM_PATH: &'static str = "test_roms/nestest.nes";
+    const NESTEST_LOG_PATH: &'static str = "test_roms/nestest.log";
+
+    // Runs the program from $C000, like nestest
+    fn create_nes(program: &[u8]) -> Nes {
+        let mut prg_rom: Vec<u8> = vec![0xEA; 0x8000]; // NOP
+        prg_rom[0x4000..0x4000 + program.len()].copy_from_slice(program);
+        return Nes::new(Rc::new(RefCell::new(Nrom::new(prg_rom, vec!(), 0x2000, Mirroring::Horizontal))), Region::Ntsc);
+    }
+
+    fn create_addressing_mode_nes() -> Nes {
+        return create_nes(&[
+            0x4C, 0x05, 0xC0, // JMP $C005
+            0xEA, 0xEA,
+            0xA2, 0x02,       // LDX #$02
+            0x86, 0x10,       // STX $10
+            0xB5, 0x0E,       // LDA $0E,X
+            0x04, 0xA9,       // *NOP $A9
+            0xA0, 0x03,       // LDY #$03
+            0xB1, 0x0F,       // LDA ($0F),Y
+            0x9D, 0xFF, 0x06, // STA $06FF,X
+            0x4A,             // LSR A
+            0xD0, 0xFC        // BNE $C014
+        ]);
+    }
+
+    fn trace(nes: &mut Nes, line_count: usize) -> Vec<String> {
+        nes.start_at(0xC000);
+        let mut lines = vec!(nestest::format_trace_line(nes));
+        while lines.len() < line_count {
+            nes.run_instruction();
+            lines.push(nestest::format_trace_line(nes));
+        }
+        return lines;
+    }
+
+    #[test]
+    fn trace_lines_are_in_the_nestest_log_format() {
+        let mut nes = create_addressing_mode_nes();
+        let lines = trace(&mut nes, 10);
+
+        assert_eq!(lines, vec!(
+            "C000  4C 05 C0  JMP $C005                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7",
+            "C005  A2 02     LDX #$02                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 30 CYC:10",
+            "C007  86 10     STX $10 = 00                    A:00 X:02 Y:00 P:24 SP:FD PPU:  0, 36 CYC:12",
+            "C009  B5 0E     LDA $0E,X @ 10 = 02             A:00 X:02 Y:00 P:24 SP:FD PPU:  0, 45 CYC:15",
+            "C00B  04 A9    *NOP $A9 = 00                    A:02 X:02 Y:00 P:24 SP:FD PPU:  0, 57 CYC:19",
+            "C00D  A0 03     LDY #$03                        A:02 X:02 Y:00 P:24 SP:FD PPU:  0, 66 CYC:22",
+            "C00F  B1 0F     LDA ($0F),Y = 0200 @ 0203 = 00  A:02 X:02 Y:03 P:24 SP:FD PPU:  0, 72 CYC:24",
+            "C011  9D FF 06  STA $06FF,X @ 0701 = 00         A:00 X:02 Y:03 P:26 SP:FD PPU:  0, 87 CYC:29",
+            "C014  4A        LSR A                           A:00 X:02 Y:03 P:26 SP:FD PPU:  0,102 CYC:34",
+            "C015  D0 FC     BNE $C013                       A:00 X:02 Y:03 P:26 SP:FD PPU:  0,108 CYC:36"
+        ));
+    }
+
+    #[test]
+    fn the_first_divergence_is_reported() {
+        let mut nes = create_addressing_mode_nes();
+        let mut reference_log = trace(&mut nes, 3).join("\r\n"); // Line endings shouldn't matter
+        reference_log = reference_log.replace("X:02 Y:00 P:24 SP:FD PPU:  0, 36", "X:02 Y:00 P:25 SP:FD PPU:  0, 36");
+
+        let mut traced_lines: Vec<String> = vec!();
+        let result = nestest::run(&mut create_addressing_mode_nes(), &reference_log, |line| traced_lines.push(line.to_owned()));
+
+        let divergence = result.unwrap_err();
+        assert_eq!(divergence.line_number, 3);
+        assert_eq!(divergence.actual, traced_lines[2]);
+        assert_eq!(divergence.expected.contains("P:25"), true);
+        assert_eq!(format!("{}", divergence).lines().last().unwrap(), format!("{}^", " ".repeat(12 + 66))); // Under the 5 of P:25
+    }
+
+    #[test]
+    fn matching_traces_count_their_lines() {
+        let mut nes = create_addressing_mode_nes();
+        let reference_log = trace(&mut nes, 8).join("\n") + "\n";
+
+        let result = nestest::run(&mut create_addressing_mode_nes(), &reference_log, |_| { });
+        assert_eq!(result, Ok(8));
+    }
+
+    #[test]
+    #[ignore] // Needs test_roms/nestest.nes and test_roms/nestest.log. Run it with cargo test nestest -- --ignored
+    fn nestest_matches_its_reference_log() {
+        let rom_data = fs::read(NESTEST_ROM_PATH).expect("Could not read test_roms/nestest.nes");
+        let reference_log = fs::read_to_string(NESTEST_LOG_PATH).expect("Could not read test_roms/nestest.log");
+
+        let cartridge = mapper::create_cartridge(Rom::from_bytes(&rom_data).unwrap()).unwrap();
+        let mut nes = Nes::new(cartridge, Region::Ntsc);
+        if let Err(divergence) = nestest::run(&mut nes, &reference_log, |_| { }) {
+            panic!("{}", divergence);
+        }
+        assert_eq!(nestest::get_result_codes(&nes), (0, 0));
+    }
+
+    #[test]
+    fn divergences_point_at_the_first_different_character() {
+        let divergence = Divergence { line_number: 7, expected: "C000  A:00".to_owned(), actual: "C000  A:01".to_owned() };
+        assert_eq!(format!("{}", divergence), "Line 7 is different\n  Expected: C000  A:00\n  Actual:   C000  A:01\n                     ^");
+    }
+}
@@ -0,0 +1,321 @@
+use region::Region;
+
+pub const USAGE: &'static str = "Usage: madnes [OPTIONS] <ROM>
+
+Options:
+    --region <ntsc|pal|dendy>   Console region to emulate (detected from the ROM when omitted)
+    --scale <N>                 Window scaling factor (default 3)
+    --headless                  Run without showing a window
+    --frames <N>                Stop after N frames have been rendered
+    --sample-rate <44100|48000> Audio sample rate (default 48000)
+    --wav <FILE>                Write the audio to a WAV file instead of playing it
+    --vsync                     Wait for the display's vertical sync before showing each frame
+    --frame-skip <N>            Only draw one out of every N + 1 frames (default 0)
+    --no-sprite-limit           Draw every sprite on a scanline instead of only 8. Reduces flickering
+    --palette <FILE|ntsc>       Load the colors from a .pal file, or generate them from the NES's NTSC video signal
+    --hue <DEGREES>             Hue adjustment for the generated ntsc palette (default 0)
+    --saturation <N>            Saturation of the generated ntsc palette (default 1.0)
+    --contrast <N>              Contrast of the generated ntsc palette (default 1.0)
+    --gamma <N>                 Gamma of the TV the generated ntsc palette imitates (default 2.2)
+    --fast-forward <N>          How many times faster to run while the fast-forward key is held (default 4)
+    --benchmark                 Run headless for --frames frames (default 600) and report how fast the CPU went
+    --nestest <LOG>             Run nestest from $C000 and compare its trace with a reference nestest.log
+    --trace <FILE>              Write the --nestest trace to a file
+    -h, --help                  Print this message";
+
+const DEFAULT_SCALE: u8 = 3;
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+const SUPPORTED_SAMPLE_RATES: [u32; 2] = [44100, 48000];
+const DEFAULT_FAST_FORWARD_SPEED: u8 = 4;
+const DEFAULT_BENCHMARK_FRAMES: u32 = 600;
+
+#[cfg_attr(not(feature = "sdl"), allow(dead_code))] // The window's settings are still checked when there's no window to use them
+pub struct Options {
+    pub rom_path: String,
+    pub region: Option<Region>, // None means the region should be detected from the ROM itself
+    pub scale: u8,
+    pub headless: bool,
+    pub frame_limit: Option<u32>,
+    pub sample_rate: u32,
+    pub wav_path: Option<String>,
+    pub vsync: bool,
+    pub frame_skip: u8,
+    pub sprite_limit_removed: bool,
+    pub palette: PaletteSource,
+    pub fast_forward_speed: u8,
+    pub benchmark: bool,
+    pub nestest_log_path: Option<String>,
+    pub trace_path: Option<String>
+}
+
+// Where the colors come from. Everyone has their own idea of what the NES's colors really look like, since it never had any
+// RGB colors to begin with. It made a composite video signal, and every TV decoded that a little differently
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaletteSource {
+    Builtin,
+    File(String), // A .pal file. Either the 64 colors (192 bytes), or all 512 emphasized versions of them too (1536 bytes)
+    Ntsc(NtscSettings)
+}
+
+// The knobs on the TV, for the generated NTSC palette
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NtscSettings {
+    pub hue: f32, // In degrees
+    pub saturation: f32,
+    pub contrast: f32,
+    pub gamma: f32 // Of the TV being imitated. 2.2 leaves the decoded colors alone, since that's what PC monitors use
+}
+
+impl NtscSettings {
+    pub fn new() -> NtscSettings {
+        return NtscSettings {
+            hue: 0.0,
+            saturation: 1.0,
+            contrast: 1.0,
+            gamma: 2.2
+        }
+    }
+}
+
+// Takes the command line arguments, minus the program name
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut rom_path: Option<String> = None;
+    let mut region: Option<Region> = None;
+    let mut scale = DEFAULT_SCALE;
+    let mut headless = false;
+    let mut frame_limit: Option<u32> = None;
+    let mut sample_rate = DEFAULT_SAMPLE_RATE;
+    let mut wav_path: Option<String> = None;
+    let mut vsync = false;
+    let mut frame_skip = 0;
+    let mut sprite_limit_removed = false;
+    let mut palette_path: Option<String> = None;
+    let mut ntsc_settings = NtscSettings::new();
+    let mut ntsc_settings_changed = false;
+    let mut fast_forward_speed = DEFAULT_FAST_FORWARD_SPEED;
+    let mut benchmark = false;
+    let mut nestest_log_path: Option<String> = None;
+    let mut trace_path: Option<String> = None;
+
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].as_str();
+        match arg {
+            "--headless" => headless = true,
+            "--vsync" => vsync = true,
+            "--no-sprite-limit" => sprite_limit_removed = true,
+            "--benchmark" => benchmark = true,
+            "--region" | "--scale" | "--frames" | "--sample-rate" | "--wav" | "--frame-skip" | "--fast-forward" | "--palette"
+            | "--hue" | "--saturation" | "--contrast" | "--gamma" | "--nestest" | "--trace" => {
+                index += 1;
+                if index >= args.len() {
+                    return Err(format!("{} requires a value", arg));
+                }
+
+                let value = args[index].as_str();
+                match arg {
+                    "--region" => {
+                        region = Some(Region::from_name(value).ok_or(format!("Unknown region '{}'", value))?);
+                    },
+                    "--scale" => {
+                        scale = parse_number(arg, value)?;
+                        if scale == 0 {
+                            return Err("--scale must be at least 1".to_owned());
+                        }
+                    },
+                    "--sample-rate" => {
+                        sample_rate = parse_number(arg, value)?;
+                        if !SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+                            return Err(format!("--sample-rate must be 44100 or 48000. Got {}", sample_rate));
+                        }
+                    },
+                    "--wav" => wav_path = Some(value.to_owned()),
+                    "--frame-skip" => frame_skip = parse_number(arg, value)?,
+                    "--fast-forward" => {
+                        fast_forward_speed = parse_number(arg, value)?;
+                        if fast_forward_speed == 0 {
+                            return Err("--fast-forward must be at least 1".to_owned());
+                        }
+                    },
+                    "--palette" => palette_path = Some(value.to_owned()),
+                    "--nestest" => nestest_log_path = Some(value.to_owned()),
+                    "--trace" => trace_path = Some(value.to_owned()),
+                    "--hue" | "--saturation" | "--contrast" | "--gamma" => {
+                        let setting: f32 = parse_number(arg, value)?;
+                        match arg {
+                            "--hue" => ntsc_settings.hue = setting,
+                            "--saturation" if setting >= 0.0 => ntsc_settings.saturation = setting,
+                            "--contrast" if setting > 0.0 => ntsc_settings.contrast = setting,
+                            "--gamma" if setting > 0.0 => ntsc_settings.gamma = setting,
+                            _ => return Err(format!("{} can't be {}", arg, setting))
+                        }
+                        ntsc_settings_changed = true;
+                    },
+                    _ => frame_limit = Some(parse_number(arg, value)?)
+                }
+            },
+            _ => {
+                if arg.starts_with("-") {
+                    return Err(format!("Unknown option '{}'", arg));
+                }
+                if rom_path.is_some() {
+                    return Err(format!("Only one ROM can be loaded at a time. Unexpected argument '{}'", arg));
+                }
+                rom_path = Some(arg.to_owned());
+            }
+        }
+        index += 1;
+    }
+
+    let palette = match palette_path {
+        Some(ref palette_path) if palette_path == "ntsc" => PaletteSource::Ntsc(ntsc_settings),
+        _ if ntsc_settings_changed => return Err("--hue, --saturation, --contrast and --gamma only work with --palette ntsc".to_owned()),
+        Some(palette_path) => PaletteSource::File(palette_path),
+        None => PaletteSource::Builtin
+    };
+
+    if trace_path.is_some() && nestest_log_path.is_none() {
+        return Err("--trace only works with --nestest".to_owned());
+    }
+
+    // A benchmark has to end sometime, and drawing to a window would only slow it down
+    if benchmark {
+        headless = true;
+        frame_limit = frame_limit.or(Some(DEFAULT_BENCHMARK_FRAMES));
+    }
+
+    return Ok(Options {
+        rom_path: rom_path.ok_or("No ROM file was given".to_owned())?,
+        region: region,
+        scale: scale,
+        headless: headless,
+        frame_limit: frame_limit,
+        sample_rate: sample_rate,
+        wav_path: wav_path,
+        vsync: vsync,
+        frame_skip: frame_skip,
+        sprite_limit_removed: sprite_limit_removed,
+        palette: palette,
+        fast_forward_speed: fast_forward_speed,
+        benchmark: benchmark,
+        nestest_log_path: nestest_log_path,
+        trace_path: trace_path
+    });
+}
+
+pub fn wants_help(args: &[String]) -> bool {
+    return args.iter().any(|arg| arg == "-h" || arg == "--help");
+}
+
+fn parse_number<T: ::std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    return value.parse::<T>().map_err(|_| format!("{} expects a number but got '{}'", option, value));
+}
+
+#[cfg(test)]
+mod tests {
+    use options;
+    use options::NtscSettings;
+    use options::PaletteSource;
+    use region::Region;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        return args.iter().map(|arg| arg.to_string()).collect();
+    }
+
+    #[test]
+    fn rom_path_is_required() {
+        assert_eq!(options::parse_args(&to_args(&[])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--headless"])).is_err(), true);
+    }
+
+    #[test]
+    fn defaults_are_used_when_only_a_rom_is_given() {
+        let options = options::parse_args(&to_args(&["Zelda.nes"])).unwrap();
+
+        assert_eq!(options.rom_path, "Zelda.nes");
+        assert_eq!(options.region, None);
+        assert_eq!(options.scale, 3);
+        assert_eq!(options.headless, false);
+        assert_eq!(options.frame_limit, None);
+        assert_eq!(options.sample_rate, 48000);
+        assert_eq!(options.wav_path, None);
+        assert_eq!(options.vsync, false);
+        assert_eq!(options.frame_skip, 0);
+        assert_eq!(options.sprite_limit_removed, false);
+        assert_eq!(options.palette, PaletteSource::Builtin);
+        assert_eq!(options.fast_forward_speed, 4);
+        assert_eq!(options.benchmark, false);
+        assert_eq!(options.nestest_log_path, None);
+        assert_eq!(options.trace_path, None);
+    }
+
+    #[test]
+    fn all_options_can_be_parsed() {
+        let args = to_args(&["--region", "pal", "--scale", "2", "--headless", "--frames", "600", "--sample-rate", "44100",
+            "--wav", "contra.wav", "--vsync", "--frame-skip", "1", "--no-sprite-limit", "--fast-forward", "8", "--palette", "smooth.pal", "Contra (USA).nes"]);
+        let options = options::parse_args(&args).unwrap();
+
+        assert_eq!(options.rom_path, "Contra (USA).nes");
+        assert_eq!(options.region, Some(Region::Pal));
+        assert_eq!(options.scale, 2);
+        assert_eq!(options.headless, true);
+        assert_eq!(options.frame_limit, Some(600));
+        assert_eq!(options.sample_rate, 44100);
+        assert_eq!(options.wav_path, Some("contra.wav".to_owned()));
+        assert_eq!(options.vsync, true);
+        assert_eq!(options.frame_skip, 1);
+        assert_eq!(options.sprite_limit_removed, true);
+        assert_eq!(options.palette, PaletteSource::File("smooth.pal".to_owned()));
+        assert_eq!(options.fast_forward_speed, 8);
+    }
+
+    #[test]
+    fn benchmarks_run_headless_for_a_limited_number_of_frames() {
+        let options = options::parse_args(&to_args(&["--benchmark", "smb.nes"])).unwrap();
+        assert_eq!(options.benchmark, true);
+        assert_eq!(options.headless, true);
+        assert_eq!(options.frame_limit, Some(600));
+
+        let options = options::parse_args(&to_args(&["--benchmark", "--frames", "60", "smb.nes"])).unwrap();
+        assert_eq!(options.frame_limit, Some(60));
+    }
+
+    #[test]
+    fn nestest_traces_can_be_saved() {
+        let options = options::parse_args(&to_args(&["--nestest", "nestest.log", "--trace", "madnes.log", "nestest.nes"])).unwrap();
+        assert_eq!(options.nestest_log_path, Some("nestest.log".to_owned()));
+        assert_eq!(options.trace_path, Some("madnes.log".to_owned()));
+
+        assert_eq!(options::parse_args(&to_args(&["--trace", "madnes.log", "nestest.nes"])).is_err(), true);
+    }
+
+    #[test]
+    fn ntsc_palette_settings_can_be_parsed() {
+        let args = to_args(&["--palette", "ntsc", "--hue", "-15", "--saturation", "1.2", "--contrast", "0.9", "--gamma", "2", "smb.nes"]);
+        let options = options::parse_args(&args).unwrap();
+
+        assert_eq!(options.palette, PaletteSource::Ntsc(NtscSettings { hue: -15.0, saturation: 1.2, contrast: 0.9, gamma: 2.0 }));
+        assert_eq!(options::parse_args(&to_args(&["--palette", "ntsc", "smb.nes"])).unwrap().palette, PaletteSource::Ntsc(NtscSettings::new()));
+    }
+
+    #[test]
+    fn bad_values_are_rejected() {
+        assert_eq!(options::parse_args(&to_args(&["--scale", "big", "smb.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--scale", "0", "smb.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--region", "secam", "smb.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["smb.nes", "--frames"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--turbo", "smb.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["smb.nes", "smb3.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--sample-rate", "22050", "smb.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--fast-forward", "0", "smb.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--palette", "ntsc", "--gamma", "0", "smb.nes"])).is_err(), true);
+        assert_eq!(options::parse_args(&to_args(&["--palette", "smooth.pal", "--hue", "10", "smb.nes"])).is_err(), true);
+    }
+
+    #[test]
+    fn help_flag_is_detected() {
+        assert_eq!(options::wants_help(&to_args(&["smb.nes", "--help"])), true);
+        assert_eq!(options::wants_help(&to_args(&["-h"])), true);
+        assert_eq!(options::wants_help(&to_args(&["smb.nes"])), false);
+    }
+}
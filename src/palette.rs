@@ -0,0 +1,259 @@
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::prelude::*;
+use options::NtscSettings;
+use options::PaletteSource;
+
+// Turns the pixels the PPU draws into RGB colors. A pixel is one of the NES's 64 colors, with the 3 color emphasis bits
+// of $2001 on top of it (bit 6 is red, bit 7 green and bit 8 blue). So a full palette has 512 entries
+pub const PALETTE_SIZE: usize = 512;
+const BASE_COLOR_COUNT: usize = 64;
+
+// Emphasizing a color really works by darkening the other two. This is about how much each emphasis bit darkens them by
+const EMPHASIS_ATTENUATION: f32 = 0.816;
+
+pub struct Palette {
+    colors: Vec<[u8; 3]>
+}
+
+// Voltages of the 2C02's video signal. Each color is a square wave alternating between a low and a high voltage, one for each of
+// its 4 brightness levels. Measurements from http://wiki.nesdev.com/w/index.php/NTSC_video
+const SIGNAL_LOW_LEVELS: [f32; 4] = [0.350, 0.518, 0.962, 1.550];
+const SIGNAL_HIGH_LEVELS: [f32; 4] = [1.094, 1.506, 1.962, 1.962];
+const SIGNAL_BLACK: f32 = 0.518;
+const SIGNAL_WHITE: f32 = 1.962;
+const EMPHASIS_SIGNAL_ATTENUATION: f32 = 0.746;
+// The signal's color wave is made up of 12 phases, 30 degrees apart
+const COLOR_PHASES: i32 = 12;
+// Lines the decoded hues up with the TV's color burst, so hue 0 comes out looking like the usual NES colors
+const HUE_PHASE_OFFSET: f32 = 4.0;
+const SRGB_GAMMA: f32 = 2.2;
+
+impl Palette {
+    pub fn new() -> Palette {
+        return Palette::from_base_colors(&DEFAULT_COLORS);
+    }
+
+    pub fn from_source(source: &PaletteSource) -> Result<Palette, String> {
+        return match *source {
+            PaletteSource::Builtin => Ok(Palette::new()),
+            PaletteSource::File(ref path) => Palette::load(path),
+            PaletteSource::Ntsc(ref settings) => Ok(Palette::generate_ntsc(settings))
+        };
+    }
+
+    pub fn load(path: &str) -> Result<Palette, String> {
+        let mut buffer = Vec::new();
+        let mut file = File::open(path).map_err(|error| format!("Could not open palette '{}': {}", path, error))?;
+        file.read_to_end(&mut buffer).map_err(|error| format!("Could not read palette '{}': {}", path, error))?;
+
+        return Palette::from_bytes(&buffer);
+    }
+
+    // Palette files are just the colors one after the other, 3 bytes of RGB each
+    pub fn from_bytes(bytes: &[u8]) -> Result<Palette, String> {
+        if bytes.len() != BASE_COLOR_COUNT * 3 && bytes.len() != PALETTE_SIZE * 3 {
+            return Err(format!("Palette files must be {} or {} bytes long, but this one is {}", BASE_COLOR_COUNT * 3, PALETTE_SIZE * 3, bytes.len()));
+        }
+
+        let colors: Vec<[u8; 3]> = bytes.chunks(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect();
+        if colors.len() == BASE_COLOR_COUNT {
+            return Ok(Palette::from_base_colors(&colors));
+        }
+
+        return Ok(Palette {
+            colors: colors
+        });
+    }
+
+    // Decodes the video signal the NES would send out for each color, the same way a TV would.
+    // Based on Bisqwit's palette generator, https://bisqwit.iki.fi/jutut/nesemu1_ntsc.html
+    pub fn generate_ntsc(settings: &NtscSettings) -> Palette {
+        let mut colors = Vec::with_capacity(PALETTE_SIZE);
+        for pixel in 0..PALETTE_SIZE as u16 {
+            // Average the signal over a full wave, to get the brightness (Y) and color (I and Q) the TV would see
+            let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+            for phase in 0..COLOR_PHASES {
+                let signal = (get_ntsc_signal(pixel, phase) - SIGNAL_BLACK) / (SIGNAL_WHITE - SIGNAL_BLACK);
+                let angle = PI * (phase as f32 + HUE_PHASE_OFFSET) / 6.0 + settings.hue.to_radians();
+                y += signal;
+                i += signal * angle.cos();
+                q += signal * angle.sin();
+            }
+
+            y *= settings.contrast / COLOR_PHASES as f32;
+            i *= settings.contrast * settings.saturation / COLOR_PHASES as f32;
+            q *= settings.contrast * settings.saturation / COLOR_PHASES as f32;
+
+            let red = y + 0.946882 * i + 0.623557 * q;
+            let green = y - 0.274788 * i - 0.635691 * q;
+            let blue = y - 1.108545 * i + 1.709007 * q;
+            colors.push([
+                to_color_channel(red, settings.gamma),
+                to_color_channel(green, settings.gamma),
+                to_color_channel(blue, settings.gamma)
+            ]);
+        }
+
+        return Palette {
+            colors: colors
+        }
+    }
+
+    // Works out the emphasized versions of each of the 64 colors
+    pub fn from_base_colors(base_colors: &[[u8; 3]]) -> Palette {
+        let mut colors = Vec::with_capacity(PALETTE_SIZE);
+        for emphasis in 0..8u8 {
+            for color in base_colors.iter().take(BASE_COLOR_COUNT) {
+                let mut emphasized_color = *color;
+                for channel in 0..3 {
+                    // A channel is darkened once for every emphasis bit that isn't its own
+                    let darkening_bits = (emphasis & !(1 << channel)).count_ones() as i32;
+                    emphasized_color[channel] = (color[channel] as f32 * EMPHASIS_ATTENUATION.powi(darkening_bits)).round() as u8;
+                }
+                colors.push(emphasized_color);
+            }
+        }
+
+        return Palette {
+            colors: colors
+        }
+    }
+
+    pub fn get_color(&self, pixel: u16) -> [u8; 3] {
+        return self.colors[pixel as usize % PALETTE_SIZE];
+    }
+}
+
+// The voltage the NES outputs for a pixel at one of the 12 phases of the color wave
+fn get_ntsc_signal(pixel: u16, phase: i32) -> f32 {
+    let hue = (pixel & 0x0F) as i32;
+    let emphasis = pixel >> 6;
+    // Hues $E and $F are always black, which is level 1's low voltage
+    let level = if hue >= 0xE { 1 } else { ((pixel >> 4) & 0b11) as usize };
+
+    // Hue 0 is a grey made of only the high voltage. $D - $F only use the low voltage. Everything else is a wave between them
+    let low = if hue == 0 { SIGNAL_HIGH_LEVELS[level] } else { SIGNAL_LOW_LEVELS[level] };
+    let high = if hue >= 0xD { SIGNAL_LOW_LEVELS[level] } else { SIGNAL_HIGH_LEVELS[level] };
+    let is_in_phase = |hue: i32| (hue + phase) % COLOR_PHASES < COLOR_PHASES / 2;
+    let signal = if is_in_phase(hue) { high } else { low };
+
+    // Each emphasis bit lowers the signal during the part of the wave where its color (red, green or blue) is
+    let is_attenuated = (emphasis & 0b001 != 0 && is_in_phase(0))
+        || (emphasis & 0b010 != 0 && is_in_phase(4))
+        || (emphasis & 0b100 != 0 && is_in_phase(8));
+
+    return if is_attenuated { signal * EMPHASIS_SIGNAL_ATTENUATION } else { signal };
+}
+
+fn to_color_channel(value: f32, gamma: f32) -> u8 {
+    let corrected = value.max(0.0).powf(SRGB_GAMMA / gamma);
+    return (corrected * 255.0).round().min(255.0) as u8;
+}
+
+const DEFAULT_COLORS: [[u8; 3]; BASE_COLOR_COUNT] = [
+    [84, 84, 84], [0, 20, 116], [8, 16, 144], [48, 0, 136], [68, 0, 100], [92, 0, 48], [84, 4, 0], [60, 24, 0],
+    [32, 42, 0], [8, 58, 0], [0, 64, 0], [0, 60, 0], [0, 50, 60], [0, 0, 0], [0, 0, 0], [0, 0, 0],
+
+    [152, 150, 152], [8, 76, 196], [48, 50, 226], [92, 30, 228], [136, 20, 176], [160, 20, 100], [152, 34, 32], [120, 60, 0],
+    [84, 90, 0], [40, 114, 0], [8, 124, 0], [0, 118, 40], [0, 102, 120], [0, 0, 0], [0, 0, 0], [0, 0, 0],
+
+    [236, 238, 236], [76, 154, 236], [120, 124, 236], [176, 98, 236], [228, 84, 236], [236, 88, 180], [236, 106, 100], [212, 136, 32],
+    [160, 170, 0], [116, 196, 0], [76, 208, 32], [56, 204, 108], [56, 180, 204], [60, 60, 60], [0, 0, 0], [0, 0, 0],
+
+    [236, 238, 236], [168, 204, 236], [188, 188, 236], [212, 178, 236], [236, 174, 236], [236, 174, 212], [236, 180, 176], [228, 196, 114],
+    [204, 210, 120], [180, 222, 120], [168, 226, 114], [152, 226, 180], [160, 214, 228], [160, 162, 160], [0, 0, 0], [0, 0, 0]
+];
+
+#[cfg(test)]
+mod tests {
+    use options::NtscSettings;
+    use palette::Palette;
+
+    #[test]
+    fn colors_without_emphasis_are_unchanged() {
+        let palette = Palette::new();
+        assert_eq!(palette.get_color(0x00), [84, 84, 84]);
+        assert_eq!(palette.get_color(0x30), [236, 238, 236]);
+    }
+
+    #[test]
+    fn emphasis_darkens_the_other_channels() {
+        let palette = Palette::new();
+        let red_emphasis = 0b001 << 6;
+        assert_eq!(palette.get_color(red_emphasis | 0x30), [236, 194, 193]);
+
+        let blue_emphasis = 0b100 << 6;
+        assert_eq!(palette.get_color(blue_emphasis | 0x30), [193, 194, 236]);
+
+        // With every bit set, every channel is darkened twice
+        let all_emphasis = 0b111 << 6;
+        assert_eq!(palette.get_color(all_emphasis | 0x30), [157, 158, 157]);
+    }
+
+    #[test]
+    fn palette_files_can_have_64_or_512_colors() {
+        let palette = Palette::from_bytes(&[0x40; 192]).unwrap();
+        assert_eq!(palette.get_color(0x3F), [0x40, 0x40, 0x40]);
+        assert_eq!(palette.get_color(0x1FF), [0x2B, 0x2B, 0x2B]); // The emphasized colors are worked out
+
+        let mut bytes = vec![0x40; 1536];
+        bytes[1535] = 0x80;
+        let palette = Palette::from_bytes(&bytes).unwrap();
+        assert_eq!(palette.get_color(0x3F), [0x40, 0x40, 0x40]);
+        assert_eq!(palette.get_color(0x1FF), [0x40, 0x40, 0x80]);
+
+        assert_eq!(Palette::from_bytes(&[0x40; 191]).is_err(), true);
+    }
+
+    #[test]
+    fn generated_palette_has_greys_black_and_white() {
+        let palette = Palette::generate_ntsc(&NtscSettings::new());
+        assert_eq!(palette.get_color(0x0F), [0, 0, 0]);
+        assert_eq!(palette.get_color(0x20), [255, 255, 255]);
+
+        let grey = palette.get_color(0x10);
+        assert_eq!(grey[0], grey[1]);
+        assert_eq!(grey[1], grey[2]);
+        assert!(grey[0] > 100 && grey[0] < 200);
+    }
+
+    #[test]
+    fn generated_palette_has_the_right_hues() {
+        let palette = Palette::generate_ntsc(&NtscSettings::new());
+        let blue = palette.get_color(0x12);
+        assert!(blue[2] > blue[0] && blue[2] > blue[1]);
+        let red = palette.get_color(0x16);
+        assert!(red[0] > red[1] && red[0] > red[2]);
+        let green = palette.get_color(0x1A);
+        assert!(green[1] > green[0] && green[1] > green[2]);
+    }
+
+    #[test]
+    fn generated_palette_follows_its_settings() {
+        let mut settings = NtscSettings::new();
+        settings.saturation = 0.0;
+        let red = Palette::generate_ntsc(&settings).get_color(0x16);
+        assert_eq!(red[0], red[1]);
+        assert_eq!(red[1], red[2]);
+
+        // Turning the hue back a third of the way around makes red into green
+        settings = NtscSettings::new();
+        settings.hue = -120.0;
+        let green = Palette::generate_ntsc(&settings).get_color(0x16);
+        assert!(green[1] > green[0] && green[1] > green[2]);
+
+        settings = NtscSettings::new();
+        settings.contrast = 0.5;
+        assert!(Palette::generate_ntsc(&settings).get_color(0x20)[0] < 255);
+    }
+
+    #[test]
+    fn generated_emphasis_darkens_the_other_colors() {
+        let palette = Palette::generate_ntsc(&NtscSettings::new());
+        let white = palette.get_color(0x20);
+        let red_emphasized = palette.get_color(0b001 << 6 | 0x20);
+        assert!(red_emphasized[1] < white[1] && red_emphasized[2] < white[2]);
+        assert!(red_emphasized[0] > red_emphasized[1]);
+    }
+}
@@ -1,7 +1,7 @@
 use ppu_memory::PPUMemory;
-use game_window::GameWindow;
-use controlletron::Controlletron;
-//use std::time::Instant;
+use framebuffer::Framebuffer;
+use mapper::Cartridge;
+use region::Region;
 
 #[allow(dead_code)]
 pub struct PPU {
@@ -13,25 +13,56 @@ pub struct PPU {
     vram_scroll_register: *const u8, // 0x2005 Probably the low byte for a vram read / write (Or maybe this is purely for scrolling?)
     vram_address_register: *const u8, // 0x2006 Probably the high byte for a vram read / write
     vram_data_register: *mut u8, // 0x2007 Reads or writes a byte from VRAM at the current location
-    scanline_counter: u16, // Tracks which scanline is currently being rendered
-    clock_cycle_counter: u16, // Tracks when to perform the next scanline. Each scanline lasts for 341 PPU clock cycles
+    region: Region,
+    scanline: u16, // 0 - 239 are drawn and the last one is the pre-render scanline. Vblank is somewhere in between, depending on the region
+    vblank_scanline: u16,
+    pre_render_scanline: u16,
+    dot: u16, // Each scanline lasts for 341 PPU clock cycles, or dots. Visible pixels are drawn during dots 1 - 256
     object_attribute_memory: [u8; 0x100], // Stores current sprite data to render. Copied here by the CPU writing to 0x4014
+    secondary_oam: [u8; 0x100], // The sprites found to be on the next scanline. Only 8 fit on the real PPU, the rest are for when the limit is removed
+    secondary_oam_count: usize,
+    overflow_byte_offset: usize, // Which byte of each sprite the buggy sprite overflow check looks at
+    sprite_limit_removed: bool, // Draws every sprite on a scanline, instead of just the first 8. Gets rid of flickering in a lot of games
+    sprite0_in_secondary_oam: bool,
+    scanline_sprites: [ScanlineSprite; 64], // Pattern data for the sprites being drawn on the current scanline
+    scanline_sprite_count: usize,
+    sprite0_on_scanline: bool, // Sprite 0 is always in the first slot when it's there
     high_byte_write: bool, // Used by $2005 and $2006 to control which part of the buffer is written to
     scroll_register_t: u16,
     scroll_register_v: u16,
     scroll_register_x: u8,
+    nametable_latch: u8, // The next background tile's data, fetched over 8 dots before going into the shift registers
+    attribute_latch: u8,
+    pattern_low_latch: u8,
+    pattern_high_latch: u8,
+    pattern_shift_low: u16, // Background pattern bits for the current tile (high byte) and the next one (low byte)
+    pattern_shift_high: u16,
+    attribute_shift_low: u16, // The palette of each of those pixels, spread out so it lines up with the pattern bits
+    attribute_shift_high: u16,
     internal_read_buffer: u8, // Reads by the CPU from $2007 are delayed one read
     memory: PPUMemory,
-    game_window: GameWindow,
-    frame_skip: u8,
-    odd_frame: bool
+    framebuffer: Framebuffer,
+    frame_skip: u8, // How many frames to skip drawing in between each one that's drawn
+    frames_until_drawn: u8,
+    odd_frame: bool,
+    frame_count: u32 // Total number of frames the PPU has finished
 }
 
-const SCREEN_WIDTH: u8 = 255;
-const SCREEN_HEIGHT: u8 = 240;
+// A sprite that has been fetched for drawing on a scanline. The pattern bytes are already flipped horizontally if need be
+#[derive(Clone, Copy)]
+struct ScanlineSprite {
+    pattern_low: u8,
+    pattern_high: u8,
+    attributes: u8,
+    x: u8
+}
+
+const MAX_SPRITES_PER_SCANLINE: usize = 8;
+const VISIBLE_SCANLINES: u16 = 240;
+const DOTS_PER_SCANLINE: u16 = 341;
 
 impl PPU {
-    pub fn new(io_registers: *mut u8) -> PPU {
+    pub fn new(io_registers: *mut u8, region: Region) -> PPU {
         unsafe {
             return PPU {
                 ppu_control_register: io_registers.offset(0),
@@ -42,34 +73,165 @@ impl PPU {
                 vram_scroll_register: io_registers.offset(5),
                 vram_address_register: io_registers.offset(6),
                 vram_data_register: io_registers.offset(7),
-                scanline_counter: 0,
-                clock_cycle_counter: 341,
+                region: region,
+                scanline: 0,
+                vblank_scanline: region.get_vblank_scanline(),
+                pre_render_scanline: region.get_scanlines_per_frame() - 1,
+                dot: 0,
                 object_attribute_memory: [0; 0x100],
+                secondary_oam: [0xFF; 0x100],
+                secondary_oam_count: 0,
+                overflow_byte_offset: 0,
+                sprite_limit_removed: false,
+                sprite0_in_secondary_oam: false,
+                scanline_sprites: [ScanlineSprite { pattern_low: 0, pattern_high: 0, attributes: 0, x: 0 }; 64],
+                scanline_sprite_count: 0,
+                sprite0_on_scanline: false,
                 high_byte_write: true,
                 scroll_register_t: 0,
                 scroll_register_v: 0,
                 scroll_register_x: 0,
+                nametable_latch: 0,
+                attribute_latch: 0,
+                pattern_low_latch: 0,
+                pattern_high_latch: 0,
+                pattern_shift_low: 0,
+                pattern_shift_high: 0,
+                attribute_shift_low: 0,
+                attribute_shift_high: 0,
                 internal_read_buffer: 0,
                 memory: PPUMemory::new(),
-                game_window: GameWindow::new(),
+                framebuffer: Framebuffer::new(),
                 frame_skip: 0,
-                odd_frame: true
+                frames_until_drawn: 0,
+                odd_frame: true,
+                frame_count: 0
             }
         }
     }
 
-    pub fn init_chr_rom(&mut self, chr_rom: Vec<u8>) {
-        self.memory.init_chr_rom(chr_rom);
+    pub fn insert_cartridge(&mut self, cartridge: Cartridge) {
+        self.memory.insert_cartridge(cartridge);
     }
 
+    pub fn get_frame_count(&self) -> u32 {
+        return self.frame_count;
+    }
+
+    // Skipped frames are still emulated, they just aren't drawn. Saves some time on slow machines
+    pub fn set_frame_skip(&mut self, frame_skip: u8) {
+        self.frame_skip = frame_skip;
+    }
+
+    // The sprite overflow flag still works the same way with the limit removed, for games that watch it
+    pub fn set_sprite_limit_removed(&mut self, sprite_limit_removed: bool) {
+        self.sprite_limit_removed = sprite_limit_removed;
+    }
+
+    // The scanline and dot that the next tick runs
+    pub fn get_position(&self) -> (u16, u16) {
+        return (self.scanline, self.dot);
+    }
+
+    #[cfg(any(feature = "sdl", test))]
+    pub fn get_framebuffer(&self) -> &Framebuffer {
+        return &self.framebuffer;
+    }
+
+    // Runs a single dot. The background and sprite pipelines do the same memory accesses, at the same dots, as the
+    // real PPU does. So anything a game changes partway through a scanline shows up right where it would on hardware
     pub fn tick(&mut self) {
-        self.clock_cycle_counter -= 1;
+        let is_visible_scanline = self.scanline < VISIBLE_SCANLINES;
+        let is_pre_render_scanline = self.scanline == self.pre_render_scanline;
+
+        if is_pre_render_scanline && self.dot == 1 {
+            self.set_vblank_status(false);
+            self.set_sprite0_hit(false);
+            self.set_sprite_overflow(false);
+        }
 
-        if self.clock_cycle_counter == 256 && self.is_rendering_enabled() {
+        if (is_visible_scanline || is_pre_render_scanline) && self.is_rendering_enabled() {
+            self.fetch_background(is_pre_render_scanline);
+            self.evaluate_sprites(is_visible_scanline);
+            self.fetch_sprites();
+        }
+
+        if is_visible_scanline && self.dot >= 1 && self.dot <= 256 {
+            self.output_pixel();
+        }
+
+        if self.scanline == self.vblank_scanline && self.dot == 1 {
+            self.start_vblank();
+        }
+
+        self.advance_dot();
+    }
+
+    fn advance_dot(&mut self) {
+        // On NTSC the pre-render scanline varies in length, depending on whether an even or an odd frame is being rendered.
+        // For odd frames, the dot at the end of the scanline is skipped
+        let is_dot_skipped = self.scanline == self.pre_render_scanline && self.dot == DOTS_PER_SCANLINE - 2
+            && self.odd_frame && self.is_rendering_enabled() && self.region.has_short_odd_frames();
+
+        self.dot += 1;
+        if self.dot == DOTS_PER_SCANLINE || is_dot_skipped {
+            self.dot = 0;
+            self.scanline = if self.scanline == self.pre_render_scanline { 0 } else { self.scanline + 1 };
+        }
+    }
+
+    fn start_vblank(&mut self) {
+        if self.frames_until_drawn == 0 {
+            if self.is_rendering_enabled() {
+                self.framebuffer.finish_frame();
+            }
+            self.frames_until_drawn = self.frame_skip;
+        } else {
+            self.frames_until_drawn -= 1;
+        }
+
+        self.set_vblank_status(true);
+        self.odd_frame = !self.odd_frame;
+        self.frame_count += 1;
+    }
+
+    // Every 8 dots the next background tile is fetched: nametable byte, attribute byte, then the two pattern bytes.
+    // The tile is loaded into the low half of the shift registers, which shift one pixel each dot. The first two tiles of a
+    // scanline are fetched at the end of the previous one, during dots 321 - 336
+    fn fetch_background(&mut self, is_pre_render_scanline: bool) {
+        let dot = self.dot;
+        if (dot >= 2 && dot <= 257) || (dot >= 321 && dot <= 337) {
+            self.shift_background_registers();
+
+            match (dot - 1) % 8 {
+                0 => {
+                    self.load_background_shift_registers();
+                    self.nametable_latch = self.memory.get_8_bit_value(0x2000 | (self.scroll_register_v & 0x0FFF));
+                },
+                2 => {
+                    let nametable_address = 0x2000 | (self.scroll_register_v & 0x0C00);
+                    let coarse_x = self.get_coarse_x();
+                    let coarse_y = self.get_coarse_y();
+                    self.attribute_latch = self.get_attribute_value(nametable_address, coarse_x, coarse_y);
+                },
+                4 => {
+                    let address = self.get_background_pattern_address();
+                    self.pattern_low_latch = self.memory.get_8_bit_value(address);
+                },
+                6 => {
+                    let address = self.get_background_pattern_address();
+                    self.pattern_high_latch = self.memory.get_8_bit_value(address + 8);
+                },
+                7 => self.increment_coarse_x(),
+                _ => {}
+            }
+        }
+
+        if dot == 256 {
             self.increment_vertical_scroll_register();
         }
 
-        if self.clock_cycle_counter == 257 && self.is_rendering_enabled() {
+        if dot == 257 {
             self.scroll_register_v &= 0b1111_1011_1110_0000;
             self.scroll_register_v |= self.scroll_register_t & 0b0000_0100_0000_0000;
             self.scroll_register_v |= self.scroll_register_t & 0b0000_0000_0001_1111;
@@ -77,193 +239,278 @@ impl PPU {
 
         // "If rendering is enabled, at the end of vblank, shortly after the horizontal bits are copied from t to v at dot 257,
         // the PPU will repeatedly copy the vertical bits from t to v from dots 280 to 304, completing the full initialization of v from t"
-        if self.scanline_counter == 20 && self.clock_cycle_counter >= 280 && self.clock_cycle_counter <= 304 && self.is_rendering_enabled() {
+        if is_pre_render_scanline && dot >= 280 && dot <= 304 {
             self.scroll_register_v &= 0b0000_0100_0001_1111;
             self.scroll_register_v |= self.scroll_register_t & 0b0111_1000_0000_0000;
             self.scroll_register_v |= self.scroll_register_t & 0b0000_0011_1110_0000;
         }
+    }
 
-        if (self.clock_cycle_counter >= 328 || self.clock_cycle_counter <= 256) && self.clock_cycle_counter % 8 == 0 {
-//            if self.is_rendering_enabled() {
-//                if (self.scroll_register_v & 0b0000_0000_0001_1111) == 0x1F {
-//                    self.scroll_register_v &= 0b1111_1111_1110_0000; // Clear coarse X
-//                    self.scroll_register_v ^= 0b0000_0100_0000_0000; // Switch the horizontal nametable
-//                } else {
-//                    self.scroll_register_v += 1;
-//                }
-//            }
-        }
+    fn get_background_pattern_address(&self) -> u16 {
+        return self.get_background_pattern_table_address() + (self.nametable_latch as u16 * 16) + self.get_fine_y() as u16;
+    }
 
-        if self.clock_cycle_counter > 0 {
-            return
-        } else {
-            // Each scanline lasts 341 (or 340) cycles. This is an imperfect representation, as we render a
-            // scanline all at once, instead of doing it one pixel at a time
-            self.clock_cycle_counter = 341;
-
-            // This scanline varies in length, depending on whether an even or an odd frame is being rendered.
-            // For odd frames, the cycle at the end of the scanline is skipped
-            if self.scanline_counter == 20 && self.odd_frame {
-                self.clock_cycle_counter -= 1;
-            }
-        }
+    fn shift_background_registers(&mut self) {
+        self.pattern_shift_low <<= 1;
+        self.pattern_shift_high <<= 1;
+        self.attribute_shift_low <<= 1;
+        self.attribute_shift_high <<= 1;
+    }
+
+    fn load_background_shift_registers(&mut self) {
+        self.pattern_shift_low = (self.pattern_shift_low & 0xFF00) | self.pattern_low_latch as u16;
+        self.pattern_shift_high = (self.pattern_shift_high & 0xFF00) | self.pattern_high_latch as u16;
+        self.attribute_shift_low = (self.attribute_shift_low & 0xFF00) | if self.attribute_latch & 0b01 != 0 { 0xFF } else { 0 };
+        self.attribute_shift_high = (self.attribute_shift_high & 0xFF00) | if self.attribute_latch & 0b10 != 0 { 0xFF } else { 0 };
+    }
 
-        self.scanline_counter += 1;
+    // While the current scanline is drawn, the PPU looks through OAM for the sprites that fall on the next one.
+    // Secondary OAM is cleared during dots 1 - 64, then each sprite is checked during dots 65 - 256
+    fn evaluate_sprites(&mut self, is_visible_scanline: bool) {
+        if self.dot == 1 {
+            self.secondary_oam_count = 0;
+            self.sprite0_in_secondary_oam = false;
+            self.overflow_byte_offset = 0;
+        }
 
-        if self.scanline_counter == 52 {
-            self.set_sprite0_hit(true);
+        // Each sprite takes 2 dots to check. There are 64 of them, so the search is done by dot 192
+        if !is_visible_scanline || self.dot < 65 || self.dot > 192 || self.dot % 2 == 0 {
+            return;
         }
 
-        if self.scanline_counter < 20 {
-            // We are in VBlank time and likely will do nothing
-        } else if self.scanline_counter == 20 {
-            self.set_vblank_status(false);
+        let sprite_index = ((self.dot - 65) / 2) as usize;
+        let oam_entry_size = 4; // Each sprite takes up 4 bytes
+        let start_address = sprite_index * oam_entry_size;
+        let is_on_scanline = self.is_sprite_on_scanline(self.object_attribute_memory[start_address]);
 
-            /* TODO from http://nesdev.com/2C02%20technical%20reference.TXT
-            After 20 scanlines worth of time go by (since the VINT flag was set), the PPU starts to render scanlines. This first scanline is a dummy one;
-            although it will access it's external memory in the same sequence it would for drawing a valid scanline, no on-screen pixels are rendered during this
-            time, making the fetched background data immaterial. Both horizontal *and* vertical scroll counters are updated (presumably) at cc offset 256 in this
-            scanline. Other than that, the operation of this scanline is identical to any other. The primary reason this scanline exists is to start the object
-            render pipeline, since it takes 256 cc's worth of time to determine which objects are in range or not for any particular scanline.
-            */
-        } else if self.scanline_counter < 261 {
-            if self.frame_skip != 0 {
+        if self.secondary_oam_count >= MAX_SPRITES_PER_SCANLINE {
+            self.check_sprite_overflow(start_address);
+            if !self.sprite_limit_removed {
                 return;
             }
-            // The screen height is 240, one scanline per pixel. Start at 0 and work up to 240 (so we subtract the previous 21 scanlines from this value)
-            let scanline_height = (self.scanline_counter - 21) as u8;
-            self.draw_scanline(scanline_height);
-        } else {
-            if self.is_background_rendered() || self.are_sprites_rendered() {
-//                let start = Instant::now();
-                if self.frame_skip <= 0 {
-                    self.game_window.repaint();
-                    self.frame_skip = 1;
-                } else {
-                    self.frame_skip -= 1;
-                }
+        }
+
+        if is_on_scanline {
+            let secondary_address = self.secondary_oam_count * oam_entry_size;
+            for byte_offset in 0..oam_entry_size {
+                self.secondary_oam[secondary_address + byte_offset] = self.object_attribute_memory[start_address + byte_offset];
             }
+            self.secondary_oam_count += 1;
+            self.sprite0_in_secondary_oam |= sprite_index == 0;
+        }
+    }
 
-            self.set_vblank_status(true);
-            self.scanline_counter = 0;
-            self.set_sprite0_hit(false);
-            self.odd_frame = !self.odd_frame;
+    // Once secondary OAM is full, the PPU keeps looking for a 9th sprite to set the overflow flag. But it's buggy. Every time a
+    // sprite isn't on the scanline, it moves on to the next byte of the next sprite, instead of its Y position. So it ends up
+    // checking tile numbers, attributes and X positions as if they were Y positions, and games can't trust the flag
+    // https://wiki.nesdev.com/w/index.php/PPU_sprite_evaluation#Sprite_overflow_bug
+    fn check_sprite_overflow(&mut self, start_address: usize) {
+        let y_offset = self.object_attribute_memory[start_address + self.overflow_byte_offset];
+        if self.is_sprite_on_scanline(y_offset) {
+            self.set_sprite_overflow(true);
+        } else {
+            self.overflow_byte_offset = (self.overflow_byte_offset + 1) % 4;
         }
     }
 
-    fn draw_scanline(&mut self, line_num: u8) {
-        if self.is_background_rendered() {
-            self.draw_background(line_num);
+    fn is_sprite_on_scanline(&self, y_offset: u8) -> bool {
+        let y_offset = y_offset as u16;
+        return self.scanline >= y_offset && self.scanline - y_offset < self.get_sprite_height();
+    }
+
+    // Dots 257 - 320 fetch the pattern data of the sprites found by evaluation, 8 dots for each of the 8 sprite slots.
+    // Boards like the MMC3 watch address line A12 of these fetches to count scanlines, so unused slots still do a fetch (of tile $FF)
+    fn fetch_sprites(&mut self) {
+        let dot = self.dot;
+        if dot < 257 || dot > 320 {
+            return;
         }
 
-        // TODO only the first 8 sprites should be drawn and a sprite overflow flag should be set
-        // https://wiki.nesdev.com/w/index.php/Sprite_overflow_games
-        if self.are_sprites_rendered() {
-            // The sprite rendering lags behind background rendering by 1 scanline... I think
-            // I don't actually see any changes when I do this vs drawing the same scanline right after
-            if line_num > 0 {
-                self.draw_sprites(line_num - 1);
+        if dot == 257 {
+            self.scanline_sprite_count = self.secondary_oam_count;
+            self.sprite0_on_scanline = self.sprite0_in_secondary_oam;
+        }
+
+        let slot = ((dot - 257) / 8) as usize;
+        match (dot - 257) % 8 {
+            4 => {
+                let address = self.get_sprite_pattern_address(slot);
+                let pattern_low = self.memory.get_8_bit_value(address);
+                if slot < self.scanline_sprite_count {
+                    self.load_scanline_sprite(slot, pattern_low);
+                }
+            },
+            6 => {
+                let address = self.get_sprite_pattern_address(slot);
+                let pattern_high = self.memory.get_8_bit_value(address + 8);
+                if slot < self.scanline_sprite_count {
+                    self.scanline_sprites[slot].pattern_high = self.flip_sprite_pattern(slot, pattern_high);
+                }
+            },
+            _ => {}
+        }
+
+        // The real PPU has nowhere to put more than 8 sprites. With the sprite limit removed we draw them anyway, so grab the rest once the 8 slots are done
+        if dot == 320 {
+            for slot in MAX_SPRITES_PER_SCANLINE..self.scanline_sprite_count {
+                let address = self.get_sprite_pattern_address(slot);
+                let pattern_low = self.memory.get_8_bit_value(address);
+                let pattern_high = self.memory.get_8_bit_value(address + 8);
+                self.load_scanline_sprite(slot, pattern_low);
+                self.scanline_sprites[slot].pattern_high = self.flip_sprite_pattern(slot, pattern_high);
             }
         }
     }
 
-    // TODO sprite iteration could be a little faster by caching all drawable sprites rather than iterating all 64 spots each scanline
-    fn draw_sprites(&mut self, line_num: u8) {
-        if self.using_16px_height_sprites() {
-            panic!("16px sprites are not yet supported!");
-        }
+    fn load_scanline_sprite(&mut self, slot: usize, pattern_low: u8) {
+        let start_address = slot * 4;
+        self.scanline_sprites[slot].attributes = self.secondary_oam[start_address + 2];
+        self.scanline_sprites[slot].x = self.secondary_oam[start_address + 3];
+        self.scanline_sprites[slot].pattern_low = self.flip_sprite_pattern(slot, pattern_low);
+    }
 
-        let num_sprites = 64; // Maximum number of sprites an NES game can hold in sprite memory
-        let oam_entry_size = 4; // Each sprite takes up 4 bytes
+    fn flip_sprite_pattern(&self, slot: usize, pattern: u8) -> u8 {
+        let flip_x = (self.scanline_sprites[slot].attributes & 0b0100_0000) != 0;
+        return if flip_x { pattern.reverse_bits() } else { pattern };
+    }
 
-        // Now iterate through all sprites backwards.
-        // Backwards because earlier sprites have priority, and need to overwrite later sprites
-        for offset in (0..num_sprites).rev() {
-            let start_address = offset * oam_entry_size;
+    fn get_sprite_pattern_address(&self, slot: usize) -> u16 {
+        // Unused sprite slots fetch tile $FF
+        if slot >= self.scanline_sprite_count {
+            return self.get_sprite_row_address(0xFF, 0);
+        }
 
-            // For whatever reason the NES renders sprites one pixel lower than they say they are. So add 1 to the y_offset here
-            let y_offset = self.object_attribute_memory[start_address].wrapping_add(1);
+        let start_address = slot * 4;
+        let y_offset = self.secondary_oam[start_address] as u16;
+        let pattern_num = self.secondary_oam[start_address + 1];
+        let flip_y = (self.secondary_oam[start_address + 2] & 0b1000_0000) != 0;
 
-            // Check if the sprite will be rendered by the current scan line's line height
-            if y_offset > line_num || (line_num > 6 && y_offset < line_num - 7) {
-                continue;
-            }
+        // Sprites are evaluated a scanline ahead of when they're drawn, which is why the NES renders sprites one pixel
+        // lower than their Y position says they are
+        let sprite_height = self.get_sprite_height();
+        let sprite_row = self.scanline.wrapping_sub(y_offset) & (sprite_height - 1);
+        let sprite_row = if flip_y { sprite_height - 1 - sprite_row } else { sprite_row };
 
-            let x_offset = self.object_attribute_memory[start_address + 3];
-            let sprite_flags = self.object_attribute_memory[start_address + 2];
+        return self.get_sprite_row_address(pattern_num, sprite_row);
+    }
 
-            let flip_x = (sprite_flags & 0b0100_0000) != 0;
-            let flip_y = (sprite_flags & 0b1000_0000) != 0;
-            let force_draw = (sprite_flags & 0b0010_0000) == 0;
-            let palette_selection = sprite_flags & 0b0000_0011;
+    // 8x16 sprites are two tiles stacked on top of each other. Bit 0 of the tile number picks their pattern table,
+    // instead of $2000 doing it, and the rest of the tile number picks the top tile. The bottom one is the tile after it
+    fn get_sprite_row_address(&self, pattern_num: u8, sprite_row: u16) -> u16 {
+        if self.using_16px_height_sprites() {
+            let pattern_table = if pattern_num & 1 != 0 { 0x1000 } else { 0x0 };
+            let tile = (pattern_num & 0xFE) as u16 + sprite_row / 8;
+            return pattern_table + tile * 16 + sprite_row % 8;
+        }
 
-            let pattern_num = self.object_attribute_memory[start_address + 1];
-            let pattern = self.get_pattern(pattern_num, true, flip_x, flip_y);
+        return self.get_sprite_pattern_table_address() + pattern_num as u16 * 16 + sprite_row;
+    }
 
-            // Check for sprite 0 hit
-            if offset == 0 && !self.is_sprite0_hit() {
-//                self.check_sprite0_hit(pattern, x_offset, y_offset, line_num);
+    fn output_pixel(&mut self) {
+        let x = (self.dot - 1) as u8;
+        let mut palette_address = 0x3F00;
+        if self.is_rendering_enabled() {
+            let (background_pixel, background_palette) = self.get_background_pixel(x);
+            if !self.is_sprite0_hit() {
+                self.check_sprite0_hit(x, background_pixel);
             }
+            palette_address = self.get_pixel_palette_address(x, background_pixel, background_palette);
+        }
 
-            self.send_pattern_to_window(pattern, x_offset as i16, y_offset, line_num, true, force_draw, palette_selection);
+        if self.frames_until_drawn == 0 {
+            let pixel = self.apply_color_effects(self.memory.get_8_bit_value(palette_address));
+            self.framebuffer.set_pixel(x, self.scanline as u8, pixel);
         }
     }
 
-    fn draw_background(&mut self, line_num: u8) {
-        let tiles_per_row: u8 = 32;
+    // Greyscale mode only keeps the brightness of a color, which is its upper 2 bits. The color emphasis bits are passed
+    // along on top of the color, for the palette to sort out what they look like
+    fn apply_color_effects(&self, color_value: u8) -> u16 {
+        unsafe {
+            let mask = *self.ppu_mask_register;
+            let is_greyscale = (mask & 0b0000_0001) != 0;
+            let color_value = if is_greyscale { color_value & 0x30 } else { color_value & 0x3F };
+            let emphasis = (mask >> 5) as u16;
 
-        for tile_x in self.get_coarse_x()..self.get_coarse_x() + tiles_per_row + 1 {
-            // This rendering strategy is definitely not standard- always rendering both name tables all the time
-            // What should happen (I think) is the nametable being rendered should dynamically switch based off scroll_register_v
-            // However, incrementing this register in the right way when rendering the entire scanline at once is proving challenging
-            let start_address = if tile_x < tiles_per_row { self.get_nametable1_address() } else { self.get_nametable2_address() };
-            let tile_y: u8 = (line_num / 8) as u8;
+            return (emphasis << 6) | color_value as u16;
+        }
+    }
 
-            let pattern_address: u16 = start_address + (tile_x % tiles_per_row) as u16 + (tile_y as u16 * tiles_per_row as u16);
+    // Picks between the background and sprite pixels at this dot. Anything transparent shows the backdrop color at 0x3F00
+    fn get_pixel_palette_address(&self, x: u8, background_pixel: u8, background_palette: u8) -> u16 {
+        return match self.get_sprite_pixel(x) {
+            Some((sprite_pixel, sprite_palette, is_behind_background)) => {
+                if background_pixel != 0 && is_behind_background {
+                    self.get_palette_address(background_palette, false) + background_pixel as u16
+                } else {
+                    self.get_palette_address(sprite_palette, true) + sprite_pixel as u16
+                }
+            },
+            None if background_pixel != 0 => self.get_palette_address(background_palette, false) + background_pixel as u16,
+            None => 0x3F00
+        };
+    }
 
-            let pattern_num = self.memory.get_8_bit_value(pattern_address);
-            let pattern = self.get_pattern(pattern_num, false, false, false);
-            let start_x: i16 = (tile_x as i16 * 8) - (self.get_coarse_x() * 8 + self.get_fine_x()) as i16;
+    // Returns the color (0 - 3) and palette (0 - 3) of the background at this dot. Fine X scroll picks which bit of the shift registers is drawn
+    fn get_background_pixel(&self, x: u8) -> (u8, u8) {
+        if !self.is_background_rendered() || (x < 8 && !self.is_background_to_left_edge()) {
+            return (0, 0);
+        }
 
-            let palette_selection = self.get_attribute_value(start_address, (tile_x % tiles_per_row) as u8, tile_y);
+        let bit = 15 - self.get_fine_x();
+        let pixel = (((self.pattern_shift_high >> bit) & 1) << 1) | ((self.pattern_shift_low >> bit) & 1);
+        let palette = (((self.attribute_shift_high >> bit) & 1) << 1) | ((self.attribute_shift_low >> bit) & 1);
 
-            self.send_pattern_to_window(pattern, start_x, tile_y * 8, line_num, false, false, palette_selection);
-        }
+        return (pixel as u8, palette as u8);
     }
 
-    fn send_pattern_to_window(&mut self, pattern: [[u8; 8]; 8], start_x: i16, tile_y: u8, line_num: u8,
-                              is_sprite_pattern: bool, force_draw: bool, palette_selection: u8) {
-        let pattern_y: usize = (line_num - tile_y) as usize; // We only draw one scanline of the pattern. Figure out which index into the pattern that is
-        for pattern_x in 0..pattern.len() {
-            let drawn_x = start_x + pattern_x as i16; // Do math as an i16 to detect drawing out of bounds
-            if drawn_x > SCREEN_WIDTH as i16 || drawn_x < 0 || line_num >= SCREEN_HEIGHT || line_num < 0 {
-                continue;
-            }
+    // Returns the color, palette, and priority of the first non-transparent sprite at this dot, if there is one.
+    // Earlier sprites always win, even when they're behind the background and a later sprite isn't
+    fn get_sprite_pixel(&self, x: u8) -> Option<(u8, u8, bool)> {
+        if !self.are_sprites_rendered() || (x < 8 && !self.are_sprites_to_left_edge()) {
+            return None;
+        }
 
-            // Don't draw a transparent pixel for sprites
-            if pattern[pattern_x][pattern_y] == 0 && is_sprite_pattern {
-                continue;
+        for slot in 0..self.scanline_sprite_count {
+            let pixel = self.get_scanline_sprite_pixel(slot, x);
+            if pixel != 0 {
+                let attributes = self.scanline_sprites[slot].attributes;
+                let palette = attributes & 0b0000_0011;
+                let is_behind_background = (attributes & 0b0010_0000) != 0;
+                return Some((pixel, palette, is_behind_background));
             }
+        }
 
-            // Now draw our pixel, if the background doesn't have higher priority than us
-            if force_draw || self.is_pixel_transparent(drawn_x as u8, line_num as u8) {
-                let color_offset = pattern[pattern_x][pattern_y];
-                let palette_address = self.get_palette_address(palette_selection, is_sprite_pattern);
-                let color_value = self.memory.get_8_bit_value(palette_address + color_offset as u16);
+        return None;
+    }
 
-                self.game_window.set_pixel_color(color_value, drawn_x as u8, line_num as u8);
-            }
+    // The color (0 - 3) of a single sprite at this dot. 0 if it's transparent, or not at this dot at all
+    fn get_scanline_sprite_pixel(&self, slot: usize, x: u8) -> u8 {
+        let sprite = &self.scanline_sprites[slot];
+        if x < sprite.x || x - sprite.x >= 8 {
+            return 0;
         }
-    }
 
-    fn get_nametable1_address(&self) -> u16 {
-        let nametable_select = (self.scroll_register_v & 0b0000_1100_0000_0000) >> 10;
-        return 0x2000 + (0x400 * nametable_select as u16);
+        let bit = 7 - (x - sprite.x);
+        return (((sprite.pattern_high >> bit) & 1) << 1) | ((sprite.pattern_low >> bit) & 1);
     }
 
-    fn get_nametable2_address(&self) -> u16 {
-        let nametable_select = (self.scroll_register_v & 0b0000_1100_0000_0000) >> 10;
-        return 0x2000 + (0x400 * (nametable_select ^ 1) as u16);
+    // Sprite 0 hit happens when an opaque pixel of sprite 0 is drawn over an opaque background pixel, no matter which of
+    // the two ends up on screen. Games use it to time changes partway down the screen, like the status bar in Super Mario Bros
+    fn check_sprite0_hit(&mut self, x: u8, background_pixel: u8) {
+        if !self.sprite0_on_scanline || !self.are_sprites_rendered() || background_pixel == 0 {
+            return;
+        }
+
+        // The hit can't happen in the leftmost 8 pixels if either of them is being clipped there. And for whatever reason, never on the last pixel
+        let is_left_edge_clipped = !self.is_background_to_left_edge() || !self.are_sprites_to_left_edge();
+        if (x < 8 && is_left_edge_clipped) || x == 255 {
+            return;
+        }
+
+        if self.get_scanline_sprite_pixel(0, x) != 0 {
+            self.set_sprite0_hit(true);
+        }
     }
 
     fn get_palette_address(&self, palette_index: u8, is_sprite_pattern: bool) -> u16 {
@@ -306,38 +553,6 @@ impl PPU {
         }
     }
 
-    // An 8x8 sprite is composed of 8x16 bits. The first 8x8 set is added to the second 8x8 set to get 1 of 4 possible
-    // values (0 - 3), each corresponding to a particular color. Though somewhat wasteful on memory, this is represented as
-    // an 8x8 array of u8 to make the calling code simpler (no need for calling code to mask bits)
-    fn get_pattern(&self, pattern_num: u8, is_sprite_pattern: bool, flip_x: bool, flip_y: bool) -> [[u8; 8]; 8] {
-        let start_address = if is_sprite_pattern { self.get_sprite_pattern_table_address() } else { self.get_background_pattern_table_address() };
-        let sprite_size: u8 = 8; // This might not be the same for every game. There is a flag to determine this I think
-
-        let mut sprite = [[0u8; 8]; 8];
-        for byte_offset in 0..sprite_size {
-            // Each line of the sprite is composed of 2 different bytes. The second byte is offset 8 further down than the first. Grab these bytes
-            let pattern_address = start_address + byte_offset as u16 + (pattern_num as u16 * sprite_size as u16 * 2);
-//            println!("Pattern address: {:X}", pattern_address);
-            let low_byte = self.memory.get_8_bit_value(pattern_address);
-            let high_byte = self.memory.get_8_bit_value(pattern_address + 8);
-            for bit_offset in 0..8 {
-                // Now that we have our high and low bytes, combine them into a single sprite row so we can determine color
-                let mask = 1 << bit_offset;
-                let low_bit = if low_byte & mask != 0 { 1 } else { 0 };
-                let high_bit = if high_byte & mask != 0 { 2 } else { 0 };
-
-                // Though it doesn't make a lot of sense to me why, perhaps a bug somewhere else in the emulator,
-                // we now default the sprites to horizontally flip so that they look normal
-                let x = if flip_x { bit_offset } else { 7 - bit_offset };
-                let y = if flip_y { 7 - byte_offset } else { byte_offset };
-
-                sprite[x as usize][y as usize] = low_bit + high_bit;
-            }
-        }
-
-        return sprite;
-    }
-
     fn get_sprite_pattern_table_address(&self) -> u16 {
         unsafe {
             let bit_set: bool = (*(self.ppu_control_register) & 0b0000_1000) != 0;
@@ -361,26 +576,17 @@ impl PPU {
         }
     }
 
-    fn is_pixel_transparent(&self, drawn_x: u8, drawn_y: u8) -> bool {
-        let color = self.game_window.get_pixel_value(drawn_x, drawn_y);
-
-        if color == 0 { // Nothing has been drawn, so it is transparent
-            return true;
-        }
-        let transparent_color = self.memory.get_8_bit_value(0x3F00);
-
-        // It's transparent because it's using the backdrop color
-        return color == transparent_color;
-    }
-
     fn using_16px_height_sprites(&self) -> bool {
         unsafe {
             return (*(self.ppu_control_register) & 0b0010_0000) != 0;
         }
     }
 
+    fn get_sprite_height(&self) -> u16 {
+        return if self.using_16px_height_sprites() { 16 } else { 8 };
+    }
+
     // If this is false, the background shouldn't be rendered on the leftmost 8 pixels
-    #[allow(dead_code)]
     fn is_background_to_left_edge(&self) -> bool {
         unsafe {
             return (*(self.ppu_mask_register) & 0b0000_0010) != 0;
@@ -388,7 +594,6 @@ impl PPU {
     }
 
     // If this is false, the sprites shouldn't be rendered on the leftmost 8 pixels
-    #[allow(dead_code)]
     fn are_sprites_to_left_edge(&self) -> bool {
         unsafe {
             return (*(self.ppu_mask_register) & 0b0000_0100) != 0;
@@ -399,6 +604,12 @@ impl PPU {
         return self.is_background_rendered() || self.are_sprites_rendered();
     }
 
+    // Rendering only uses the scroll registers on the visible and pre-render scanlines. During vblank the CPU has them to itself
+    fn is_rendering_in_progress(&self) -> bool {
+        let is_rendered_scanline = self.scanline < VISIBLE_SCANLINES || self.scanline == self.pre_render_scanline;
+        return is_rendered_scanline && self.is_rendering_enabled();
+    }
+
     fn is_background_rendered(&self) -> bool {
         unsafe {
             return (*(self.ppu_mask_register) & 0b0000_1000) != 0;
@@ -422,6 +633,26 @@ impl PPU {
         self.scroll_register_v += self.get_address_increment_amount() as u16;
     }
 
+    // Outside of rendering, $2007 accesses step through VRAM by 1 or 32. While rendering is going on, v is busy being the
+    // scroll position, and an access bumps it through both of the increments rendering uses instead
+    fn increment_address_after_data_access(&mut self) {
+        if self.is_rendering_in_progress() {
+            self.increment_coarse_x();
+            self.increment_vertical_scroll_register();
+        } else {
+            self.increment_horizontal_scroll_register();
+        }
+    }
+
+    fn increment_coarse_x(&mut self) {
+        if (self.scroll_register_v & 0b0000_0000_0001_1111) == 0x1F {
+            self.scroll_register_v &= 0b1111_1111_1110_0000; // Clear coarse X
+            self.scroll_register_v ^= 0b0000_0100_0000_0000; // Switch the horizontal nametable
+        } else {
+            self.scroll_register_v += 1;
+        }
+    }
+
     fn increment_vertical_scroll_register(&mut self) {
         if (self.scroll_register_v & 0x7000) != 0x7000 {
             self.scroll_register_v += 0x1000;
@@ -442,9 +673,7 @@ impl PPU {
 
     // This happens on reading 0x2007
     pub fn read_from_ppu_data(&mut self) -> u8 {
-        if !self.is_rendering_enabled() {
-            self.increment_horizontal_scroll_register();
-        }
+        self.increment_address_after_data_access();
 
 //        return self.memory.get_8_bit_value(self.scroll_register_v);
         let address = self.scroll_register_v;
@@ -504,10 +733,7 @@ impl PPU {
             self.high_byte_write = !self.high_byte_write;
         } else if address == 0x2007 {
             self.memory.set_8_bit_value(self.scroll_register_v, value);
-
-            if !self.is_rendering_enabled() {
-                self.increment_horizontal_scroll_register();
-            }
+            self.increment_address_after_data_access();
         }
     }
 
@@ -516,10 +742,6 @@ impl PPU {
         self.object_attribute_memory[index as usize] = sprite_data;
     }
 
-    pub fn get_controlletron(&mut self) -> *mut Controlletron {
-        return &mut self.game_window.controlletron;
-    }
-
     fn set_vblank_status(&mut self, is_set: bool) {
         unsafe {
             if is_set {
@@ -530,28 +752,6 @@ impl PPU {
         }
     }
 
-    // Iterate over the pixels of sprite0 on the current scanline (line_num) and check if both the sprite
-    // and the background both have non-transparent pixel values. If they do, set sprite0 as being hit
-    fn check_sprite0_hit(&mut self, sprite0: [[u8; 8]; 8], x_offset: u8, y_offset: u8, line_num: u8) {
-        let sprite_line = y_offset - line_num;
-//        println!("{:X} {:X} {:X}", sprite_line, y_offset, line_num);
-        for x in 0..sprite0.len() {
-            // TODO this background_pixel_exists bool should be negated for the logic to be sound... but sprite0 is never triggered when it is
-            let background_pixel_exists = self.is_pixel_transparent(x_offset + x as u8, line_num);
-            let sprite_pixel_exists = sprite0[x][sprite_line as usize] != 0;
-//            println!("{} {}", background_pixel_exists, sprite_pixel_exists);
-            if sprite_pixel_exists && background_pixel_exists {
-//                println!("Hit!");
-//                println!("-----------");
-//                self.debug_draw_sprite(sprite0);
-//                println!("--");
-//                self.debug_background(x_offset, y_offset);
-                self.set_sprite0_hit(true);
-                return;
-            }
-        }
-    }
-
     fn is_sprite0_hit(&self) -> bool {
         unsafe {
             return (*self.ppu_status_register & 0b0100_0000) != 0;
@@ -568,11 +768,20 @@ impl PPU {
         }
     }
 
+    fn set_sprite_overflow(&mut self, is_set: bool) {
+        unsafe {
+            if is_set {
+                *self.ppu_status_register |= 0b0010_0000;
+            } else {
+                *self.ppu_status_register &= !0b0010_0000;
+            }
+        }
+    }
+
     fn get_coarse_x(&self) -> u8 {
         return (self.scroll_register_v & 0b0000_0000_0001_1111) as u8;
     }
 
-    #[allow(dead_code)]
     fn get_coarse_y(&self) -> u8 {
         return ((self.scroll_register_v & 0b0000_0011_1110_0000) >> 5) as u8;
     }
@@ -581,7 +790,6 @@ impl PPU {
         return self.scroll_register_x;
     }
 
-    #[allow(dead_code)]
     fn get_fine_y(&self) -> u8 {
         return ((self.scroll_register_v & 0b0111_0000_0000_0000) >> 12) as u8;
     }
@@ -590,11 +798,61 @@ impl PPU {
 #[cfg(test)]
 mod tests {
     use ppu::PPU;
+    use framebuffer::SCREEN_WIDTH;
+    use mapper::Mirroring;
+    use mapper::Nrom;
+    use region::Region;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     fn create_test_ppu() -> PPU {
-        let mut memory: Vec<u8> = vec!(0x12, 0x23, 0x31, 0x48, 0x51, 0x70, 0xAB, 0xE2);
+        // The registers have to outlive the PPU that points at them. Leaking a few bytes in a test is fine
+        let memory: &'static mut Vec<u8> = Box::leak(Box::new(vec!(0x12, 0x23, 0x31, 0x48, 0x51, 0x70, 0xAB, 0xE2)));
         let first_address: *mut u8 = memory.first_mut().unwrap();
-        return PPU::new(first_address);
+        return PPU::new(first_address, Region::Ntsc);
+    }
+
+    // Tile 1 is solid color 1. Tile 2 only has its leftmost column drawn, and tile 3 only its top row.
+    // In the second pattern table, only tile 2 is drawn and it's solid
+    fn create_rendering_ppu(control: u8, mask: u8) -> PPU {
+        let mut ppu = create_test_ppu();
+        unsafe {
+            *(ppu.ppu_control_register as *mut u8) = control;
+            *(ppu.ppu_mask_register as *mut u8) = mask;
+            *ppu.ppu_status_register = 0;
+        }
+
+        let mut chr = vec![0; 0x2000];
+        for row in 0..8 {
+            chr[0x10 + row] = 0xFF;
+            chr[0x20 + row] = 0x80;
+            chr[0x1020 + row] = 0xFF;
+        }
+        chr[0x30] = 0xFF;
+        ppu.insert_cartridge(Rc::new(RefCell::new(Nrom::new(vec![0; 0x8000], chr, 0, Mirroring::Horizontal))));
+
+        ppu.memory.set_8_bit_value(0x3F00, 0x0F);
+        ppu.memory.set_8_bit_value(0x3F01, 0x21);
+        ppu.memory.set_8_bit_value(0x3F11, 0x16);
+        return ppu;
+    }
+
+    fn run_until(ppu: &mut PPU, scanline: u16, dot: u16) {
+        while ppu.scanline != scanline || ppu.dot != dot {
+            ppu.tick();
+        }
+    }
+
+    fn run_frame(ppu: &mut PPU) -> Vec<u16> {
+        let frame_count = ppu.get_frame_count();
+        while ppu.get_frame_count() == frame_count {
+            ppu.tick();
+        }
+        return ppu.get_framebuffer().get_finished_frame().to_vec();
+    }
+
+    fn get_pixel(frame: &[u16], x: usize, y: usize) -> u16 {
+        return frame[y * SCREEN_WIDTH + x];
     }
 
     #[test]
@@ -603,7 +861,7 @@ mod tests {
 
         unsafe {
             let first_address: *mut u8 = memory.first_mut().unwrap();
-            let ppu = PPU::new(first_address.offset(1));
+            let ppu = PPU::new(first_address.offset(1), Region::Ntsc);
             assert_eq!(*ppu.ppu_control_register, 0x12);
             assert_eq!(*ppu.ppu_mask_register, 0x23);
             assert_eq!(*ppu.ppu_status_register, 0x31);
@@ -619,7 +877,7 @@ mod tests {
     fn ppu_memory_is_shared() {
         let mut memory: Vec<u8> = vec!(0x12, 0x23, 0x31, 0x48, 0x51, 0x70, 0xAB, 0xE2);
         let first_address: *mut u8 = memory.first_mut().unwrap();
-        let ppu = PPU::new(first_address);
+        let ppu = PPU::new(first_address, Region::Ntsc);
 
         unsafe {
             assert_eq!(*ppu.ppu_control_register, 0x12);
@@ -648,11 +906,374 @@ mod tests {
     fn test_get_attribute_value() {
         let mut ppu: PPU = create_test_ppu();
 
+        ppu.scroll_register_v = 0;
+        ppu.memory.set_8_bit_value(0x23D2, 0b0110_1100);
+        let attribute_value = ppu.get_attribute_value(0x2000, 10, 8);
+        assert_eq!(attribute_value, 0x3);
+    }
+
+    #[test]
+    fn background_tiles_are_drawn_from_the_nametable() {
+        let mut ppu = create_rendering_ppu(0, 0x0A);
+        ppu.memory.set_8_bit_value(0x2001, 1);
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 7, 0), 0x0F);
+        assert_eq!(get_pixel(&frame, 8, 0), 0x21);
+        assert_eq!(get_pixel(&frame, 15, 7), 0x21);
+        assert_eq!(get_pixel(&frame, 16, 0), 0x0F);
+        assert_eq!(get_pixel(&frame, 8, 8), 0x0F);
+    }
+
+    #[test]
+    fn fine_x_scroll_shifts_the_background() {
+        let mut ppu = create_rendering_ppu(0, 0x0A);
+        ppu.memory.set_8_bit_value(0x2001, 1);
+        ppu.write_to_register(0x2005, 3);
+        ppu.write_to_register(0x2005, 0);
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 4, 0), 0x0F);
+        assert_eq!(get_pixel(&frame, 5, 0), 0x21);
+        assert_eq!(get_pixel(&frame, 12, 0), 0x21);
+        assert_eq!(get_pixel(&frame, 13, 0), 0x0F);
+    }
+
+    #[test]
+    fn scrolling_partway_down_the_screen_takes_effect_on_the_next_scanline() {
+        let mut ppu = create_rendering_ppu(0, 0x0A);
+        for tile_y in 0..30 {
+            ppu.memory.set_8_bit_value(0x2001 + tile_y * 32, 1);
+        }
+        run_frame(&mut ppu);
+
+        // Like a status bar, change the horizontal scroll in the middle of a scanline
+        run_until(&mut ppu, 100, 200);
+        ppu.write_to_register(0x2005, 8);
+        ppu.write_to_register(0x2005, 0);
+
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 0, 100), 0x0F);
+        assert_eq!(get_pixel(&frame, 8, 100), 0x21);
+        assert_eq!(get_pixel(&frame, 0, 101), 0x21);
+        assert_eq!(get_pixel(&frame, 8, 101), 0x0F);
+    }
+
+    #[test]
+    fn sprites_are_drawn_one_scanline_below_their_y_position() {
+        let mut ppu = create_rendering_ppu(0, 0x14);
+        ppu.receive_dma(0, 20);
+        ppu.receive_dma(1, 1);
+        ppu.receive_dma(2, 0);
+        ppu.receive_dma(3, 30);
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 30, 20), 0x0F);
+        assert_eq!(get_pixel(&frame, 30, 21), 0x16);
+        assert_eq!(get_pixel(&frame, 37, 28), 0x16);
+        assert_eq!(get_pixel(&frame, 38, 21), 0x0F);
+        assert_eq!(get_pixel(&frame, 30, 29), 0x0F);
+    }
+
+    #[test]
+    fn sprites_can_be_flipped() {
+        let mut ppu = create_rendering_ppu(0, 0x14);
+        let sprites = [20, 2, 0b0100_0000, 30, 40, 3, 0b1000_0000, 30];
+        for (index, value) in sprites.iter().enumerate() {
+            ppu.receive_dma(index as u8, *value);
+        }
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 30, 21), 0x0F);
+        assert_eq!(get_pixel(&frame, 37, 21), 0x16);
+        assert_eq!(get_pixel(&frame, 30, 41), 0x0F);
+        assert_eq!(get_pixel(&frame, 30, 48), 0x16);
+    }
+
+    #[test]
+    fn sprites_behind_the_background_only_show_through_its_transparent_pixels() {
+        let mut ppu = create_rendering_ppu(0, 0x1E);
+        ppu.memory.set_8_bit_value(0x2001, 1);
+        let sprite = [0, 1, 0b0010_0000, 4];
+        for (index, value) in sprite.iter().enumerate() {
+            ppu.receive_dma(index as u8, *value);
+        }
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 5, 1), 0x16);
+        assert_eq!(get_pixel(&frame, 9, 1), 0x21);
+    }
+
+    fn is_sprite0_hit_set(ppu: &PPU) -> bool {
+        unsafe {
+            return (*ppu.ppu_status_register & 0b0100_0000) != 0;
+        }
+    }
+
+    // A solid sprite 0 at the given position, over a column of solid background tiles starting at the given X
+    fn create_sprite0_test_ppu(mask: u8, sprite_x: u8, sprite_y: u8, background_tile_x: u16) -> PPU {
+        let mut ppu = create_rendering_ppu(0, mask);
+        for tile_y in 0..30 {
+            ppu.memory.set_8_bit_value(0x2000 + background_tile_x + tile_y * 32, 1);
+        }
+        let sprite = [sprite_y, 1, 0, sprite_x];
+        for (index, value) in sprite.iter().enumerate() {
+            ppu.receive_dma(index as u8, *value);
+        }
+        run_frame(&mut ppu);
+        return ppu;
+    }
+
+    #[test]
+    fn sprite0_hit_is_set_on_the_first_overlapping_pixel() {
+        let mut ppu = create_sprite0_test_ppu(0x1E, 44, 30, 5);
+
+        // Sprite 0 is drawn on scanline 31. The background starts at x = 40 but the sprite doesn't start until 44, which is drawn on dot 45
+        run_until(&mut ppu, 31, 45);
+        assert!(!is_sprite0_hit_set(&ppu));
+        ppu.tick();
+        assert!(is_sprite0_hit_set(&ppu));
+
+        // It stays set until the end of vblank
+        run_until(&mut ppu, 261, 1);
+        assert!(is_sprite0_hit_set(&ppu));
+        ppu.tick();
+        assert!(!is_sprite0_hit_set(&ppu));
+    }
+
+    #[test]
+    fn sprite0_hit_needs_an_opaque_background_pixel() {
+        let mut ppu = create_sprite0_test_ppu(0x1E, 60, 30, 5);
+        run_frame(&mut ppu);
+        assert!(!is_sprite0_hit_set(&ppu));
+    }
+
+    #[test]
+    fn sprite0_hit_happens_behind_the_background() {
+        let mut ppu = create_sprite0_test_ppu(0x1E, 44, 30, 5);
+        ppu.receive_dma(2, 0b0010_0000);
+        run_frame(&mut ppu);
+        assert!(is_sprite0_hit_set(&ppu));
+    }
+
+    #[test]
+    fn sprite0_hit_does_not_happen_in_the_clipped_left_edge() {
+        let mut ppu = create_sprite0_test_ppu(0x18, 0, 30, 0);
+        run_frame(&mut ppu);
+        assert!(!is_sprite0_hit_set(&ppu));
+
+        let mut ppu = create_sprite0_test_ppu(0x1E, 0, 30, 0);
+        run_frame(&mut ppu);
+        assert!(is_sprite0_hit_set(&ppu));
+    }
+
+    #[test]
+    fn sprite0_hit_does_not_happen_on_the_last_pixel() {
+        let mut ppu = create_sprite0_test_ppu(0x1E, 255, 30, 31);
+        run_frame(&mut ppu);
+        assert!(!is_sprite0_hit_set(&ppu));
+    }
+
+    #[test]
+    fn tall_sprites_are_two_tiles_stacked() {
+        let mut ppu = create_rendering_ppu(0b0010_0000, 0x14);
+        let sprites = [20, 2, 0, 30, 20, 3, 0, 60];
+        for (index, value) in sprites.iter().enumerate() {
+            ppu.receive_dma(index as u8, *value);
+        }
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 30, 28), 0x16);
+        assert_eq!(get_pixel(&frame, 31, 28), 0x0F);
+        assert_eq!(get_pixel(&frame, 31, 29), 0x16);
+        assert_eq!(get_pixel(&frame, 30, 30), 0x0F);
+
+        // An odd tile number takes both tiles from the second pattern table
+        assert_eq!(get_pixel(&frame, 60, 21), 0x16);
+        assert_eq!(get_pixel(&frame, 67, 28), 0x16);
+        assert_eq!(get_pixel(&frame, 60, 29), 0x0F);
+    }
+
+    #[test]
+    fn flipping_a_tall_sprite_vertically_swaps_its_tiles() {
+        let mut ppu = create_rendering_ppu(0b0010_0000, 0x14);
+        let sprite = [20, 2, 0b1000_0000, 30];
+        for (index, value) in sprite.iter().enumerate() {
+            ppu.receive_dma(index as u8, *value);
+        }
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 31, 28), 0x16);
+        assert_eq!(get_pixel(&frame, 31, 27), 0x0F);
+        assert_eq!(get_pixel(&frame, 30, 29), 0x16);
+        assert_eq!(get_pixel(&frame, 30, 36), 0x16);
+        assert_eq!(get_pixel(&frame, 31, 36), 0x0F);
+    }
+
+    #[test]
+    fn sprite0_hit_includes_the_bottom_half_of_tall_sprites() {
+        let mut ppu = create_sprite0_test_ppu(0x1E, 40, 30, 5);
+        unsafe {
+            *(ppu.ppu_control_register as *mut u8) = 0b0010_0000;
+        }
+        ppu.receive_dma(1, 0); // Tile 0 is empty, tile 1 below it is solid
+
+        run_frame(&mut ppu);
+        run_until(&mut ppu, 39, 41);
+        assert!(!is_sprite0_hit_set(&ppu));
+        ppu.tick();
+        assert!(is_sprite0_hit_set(&ppu));
+    }
+
+    fn is_sprite_overflow_set(ppu: &PPU) -> bool {
+        unsafe {
+            return (*ppu.ppu_status_register & 0b0010_0000) != 0;
+        }
+    }
+
+    // Every sprite starts off the screen. Then lines up the given sprites side by side, 10 pixels apart
+    fn load_sprite_row(ppu: &mut PPU, sprites: &[(u8, u8)]) {
+        for index in 0..0x100 {
+            ppu.receive_dma(index as u8, 0xFF);
+        }
+        for (sprite_num, &(y_offset, pattern_num)) in sprites.iter().enumerate() {
+            let start_address = sprite_num as u8 * 4;
+            ppu.receive_dma(start_address, y_offset);
+            ppu.receive_dma(start_address + 1, pattern_num);
+            ppu.receive_dma(start_address + 2, 0);
+            ppu.receive_dma(start_address + 3, sprite_num as u8 * 10);
+        }
+    }
+
+    #[test]
+    fn only_8_sprites_are_drawn_on_a_scanline() {
+        let mut ppu = create_rendering_ppu(0, 0x1E);
+        load_sprite_row(&mut ppu, &[(20, 1); 9]);
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 70, 21), 0x16);
+        assert_eq!(get_pixel(&frame, 80, 21), 0x0F);
+    }
+
+    #[test]
+    fn sprite_overflow_is_set_when_a_9th_sprite_is_found() {
+        let mut ppu = create_rendering_ppu(0, 0x1E);
+        load_sprite_row(&mut ppu, &[(20, 1); 8]);
+        run_frame(&mut ppu);
+        run_until(&mut ppu, 30, 0);
+        assert!(!is_sprite_overflow_set(&ppu));
+
+        load_sprite_row(&mut ppu, &[(20, 1); 9]);
+        run_frame(&mut ppu);
+        run_until(&mut ppu, 20, 0);
+        assert!(!is_sprite_overflow_set(&ppu));
+        run_until(&mut ppu, 21, 0);
+        assert!(is_sprite_overflow_set(&ppu));
+
+        // It stays set until the end of vblank
+        run_until(&mut ppu, 261, 1);
+        assert!(is_sprite_overflow_set(&ppu));
+        ppu.tick();
+        assert!(!is_sprite_overflow_set(&ppu));
+    }
+
+    #[test]
+    fn sprite_overflow_check_is_buggy() {
+        // The 9th sprite isn't on the scanline, so the 10th sprite's tile number gets checked instead of its Y position
+        let mut sprites = [(20, 1); 10];
+        sprites[8] = (100, 1);
+        sprites[9] = (100, 20);
+
+        let mut ppu = create_rendering_ppu(0, 0x1E);
+        load_sprite_row(&mut ppu, &sprites);
+        run_frame(&mut ppu);
+        run_until(&mut ppu, 21, 0);
+        assert!(is_sprite_overflow_set(&ppu));
+
+        // And the real 10th sprite is missed, since its tile number is checked instead of its Y position
+        sprites[9] = (20, 1);
+        let mut ppu = create_rendering_ppu(0, 0x1E);
+        load_sprite_row(&mut ppu, &sprites);
+        run_frame(&mut ppu);
+        run_until(&mut ppu, 30, 0);
+        assert!(!is_sprite_overflow_set(&ppu));
+    }
+
+    #[test]
+    fn removing_the_sprite_limit_draws_every_sprite_but_still_sets_overflow() {
+        let mut ppu = create_rendering_ppu(0, 0x1E);
+        ppu.set_sprite_limit_removed(true);
+        load_sprite_row(&mut ppu, &[(20, 1); 12]);
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 80, 21), 0x16);
+        assert_eq!(get_pixel(&frame, 110, 28), 0x16);
+
+        run_until(&mut ppu, 21, 0);
+        assert!(is_sprite_overflow_set(&ppu));
+    }
+
+    #[test]
+    fn the_left_edge_can_be_clipped() {
+        let mut ppu = create_rendering_ppu(0, 0x18);
+        ppu.memory.set_8_bit_value(0x2000, 1);
+        ppu.memory.set_8_bit_value(0x2001, 1);
+        let sprite = [40, 1, 0, 4];
+        for (index, value) in sprite.iter().enumerate() {
+            ppu.receive_dma(index as u8, *value);
+        }
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 7, 0), 0x0F);
+        assert_eq!(get_pixel(&frame, 8, 0), 0x21);
+        assert_eq!(get_pixel(&frame, 7, 41), 0x0F);
+        assert_eq!(get_pixel(&frame, 8, 41), 0x16);
+    }
+
+    #[test]
+    fn greyscale_and_emphasis_are_applied_to_drawn_pixels() {
+        let mut ppu = create_rendering_ppu(0, 0b1010_1011);
+        ppu.memory.set_8_bit_value(0x2000, 1);
+
+        run_frame(&mut ppu);
+        let frame = run_frame(&mut ppu);
+        assert_eq!(get_pixel(&frame, 0, 0), 0b101_10_0000); // 0x21 loses its hue
+        assert_eq!(get_pixel(&frame, 8, 0), 0b101_00_0000); // So does the 0x0F backdrop
+    }
+
+    fn write_pattern_byte(ppu: &mut PPU, value: u8) {
+        ppu.reset_high_byte_read();
+        ppu.write_to_register(0x2006, 0x01);
+        ppu.write_to_register(0x2006, 0x23);
+        ppu.write_to_register(0x2007, value);
+    }
+
+    #[test]
+    fn chr_ram_is_writable_through_2007() {
+        let mut ppu = create_test_ppu();
         unsafe {
-            ppu.scroll_register_v = 0;
-            ppu.memory.set_8_bit_value(0x23D2, 0b0110_1100);
-            let attribute_value = ppu.get_attribute_value(0x2000, 10, 8);
-            assert_eq!(attribute_value, 0x3);
+            *(ppu.ppu_mask_register as *mut u8) = 0;
         }
+        ppu.insert_cartridge(Rc::new(RefCell::new(Nrom::new(vec![0; 0x8000], vec!(), 0x2000, Mirroring::Horizontal))));
+
+        write_pattern_byte(&mut ppu, 0x42);
+        assert_eq!(ppu.memory.get_8_bit_value(0x0123), 0x42);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn chr_rom_is_read_only() {
+        let mut ppu = create_rendering_ppu(0, 0);
+        write_pattern_byte(&mut ppu, 0x42);
+        assert_eq!(ppu.memory.get_8_bit_value(0x0123), 0x00);
+    }
+}
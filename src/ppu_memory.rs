@@ -1,33 +1,42 @@
+use mapper::Cartridge as Cartridge;
+use mapper::Mirroring as Mirroring;
+
 pub struct PPUMemory {
-    memory: [u8; 0x10000]
+    memory: [u8; 0x10000],
+    // The console only has 2KB of nametable memory. Four-screen boards add another 2KB on the cartridge,
+    // which is kept here as well so every mirroring mode can be resolved the same way
+    vram: [u8; 0x1000],
+    cartridge: Option<Cartridge> // Pattern tables ($0000 - $1FFF) live on the cartridge
 }
 
-//TODO need to somehow implement memory mirroring on either the getters or the setters
-//Not sure which is better yet... probably setters tho
+const NAMETABLE_SIZE: u16 = 0x400;
 
 #[allow(dead_code)]
 impl PPUMemory {
     pub fn new() -> PPUMemory {
         return PPUMemory {
-            memory: [0; 0x10000]
+            memory: [0; 0x10000],
+            vram: [0; 0x1000],
+            cartridge: None
         }
     }
 
-    pub fn init_chr_rom(&mut self, chr_rom: Vec<u8>) {
-        if chr_rom.len() > 0x2000 {
-            panic!("This ROM isn't supported! It has too much CHR_ROM!");
-        }
-
-        // TODO this will need to be more sophisticated with a ROM that requires bank switching. Should handle this with pointers to different banks
-        // TODO can try to use https://stackoverflow.com/a/28224758 here instead of looping
-        for i in 0..chr_rom.len() {
-            let rom_byte = chr_rom[i];
-            self.memory[0x0000 + i] = rom_byte;
-        }
+    pub fn insert_cartridge(&mut self, cartridge: Cartridge) {
+        self.cartridge = Some(cartridge);
     }
 
     pub fn get_8_bit_value(&self, mut address: u16) -> u8 {
         address = self.get_non_mirrored_address(address);
+        if PPUMemory::is_pattern_table_address(address) {
+            if let Some(ref cartridge) = self.cartridge {
+                return cartridge.borrow_mut().read_chr(address);
+            }
+        }
+
+        if PPUMemory::is_nametable_address(address) {
+            return self.vram[self.get_vram_index(address)];
+        }
+
         // Anything reading from 0x3F00 by 4's, 0x3F04, 0x3F08... etc gets the backdrop color from 0x3F00
         if PPUMemory::is_palette_address(address) && address % 4 == 0 {
             return self.memory[0x3F00];
@@ -35,23 +44,26 @@ impl PPUMemory {
         return self.memory[address as usize];
     }
 
-    pub fn get_memory_range(&self, mut address: u16, num_bytes: u16) -> Vec<u8> {
-        address = self.get_non_mirrored_address(address);
-
-        let memory: &[u8] = &self.memory[(address as usize)..(address as usize + num_bytes as usize)];
-        let mut memory_copy: Vec<u8> = vec![0; num_bytes as usize];
-        memory_copy.copy_from_slice(memory);
-        return memory_copy;
+    pub fn get_memory_range(&self, address: u16, num_bytes: u16) -> Vec<u8> {
+        return (0..num_bytes).map(|offset| self.get_8_bit_value(address.wrapping_add(offset))).collect();
     }
 
     pub fn set_8_bit_value(&mut self, mut address: u16, value: u8) {
         address = self.get_non_mirrored_address(address);
-
-        self.memory[address as usize] = value;
+        if PPUMemory::is_pattern_table_address(address) {
+            if let Some(ref cartridge) = self.cartridge {
+                cartridge.borrow_mut().write_chr(address, value);
+                return;
+            }
+        }
 
         if PPUMemory::is_nametable_address(address) {
-            self.mirror_nametable_write(address, value);
+            let index = self.get_vram_index(address);
+            self.vram[index] = value;
+            return;
         }
+
+        self.memory[address as usize] = value;
     }
 
     // The PPU addresses more space than it can actually use. When operating on certain memory values, it is
@@ -75,6 +87,10 @@ impl PPUMemory {
         }
     }
 
+    fn is_pattern_table_address(address: u16) -> bool {
+        return address < 0x2000;
+    }
+
     fn is_nametable_address(address: u16) -> bool {
         return address >= 0x2000 && address < 0x3000;
     }
@@ -83,21 +99,58 @@ impl PPUMemory {
         return address >= 0x3F00 && address < 0x4000;
     }
 
-    // This is a hacky thing that is specifically in use for SMB1. This needs to be replaced with a module for
-    // mappers, with proper functionality for detecting horizontal, vertical, or other forms of nametable mirroring
-    fn mirror_nametable_write(&mut self, address: u16, value: u8) {
-        if address < 0x2800 {
-            self.memory[(address + 0x800) as usize] = value;
-        } else {
-            self.memory[(address - 0x800) as usize] = value;
+    // The cartridge decides how the 4 nametables map onto VRAM, and mappers like MMC1 and AxROM can change it at any time,
+    // so it is asked on every access rather than once when the cartridge is inserted
+    fn get_mirroring(&self) -> Mirroring {
+        return match self.cartridge {
+            Some(ref cartridge) => cartridge.borrow().get_mirroring(),
+            None => Mirroring::Horizontal
         }
     }
 
+    // Takes an address from $2000 - $2FFF and finds where it actually lives in VRAM
+    fn get_vram_index(&self, address: u16) -> usize {
+        let nametable = (address - 0x2000) / NAMETABLE_SIZE;
+        let page = match self.get_mirroring() {
+            Mirroring::Horizontal => nametable / 2,
+            Mirroring::Vertical => nametable % 2,
+            Mirroring::FourScreen => nametable,
+            Mirroring::SingleScreenLower => 0,
+            Mirroring::SingleScreenUpper => 1
+        };
+
+        return (page * NAMETABLE_SIZE + address % NAMETABLE_SIZE) as usize;
+    }
+
 }
 
 #[cfg(test)]
 mod tests {
     use ppu_memory::PPUMemory;
+    use mapper::Mapper;
+    use mapper::Mirroring;
+    use mapper::Nrom;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    fn create_memory_with_mirroring(mirroring: Mirroring) -> PPUMemory {
+        let mut memory = PPUMemory::new();
+        memory.insert_cartridge(Rc::new(RefCell::new(Nrom::new(vec![0; 0x4000], vec!(), 0x2000, mirroring))));
+        return memory;
+    }
+
+    // Writes a distinct value to each of the 4 nametables and returns what each one reads back
+    fn read_back_nametables(memory: &mut PPUMemory) -> [u8; 4] {
+        for nametable in 0..4 {
+            memory.set_8_bit_value(0x2000 + nametable * 0x400 + 0x10, nametable as u8 + 1);
+        }
+
+        let mut values = [0; 4];
+        for nametable in 0..4 {
+            values[nametable] = memory.get_8_bit_value(0x2000 + nametable as u16 * 0x400 + 0x10);
+        }
+        return values;
+    }
 
     #[test]
     fn can_get_a_stored_8_bit_value() {
@@ -128,4 +181,47 @@ mod tests {
         memory.memory[0x3F05] = 0x42;
         assert_eq!(memory.get_8_bit_value(0x3F25), 0x42);
     }
+
+    #[test]
+    fn nametables_follow_the_cartridge_mirroring() {
+        assert_eq!(read_back_nametables(&mut create_memory_with_mirroring(Mirroring::Horizontal)), [2, 2, 4, 4]);
+        assert_eq!(read_back_nametables(&mut create_memory_with_mirroring(Mirroring::Vertical)), [3, 4, 3, 4]);
+        assert_eq!(read_back_nametables(&mut create_memory_with_mirroring(Mirroring::FourScreen)), [1, 2, 3, 4]);
+        assert_eq!(read_back_nametables(&mut create_memory_with_mirroring(Mirroring::SingleScreenLower)), [4, 4, 4, 4]);
+    }
+
+    #[test]
+    fn nametable_mirrors_above_3000_are_resolved() {
+        let mut memory = create_memory_with_mirroring(Mirroring::Vertical);
+        memory.set_8_bit_value(0x2C05, 0x42);
+        assert_eq!(memory.get_8_bit_value(0x3C05), 0x42);
+        assert_eq!(memory.get_8_bit_value(0x2405), 0x42);
+    }
+
+    #[test]
+    fn mirroring_changes_made_by_the_mapper_take_effect_immediately() {
+        struct SwitchableMirroring {
+            mirroring: Mirroring
+        }
+
+        impl Mapper for SwitchableMirroring {
+            fn read_prg(&mut self, _address: u16) -> u8 { return 0; }
+            fn write_prg(&mut self, _address: u16, _value: u8) {}
+            fn read_chr(&mut self, _address: u16) -> u8 { return 0; }
+            fn write_chr(&mut self, _address: u16, _value: u8) {}
+            fn get_mirroring(&self) -> Mirroring { return self.mirroring; }
+        }
+
+        let cartridge = Rc::new(RefCell::new(SwitchableMirroring { mirroring: Mirroring::SingleScreenLower }));
+        let mut memory = PPUMemory::new();
+        memory.insert_cartridge(cartridge.clone());
+        memory.set_8_bit_value(0x2000, 0x11);
+
+        cartridge.borrow_mut().mirroring = Mirroring::SingleScreenUpper;
+        memory.set_8_bit_value(0x2000, 0x22);
+        assert_eq!(memory.get_8_bit_value(0x2C00), 0x22);
+
+        cartridge.borrow_mut().mirroring = Mirroring::SingleScreenLower;
+        assert_eq!(memory.get_8_bit_value(0x2C00), 0x11);
+    }
 }
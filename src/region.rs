@@ -0,0 +1,122 @@
+// The console region decides how fast the CPU and PPU run, and how many scanlines make up a frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Region {
+    Ntsc,
+    Pal,
+    Dendy
+}
+
+impl Region {
+    pub fn from_name(name: &str) -> Option<Region> {
+        match name.to_lowercase().as_ref() {
+            "ntsc" => Some(Region::Ntsc),
+            "pal" => Some(Region::Pal),
+            "dendy" => Some(Region::Dendy),
+            _ => None
+        }
+    }
+
+    // Guesses the region from the tags in a ROM's file name, like the "(Europe)" in "Tetris (Europe).nes". Most dumps are
+    // named after the No-Intro or GoodNES databases, which is the closest thing to a ROM database we have to go on
+    pub fn from_file_name(file_name: &str) -> Option<Region> {
+        let mut region = None;
+        let tags = file_name.split(|c| c == '(' || c == ')' || c == '[' || c == ']' || c == ',');
+        for tag in tags.map(|tag| tag.trim().to_lowercase()) {
+            match tag.as_ref() {
+                // A ROM released everywhere is almost always the NTSC version, so NTSC wins out over anything else
+                "u" | "usa" | "j" | "japan" | "ju" | "ue" | "jue" | "ntsc" | "world" => return Some(Region::Ntsc),
+                "e" | "europe" | "pal" | "a" | "australia" | "g" | "germany" | "f" | "france" | "s" | "spain" | "i" | "italy"
+                | "sw" | "sweden" | "uk" => region = Some(Region::Pal),
+                "dendy" => region = Some(Region::Dendy),
+                _ => {}
+            }
+        }
+        return region;
+    }
+
+    // In Hz. The master clock divided down by however much this region's CPU divides it
+    pub fn get_cpu_clock_rate(&self) -> f64 {
+        match *self {
+            Region::Ntsc => 1_789_773.0,
+            Region::Pal => 1_662_607.0,
+            Region::Dendy => 1_773_448.0
+        }
+    }
+
+    // How many master clock cycles go by for every CPU cycle, and for every PPU dot. On NTSC and Dendy consoles the PPU runs
+    // 3 times as fast as the CPU, but on PAL it's 3.2 times as fast
+    pub fn get_cpu_clock_divider(&self) -> u8 {
+        match *self {
+            Region::Ntsc => 12,
+            Region::Pal => 16,
+            Region::Dendy => 15
+        }
+    }
+
+    pub fn get_ppu_clock_divider(&self) -> u8 {
+        match *self {
+            Region::Ntsc => 4,
+            Region::Pal | Region::Dendy => 5
+        }
+    }
+
+    pub fn get_scanlines_per_frame(&self) -> u16 {
+        match *self {
+            Region::Ntsc => 262,
+            Region::Pal | Region::Dendy => 312
+        }
+    }
+
+    // The scanline vblank starts on. PAL has its extra scanlines in vblank, but Dendy puts most of them before it,
+    // so that games written for NTSC still have the same amount of vblank time to work with
+    pub fn get_vblank_scanline(&self) -> u16 {
+        match *self {
+            Region::Ntsc | Region::Pal => 241,
+            Region::Dendy => 291
+        }
+    }
+
+    // Only the NTSC PPU skips a dot on odd frames
+    pub fn has_short_odd_frames(&self) -> bool {
+        return *self == Region::Ntsc;
+    }
+
+    #[cfg(feature = "sdl")]
+    pub fn get_frame_rate(&self) -> f64 {
+        match *self {
+            Region::Ntsc => 60.0988,
+            Region::Pal | Region::Dendy => 50.007
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use region::Region;
+
+    #[test]
+    fn region_names_are_case_insensitive() {
+        assert_eq!(Region::from_name("NTSC"), Some(Region::Ntsc));
+        assert_eq!(Region::from_name("pal"), Some(Region::Pal));
+        assert_eq!(Region::from_name("Dendy"), Some(Region::Dendy));
+        assert_eq!(Region::from_name("secam"), None);
+    }
+
+    #[test]
+    fn region_can_be_guessed_from_file_name_tags() {
+        assert_eq!(Region::from_file_name("Super Mario Bros. (World).nes"), Some(Region::Ntsc));
+        assert_eq!(Region::from_file_name("roms/Tetris (Europe).nes"), Some(Region::Pal));
+        assert_eq!(Region::from_file_name("Kirby's Adventure (E) [!].nes"), Some(Region::Pal));
+        assert_eq!(Region::from_file_name("Mega Man (USA, Europe).nes"), Some(Region::Ntsc));
+        assert_eq!(Region::from_file_name("Contra (Dendy).nes"), Some(Region::Dendy));
+        assert_eq!(Region::from_file_name("homebrew.nes"), None);
+    }
+
+    #[test]
+    fn pal_cpu_runs_slower_compared_to_the_ppu() {
+        let ppu_dots_per_cpu_cycle = |region: Region| region.get_cpu_clock_divider() as f64 / region.get_ppu_clock_divider() as f64;
+        assert_eq!(ppu_dots_per_cpu_cycle(Region::Ntsc), 3.0);
+        assert_eq!(ppu_dots_per_cpu_cycle(Region::Pal), 3.2);
+        assert_eq!(ppu_dots_per_cpu_cycle(Region::Dendy), 3.0);
+    }
+}
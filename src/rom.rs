@@ -1,54 +1,100 @@
+use std::fmt;
 use std::fs::File;
+use std::io;
 use std::io::prelude::*;
+use std::path::Path;
 
 use rom_header::RomHeader as RomHeader;
 
+const TRAINER_SIZE: usize = 512; // Loaded into $7000 - $71FF at power on
+const PLAYCHOICE_INST_ROM_SIZE: usize = 0x2000; // The instructions the PlayChoice-10 shows on its second screen
+const PLAYCHOICE_PROM_SIZE: usize = 32; // 16 bytes of data, followed by 16 bytes of CounterOut
+
 pub struct Rom {
     pub rom_header: RomHeader,
     pub trainer: Vec<u8>,
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
+    #[allow(dead_code)] // Kept so the rest of the file lines up, but the PlayChoice-10 hardware isn't emulated
     pub playchoice_inst_rom: Vec<u8>,
+    #[allow(dead_code)] // Same as playchoice_inst_rom
     pub playchoice_prom: Vec<u8>,
 }
 
-pub fn read_file() -> Result<Rom, String> {
-    let mut buffer = vec![0; 10];
-//    let mut file = File::open("Super Mario Bros 3 (E).nes").expect("Bad things");
-    let mut file = File::open("Super Mario Bros. (JU).nes").expect("Bad things");
-//    let mut file = File::open("nestest.nes").expect("Bad things");
-//    let mut file = File::open("Zelda.nes").expect("Bad things");
-//    let mut file = File::open("Contra (USA).nes").expect("Bad things");
+#[derive(Debug)]
+pub enum RomError {
+    Io(io::Error),
+    MissingNesHeader(String), // The file doesn't start with 'N' 'E' 'S' 'EOF'
+    InvalidHeader(String),
+    Truncated { section: &'static str, expected: usize, actual: usize } // The header promised more data than the file has
+}
 
-    file.read_to_end(&mut buffer).expect("More bad things");
+impl fmt::Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RomError::Io(ref error) => write!(f, "Could not read ROM file: {}", error),
+            RomError::MissingNesHeader(ref reason) => write!(f, "Not an NES ROM: {}", reason),
+            RomError::InvalidHeader(ref reason) => write!(f, "Invalid ROM header: {}", reason),
+            RomError::Truncated { section, expected, actual } =>
+                write!(f, "ROM is truncated. Expected {} bytes of {} but only {} remain", expected, section, actual)
+        }
+    }
+}
 
-    let rom_data = buffer.as_slice(); // We no longer need the mutable vector
-    let mut current_rom_offset = find_start_of_rom_data(rom_data).expect("Bad thing dude");
+impl From<io::Error> for RomError {
+    fn from(error: io::Error) -> RomError {
+        return RomError::Io(error);
+    }
+}
 
-    let header = parse_header_struct(&rom_data[current_rom_offset..]).expect("Disaster");
+impl Rom {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Rom, RomError> {
+        let mut buffer: Vec<u8> = vec!();
+        let mut file = File::open(path)?;
+        file.read_to_end(&mut buffer)?;
 
-    current_rom_offset += 16; // 16 is the number of bytes the header takes up in the ROM
-    let prg_rom_data = parse_prg_rom_data(header.prg_rom_size, &rom_data[current_rom_offset..]);
+        return Rom::from_bytes(&buffer);
+    }
 
-    current_rom_offset += prg_rom_data.len();
-    let chr_rom_data = parse_chr_rom_data(header.chr_rom_size, &rom_data[current_rom_offset..]);
+    pub fn from_bytes(rom_data: &[u8]) -> Result<Rom, RomError> {
+        let mut current_rom_offset = find_start_of_rom_data(rom_data).map_err(RomError::MissingNesHeader)?;
 
-    if header.rom_has_trainer_data() {
-        panic!("ROMs with trainers not yet supported!");
-    }
+        let header = parse_header_struct(&rom_data[current_rom_offset..]).map_err(RomError::InvalidHeader)?;
 
-    if header.rom_has_playchoice_data() {
-        panic!("ROMs with playchoice data not yet supported!");
-    }
+        current_rom_offset += 16; // 16 is the number of bytes the header takes up in the ROM
+        let mut trainer_data = vec!();
+        if header.rom_has_trainer_data() {
+            trainer_data = read_section("trainer", TRAINER_SIZE, &rom_data[current_rom_offset..])?;
+            current_rom_offset += TRAINER_SIZE;
+        }
 
-    return Ok(Rom {
-        rom_header: header,
-        trainer: vec!(),
-        prg_rom: prg_rom_data,
-        chr_rom: chr_rom_data,
-        playchoice_inst_rom: vec!(),
-        playchoice_prom: vec!()
-    });
+        let prg_rom_data = parse_prg_rom_data(&header, &rom_data[current_rom_offset..])?;
+
+        current_rom_offset += prg_rom_data.len();
+        let chr_rom_data = parse_chr_rom_data(&header, &rom_data[current_rom_offset..])?;
+
+        current_rom_offset += chr_rom_data.len();
+        let mut playchoice_inst_rom_data = vec!();
+        let mut playchoice_prom_data = vec!();
+        if header.rom_has_playchoice_data() {
+            playchoice_inst_rom_data = read_section("PlayChoice INST-ROM", PLAYCHOICE_INST_ROM_SIZE, &rom_data[current_rom_offset..])?;
+            current_rom_offset += PLAYCHOICE_INST_ROM_SIZE;
+
+            // Plenty of dumps leave the PROM out, since it's only used for decrypting the INST-ROM
+            if rom_data.len() - current_rom_offset >= PLAYCHOICE_PROM_SIZE {
+                playchoice_prom_data = read_section("PlayChoice PROM", PLAYCHOICE_PROM_SIZE, &rom_data[current_rom_offset..])?;
+            }
+        }
+
+        return Ok(Rom {
+            rom_header: header,
+            trainer: trainer_data,
+            prg_rom: prg_rom_data,
+            chr_rom: chr_rom_data,
+            playchoice_inst_rom: playchoice_inst_rom_data,
+            playchoice_prom: playchoice_prom_data
+        });
+    }
 }
 
 fn find_start_of_rom_data(buffer: &[u8]) -> Result<usize, String> {
@@ -84,40 +130,95 @@ fn find_start_of_rom_data(buffer: &[u8]) -> Result<usize, String> {
 }
 
 fn parse_header_struct(rom_data: &[u8]) -> Result<RomHeader, String> {
-    let index = 4 as usize; // skip past the starting 'N' 'E' 'S' 'EOF'. We don't need to parse these
-    if index + 11 >= rom_data.len() {
+    if rom_data.len() < 16 {
         return Err("Rom data is not large enough to parse headers from".to_owned());
     }
 
-    for i in 7..12 {
-        if rom_data[index + i] != 0 {
-            return Err(format!("Expected byte {} to be zero but it was {}", index + i, rom_data[index + i]));
+    let mut header_data = [0; 16];
+    header_data.copy_from_slice(&rom_data[..16]);
+
+    // iNES 1.0 never used bytes 12 - 15, and some old tools wrote their name over the end of the header ("DiskDude!" being the
+    // best known). When that's happened, everything from byte 7 on is garbage, including the top half of the mapper number
+    let is_nes2_format = (header_data[7] & 0x0C) == 0x08;
+    if !is_nes2_format && header_data[12..16].iter().any(|&byte| byte != 0) {
+        for byte in header_data[7..16].iter_mut() {
+            *byte = 0;
         }
     }
 
     return Ok(RomHeader {
-        prg_rom_size: rom_data[index],
-        chr_rom_size: rom_data[index + 1],
-        flags6: rom_data[index + 2],
-        flags7: rom_data[index + 3],
-        prg_ram_size: rom_data[index + 4],
-        flags9: rom_data[index + 5],
-        flags10: rom_data[index + 6]
+        prg_rom_size: header_data[4],
+        chr_rom_size: header_data[5],
+        flags6: header_data[6],
+        flags7: header_data[7],
+        flags8: header_data[8],
+        flags9: header_data[9],
+        flags10: header_data[10],
+        flags11: header_data[11],
+        flags12: header_data[12],
+        flags13: header_data[13],
+        flags14: header_data[14],
+        flags15: header_data[15]
     });
 }
 
-fn parse_prg_rom_data(prg_rom_size: u8, rom_data: &[u8]) -> Vec<u8> {
-    let bytes_to_read: u32 = prg_rom_size as u32 * 16384; // prg_rom_size comes in as 16 kb units
-    return rom_data[..bytes_to_read as usize].to_vec();
+fn parse_prg_rom_data(header: &RomHeader, rom_data: &[u8]) -> Result<Vec<u8>, RomError> {
+    return read_section("PRG ROM", header.get_prg_rom_size(), rom_data);
 }
 
-fn parse_chr_rom_data(chr_rom_size: u8, rom_data: &[u8]) -> Vec<u8> {
-    let bytes_to_read: u32 = chr_rom_size as u32 * 8192; // chr_rom_size comes in as 8 kb units
-    return rom_data[..bytes_to_read as usize].to_vec();
+fn parse_chr_rom_data(header: &RomHeader, rom_data: &[u8]) -> Result<Vec<u8>, RomError> {
+    return read_section("CHR ROM", header.get_chr_rom_size(), rom_data);
+}
+
+fn read_section(section: &'static str, bytes_to_read: usize, rom_data: &[u8]) -> Result<Vec<u8>, RomError> {
+    if rom_data.len() < bytes_to_read {
+        return Err(RomError::Truncated { section: section, expected: bytes_to_read, actual: rom_data.len() });
+    }
+    return Ok(rom_data[..bytes_to_read].to_vec());
 }
 
 #[cfg(test)]
 mod tests {
+    use rom::Rom;
+    use rom_header::ConsoleType;
+    use region::Region;
+
+    // Builds the bytes of an iNES file with the given header flags and zero filled PRG and CHR banks
+    fn build_rom_file(prg_banks: u8, chr_banks: u8, flags6: u8, flags7: u8) -> Vec<u8> {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, prg_banks, chr_banks, flags6, flags7, 0, 0, 0, 0, 0, 0, 0, 0);
+        file.extend(vec![0xEA; prg_banks as usize * 0x4000]);
+        file.extend(vec![0x55; chr_banks as usize * 0x2000]);
+        return file;
+    }
+
+    #[test]
+    fn rom_can_be_loaded_from_bytes() {
+        let rom = Rom::from_bytes(&build_rom_file(2, 1, 0, 0)).unwrap();
+
+        assert_eq!(rom.prg_rom.len(), 0x8000);
+        assert_eq!(rom.prg_rom[0x7FFF], 0xEA);
+        assert_eq!(rom.chr_rom.len(), 0x2000);
+        assert_eq!(rom.chr_rom[0], 0x55);
+    }
+
+    #[test]
+    fn truncated_rom_is_an_error() {
+        let mut file = build_rom_file(2, 1, 0, 0);
+        file.truncate(0x5000);
+
+        match Rom::from_bytes(&file) {
+            Err(super::RomError::Truncated { section, .. }) => assert_eq!(section, "PRG ROM"),
+            _ => panic!("Expected a truncated PRG ROM error")
+        }
+    }
+
+    #[test]
+    fn missing_rom_file_is_an_error() {
+        match Rom::load("this file does not exist.nes") {
+            Err(super::RomError::Io(_)) => { },
+            _ => panic!("Expected an IO error")
+        }
+    }
     #[test]
     fn fails_on_no_nes_data() {
         let nes_data: [u8; 0] = [];
@@ -161,10 +262,17 @@ mod tests {
     }
 
     #[test]
-    fn zero_filled_header_data_required() {
-        let nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 16, 8, 2, 0, 1, 0, 0, 0, 0, 0, 0, 4]; // Last 4 should be zero filled
-        let res = super::parse_header_struct(&nes_data);
-        assert_eq!(true, res.is_err())
+    fn garbage_at_the_end_of_ines_header_is_ignored() {
+        let mut nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 16, 8, 0x12, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        nes_data[7..16].copy_from_slice(b"DiskDude!");
+        let header = super::parse_header_struct(&nes_data).unwrap();
+
+        assert_eq!(header.prg_rom_size, 16);
+        assert_eq!(header.get_mapper_number(), 1);
+        assert_eq!(header.has_battery(), true);
+        assert_eq!(header.flags7, 0);
+        assert_eq!(header.flags8, 0);
+        assert_eq!(header.flags15, 0);
     }
 
     #[test]
@@ -177,7 +285,7 @@ mod tests {
         assert_eq!(header.chr_rom_size, 8);
         assert_eq!(header.flags6, 2);
         assert_eq!(header.flags7, 0);
-        assert_eq!(header.prg_ram_size, 1);
+        assert_eq!(header.flags8, 1);
         assert_eq!(header.flags9, 0);
         assert_eq!(header.flags10, 0);
     }
@@ -204,4 +312,133 @@ mod tests {
 
         assert_eq!(header_without_trainer.get_mapper_number(), 66);
     }
+
+    #[test]
+    fn parse_region() {
+        let nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 16, 8, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+        let pal_nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 16, 8, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0];
+        let unofficial_pal_nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 16, 8, 0, 0, 1, 0, 2, 0, 0, 0, 0, 0];
+
+        assert_eq!(super::parse_header_struct(&nes_data).unwrap().get_region(), None);
+        assert_eq!(super::parse_header_struct(&pal_nes_data).unwrap().get_region(), Some(Region::Pal));
+        assert_eq!(super::parse_header_struct(&unofficial_pal_nes_data).unwrap().get_region(), Some(Region::Pal));
+    }
+
+    #[test]
+    fn parse_nes2_mapper_and_submapper() {
+        let nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 16, 8, 0x40, 0x18, 0x31, 0, 0, 0, 0, 0, 0, 0];
+        let header = super::parse_header_struct(&nes_data).unwrap();
+
+        assert_eq!(header.is_nes2_format(), true);
+        assert_eq!(header.get_mapper_number(), 0x114);
+        assert_eq!(header.get_submapper_number(), 3);
+    }
+
+    #[test]
+    fn parse_nes2_rom_sizes() {
+        let nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 0x02, 0x01, 0, 0x08, 0, 0x11, 0, 0, 0, 0, 0, 0];
+        let header = super::parse_header_struct(&nes_data).unwrap();
+        assert_eq!(header.get_prg_rom_size(), 0x102 * 0x4000);
+        assert_eq!(header.get_chr_rom_size(), 0x101 * 0x2000);
+
+        // 2^5 * 3 bytes of PRG ROM and 2^13 * 1 bytes of CHR ROM
+        let exponent_nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, (5 << 2) | 1, 13 << 2, 0, 0x08, 0, 0xFF, 0, 0, 0, 0, 0, 0];
+        let exponent_header = super::parse_header_struct(&exponent_nes_data).unwrap();
+        assert_eq!(exponent_header.get_prg_rom_size(), 96);
+        assert_eq!(exponent_header.get_chr_rom_size(), 0x2000);
+    }
+
+    #[test]
+    fn parse_nes2_ram_sizes() {
+        let nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 2, 0, 0, 0x08, 0, 0, 0x70, 0x07, 0, 0, 0, 0];
+        let header = super::parse_header_struct(&nes_data).unwrap();
+
+        assert_eq!(header.get_prg_ram_size(), 0);
+        assert_eq!(header.get_prg_nvram_size(), 0x2000);
+        assert_eq!(header.get_chr_ram_size(), 0x2000);
+        assert_eq!(header.get_chr_nvram_size(), 0);
+    }
+
+    #[test]
+    fn ines_ram_sizes_are_inferred() {
+        let nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let battery_nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 2, 1, 2, 0, 2, 0, 0, 0, 0, 0, 0, 0];
+        let header = super::parse_header_struct(&nes_data).unwrap();
+        let battery_header = super::parse_header_struct(&battery_nes_data).unwrap();
+
+        assert_eq!(header.get_prg_ram_size(), 0x2000);
+        assert_eq!(header.get_prg_nvram_size(), 0);
+        assert_eq!(header.get_chr_ram_size(), 0x2000);
+        assert_eq!(battery_header.get_prg_ram_size(), 0);
+        assert_eq!(battery_header.get_prg_nvram_size(), 0x4000);
+        assert_eq!(battery_header.get_chr_ram_size(), 0);
+    }
+
+    #[test]
+    fn parse_nes2_timing_console_type_and_expansion_device() {
+        let nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 2, 1, 0, 0x0B, 0, 0, 0, 0, 3, 0x05, 0, 0x08];
+        let header = super::parse_header_struct(&nes_data).unwrap();
+        assert_eq!(header.get_region(), Some(Region::Dendy));
+        assert_eq!(header.get_console_type(), ConsoleType::Extended(5));
+        assert_eq!(header.get_default_expansion_device(), 8);
+
+        let multi_region_nes_data: [u8; 16] = ['N' as u8, 'E' as u8, 'S' as u8, 0x1A, 2, 1, 0, 0x09, 0, 0, 0, 0, 2, 0, 0, 1];
+        let multi_region_header = super::parse_header_struct(&multi_region_nes_data).unwrap();
+        assert_eq!(multi_region_header.get_region(), None);
+        assert_eq!(multi_region_header.get_console_type(), ConsoleType::VsSystem);
+        assert_eq!(multi_region_header.get_default_expansion_device(), 1);
+    }
+
+    #[test]
+    fn nes2_rom_can_be_loaded_from_bytes() {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 2, 0, 0, 0x08, 0, 0, 0x07, 0x07, 0x01, 0, 0, 0);
+        file.extend(vec![0xEA; 0x8000]);
+        let rom = Rom::from_bytes(&file).unwrap();
+
+        assert_eq!(rom.prg_rom.len(), 0x8000);
+        assert_eq!(rom.chr_rom.len(), 0);
+        assert_eq!(rom.rom_header.get_region(), Some(Region::Pal));
+    }
+
+    #[test]
+    fn trainer_comes_before_prg_rom() {
+        let mut file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 1, 0, 0b0100, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+        file.extend(vec![0x77; 512]);
+        file.extend(vec![0xEA; 0x4000]);
+        let rom = Rom::from_bytes(&file).unwrap();
+
+        assert_eq!(rom.trainer, vec![0x77; 512]);
+        assert_eq!(rom.prg_rom, vec![0xEA; 0x4000]);
+    }
+
+    #[test]
+    fn playchoice_sections_come_after_chr_rom() {
+        let mut file = build_rom_file(1, 1, 0, 0b10);
+        file.extend(vec![0x11; 0x2000]);
+        file.extend(vec![0x22; 32]);
+        let rom = Rom::from_bytes(&file).unwrap();
+
+        assert_eq!(rom.chr_rom, vec![0x55; 0x2000]);
+        assert_eq!(rom.playchoice_inst_rom, vec![0x11; 0x2000]);
+        assert_eq!(rom.playchoice_prom, vec![0x22; 32]);
+    }
+
+    #[test]
+    fn playchoice_prom_is_optional() {
+        let mut file = build_rom_file(1, 1, 0, 0b10);
+        file.extend(vec![0x11; 0x2000]);
+        let rom = Rom::from_bytes(&file).unwrap();
+
+        assert_eq!(rom.playchoice_inst_rom.len(), 0x2000);
+        assert_eq!(rom.playchoice_prom.len(), 0);
+    }
+
+    #[test]
+    fn truncated_trainer_is_an_error() {
+        let file: Vec<u8> = vec!('N' as u8, 'E' as u8, 'S' as u8, 0x1A, 1, 0, 0b0100, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+        match Rom::from_bytes(&file) {
+            Err(super::RomError::Truncated { section, .. }) => assert_eq!(section, "trainer"),
+            _ => panic!("Expected a truncated trainer error")
+        }
+    }
 }
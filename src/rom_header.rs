@@ -1,25 +1,207 @@
+use region::Region;
+
+// Bytes 4 - 15 of the header. iNES 1.0 only gave meaning to the first few of these, and NES 2.0 reuses the rest
+// (along with bytes 8 - 10) to describe the cartridge in more detail. See is_nes2_format for which one a ROM is using
 pub struct RomHeader {
-     pub prg_rom_size: u8, // Size of PRG ROM in 16 KB units (this is program code)
+     pub prg_rom_size: u8, // Size of PRG ROM in 16 KB units (this is program code). NES 2.0 adds more bits in flags9
      pub chr_rom_size: u8, // Size of CHR ROM in 8 KB units (Value 0 means the board uses CHR RAM) (this is graphical information)
      pub flags6: u8,
      pub flags7: u8,
-     pub prg_ram_size: u8, // Size of PRG RAM in 8 KB units (Value 0 infers 8 KB for compatibility; see PRG RAM circuit)
-     pub flags9: u8,
-     pub flags10: u8 // unofficial flags
+     pub flags8: u8, // iNES 1.0: Size of PRG RAM in 8 KB units. NES 2.0: Upper bits of the mapper number, and the submapper number
+     pub flags9: u8, // iNES 1.0: TV system. NES 2.0: Upper bits of the PRG and CHR ROM sizes
+     pub flags10: u8, // iNES 1.0: Unofficial flags. NES 2.0: PRG RAM and PRG NVRAM sizes
+     pub flags11: u8, // NES 2.0: CHR RAM and CHR NVRAM sizes
+     pub flags12: u8, // NES 2.0: CPU/PPU timing
+     pub flags13: u8, // NES 2.0: Vs. System hardware, or the extended console type
+     pub flags14: u8, // NES 2.0: Number of miscellaneous ROMs
+     pub flags15: u8 // NES 2.0: Default expansion device
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConsoleType {
+    Nes, // Also covers the Famicom and Dendy
+    VsSystem,
+    PlayChoice10,
+    Extended(u8) // One of the less common consoles (Famiclones, VT01 and friends). NES 2.0 only
 }
 
 impl RomHeader {
+    // NES 2.0 headers set bits 2 - 3 of flags7 to 2. iNES 1.0 headers leave them clear
+    pub fn is_nes2_format(&self) -> bool {
+        return (&self.flags7 & 0x0C) == 0x08;
+    }
+
     pub fn rom_has_trainer_data(&self) -> bool {
         return (&self.flags6 & 4) == 4;
     }
 
+    // PlayChoice-10 dumps have the INST-ROM (and usually the PROM) after the CHR ROM
     pub fn rom_has_playchoice_data(&self) -> bool {
-        return (&self.flags7 & 2) == 2;
+        return self.get_console_type() == ConsoleType::PlayChoice10;
+    }
+
+    // Bit 1 of flags6. When set, the cartridge keeps its PRG RAM (or other memory) alive with a battery
+    pub fn has_battery(&self) -> bool {
+        return (&self.flags6 & 2) == 2;
+    }
+
+    // Bit 0 of flags6. 0 is horizontal mirroring, 1 is vertical mirroring
+    pub fn has_vertical_mirroring(&self) -> bool {
+        return (&self.flags6 & 1) == 1;
+    }
+
+    // Bit 3 of flags6. When set, the cartridge provides extra VRAM and the mirroring bit is ignored
+    pub fn has_four_screen_vram(&self) -> bool {
+        return (&self.flags6 & 8) == 8;
+    }
+
+    // 8 bits for iNES 1.0, and 12 bits for NES 2.0
+    pub fn get_mapper_number(&self) -> u16 {
+        let mapper_number = ((&self.flags6 >> 4) + (&self.flags7 & 0xF0)) as u16;
+        if self.is_nes2_format() {
+            return mapper_number + ((&self.flags8 & 0x0F) as u16) * 0x100;
+        }
+        return mapper_number;
+    }
+
+    // Tells apart boards that share a mapper number but behave differently. 0 when the ROM doesn't say
+    #[allow(dead_code)] // None of the supported mappers need it yet
+    pub fn get_submapper_number(&self) -> u8 {
+        if self.is_nes2_format() {
+            return &self.flags8 >> 4;
+        }
+        return 0;
+    }
+
+    // In bytes
+    pub fn get_prg_rom_size(&self) -> usize {
+        if self.is_nes2_format() {
+            return get_nes2_rom_size(self.prg_rom_size, &self.flags9 & 0x0F, 0x4000);
+        }
+        return self.prg_rom_size as usize * 0x4000;
+    }
+
+    // In bytes
+    pub fn get_chr_rom_size(&self) -> usize {
+        if self.is_nes2_format() {
+            return get_nes2_rom_size(self.chr_rom_size, &self.flags9 >> 4, 0x2000);
+        }
+        return self.chr_rom_size as usize * 0x2000;
+    }
+
+    // In bytes. iNES 1.0 can't tell RAM from NVRAM, so whatever it has counts as NVRAM if there's a battery
+    #[allow(dead_code)] // None of the supported mappers need it yet
+    pub fn get_prg_ram_size(&self) -> usize {
+        if self.is_nes2_format() {
+            return get_nes2_ram_size(&self.flags10 & 0x0F);
+        } else if self.has_battery() {
+            return 0;
+        }
+        return self.get_ines_prg_ram_size();
     }
 
-    #[allow(dead_code)]
-    pub fn get_mapper_number(&self) -> u8 {
-        return (&self.flags6 >> 4) + (&self.flags7 & 0xF0);
+    // In bytes. This is the battery backed PRG RAM that games keep their saves in
+    #[allow(dead_code)] // None of the supported mappers need it yet
+    pub fn get_prg_nvram_size(&self) -> usize {
+        if self.is_nes2_format() {
+            return get_nes2_ram_size(&self.flags10 >> 4);
+        } else if self.has_battery() {
+            return self.get_ines_prg_ram_size();
+        }
+        return 0;
+    }
+
+    // In bytes. iNES 1.0 boards without any CHR ROM have 8 KB of CHR RAM instead
+    pub fn get_chr_ram_size(&self) -> usize {
+        if self.is_nes2_format() {
+            return get_nes2_ram_size(&self.flags11 & 0x0F);
+        } else if self.chr_rom_size == 0 {
+            return 0x2000;
+        }
+        return 0;
+    }
+
+    // In bytes
+    pub fn get_chr_nvram_size(&self) -> usize {
+        if self.is_nes2_format() {
+            return get_nes2_ram_size(&self.flags11 >> 4);
+        }
+        return 0;
+    }
+
+    // 0 in flags8 infers 8 KB for compatibility with ROMs dumped before the byte was used
+    fn get_ines_prg_ram_size(&self) -> usize {
+        return (if self.flags8 == 0 { 1 } else { self.flags8 as usize }) * 0x2000;
+    }
+
+    // NES 2.0 stores the CPU/PPU timing in bits 0 - 1 of flags12 (and None means the game runs on any region).
+    // For iNES 1.0, bit 0 of flags9 (or bits 0 - 1 of the unofficial flags10 being 2) marks a PAL ROM. Most ROMs leave both
+    // clear no matter where they're from, so that's taken as not knowing rather than as NTSC
+    pub fn get_region(&self) -> Option<Region> {
+        if self.is_nes2_format() {
+            match &self.flags12 & 3 {
+                0 => return Some(Region::Ntsc),
+                1 => return Some(Region::Pal),
+                2 => return None,
+                _ => return Some(Region::Dendy)
+            }
+        }
+
+        if (&self.flags9 & 1) == 1 || (&self.flags10 & 3) == 2 {
+            return Some(Region::Pal);
+        }
+        return None;
+    }
+
+    // Bits 0 - 1 of flags7. For NES 2.0, type 3 means the actual console type is in flags13
+    pub fn get_console_type(&self) -> ConsoleType {
+        match &self.flags7 & 3 {
+            0 => return ConsoleType::Nes,
+            1 => return ConsoleType::VsSystem,
+            2 => return ConsoleType::PlayChoice10,
+            _ => {
+                if self.is_nes2_format() {
+                    return ConsoleType::Extended(&self.flags13 & 0x0F);
+                }
+                return ConsoleType::Nes;
+            }
+        }
+    }
+
+    // Extra ROMs that come after the CHR ROM, for boards with chips that have their own ROM
+    #[allow(dead_code)] // None of the supported boards have any
+    pub fn get_misc_rom_count(&self) -> u8 {
+        if self.is_nes2_format() {
+            return &self.flags14 & 3;
+        }
+        return 0;
+    }
+
+    // The NES 2.0 number of whatever the game expects to be plugged into the controller ports (1 is the standard controllers).
+    // 0 means the ROM doesn't say
+    pub fn get_default_expansion_device(&self) -> u8 {
+        if self.is_nes2_format() {
+            return &self.flags15 & 0x3F;
+        }
+        return 0;
     }
 }
 
+// When the upper bits are all set, the lower byte holds an exponent and a multiplier (EEEEEEMM) instead of a count of units.
+// That allows for sizes that aren't a multiple of the usual unit size
+fn get_nes2_rom_size(size_lsb: u8, size_msb: u8, unit_size: usize) -> usize {
+    if size_msb == 0x0F {
+        let exponent = (size_lsb >> 2) as u32;
+        let multiplier = (size_lsb & 3) as usize * 2 + 1;
+        return 2usize.saturating_pow(exponent).saturating_mul(multiplier); // Saturating so silly headers end up as truncated ROMs
+    }
+    return ((size_msb as usize) * 0x100 + size_lsb as usize) * unit_size;
+}
+
+// RAM sizes are stored as shift counts. 0 means there's none, otherwise it's 64 bytes shifted left by the count
+fn get_nes2_ram_size(shift_count: u8) -> usize {
+    if shift_count == 0 {
+        return 0;
+    }
+    return 64 << shift_count;
+}